@@ -0,0 +1,389 @@
+/*
+ * Windows 以外向けの DxLib_x64 のスタブ
+ *
+ * src/dxlib.rs で使用している dx_* 関数を最低限の動作で実装し、
+ * Linux の CI 等でリンク、テストを行えるようにする。
+ * ウインドウは存在しないため、メインループが1フレームで終わるように
+ * キーは常に押されている扱いにする。
+ */
+#include <stdarg.h>
+#include <stdio.h>
+#include <string.h>
+
+typedef struct {
+    int left;
+    int top;
+    int right;
+    int bottom;
+} RECT;
+
+#define STUB_MAX_FILE 64
+#define STUB_MAX_HANDLE 4096
+
+static int g_initialized = 0;
+static int g_next_handle = 1;
+static FILE *g_files[STUB_MAX_FILE];
+/* 読み込み済みのグラフィック、サウンドのハンドル */
+static unsigned char g_graphs[STUB_MAX_HANDLE];
+static unsigned char g_sounds[STUB_MAX_HANDLE];
+
+int dx_DxLib_Init(void) {
+    if (g_initialized) {
+        return -1;
+    }
+    g_initialized = 1;
+    return 0;
+}
+
+int dx_DxLib_End(void) {
+    if (!g_initialized) {
+        return -1;
+    }
+    g_initialized = 0;
+    return 0;
+}
+
+/* SetHookWinProc で登録されたウインドウプロシージャ (ProcessMessage で WM_NULL を渡して呼び出す) */
+typedef long long (*STUB_WNDPROC)(void *hwnd, unsigned int msg, unsigned long long wparam, long long lparam);
+static STUB_WNDPROC g_hook_win_proc = NULL;
+/* グラフィックスデバイスのロスト、復元時のコールバック (ロストは発生しないため呼び出さない) */
+static void (*g_restore_graph_callback)(void) = NULL;
+static void (*g_device_lost_callback)(void *data) = NULL;
+static void *g_device_lost_callback_data = NULL;
+
+int dx_ProcessMessage(void) {
+    if (g_hook_win_proc != NULL) {
+        g_hook_win_proc(NULL, 0, 0, 0);
+    }
+    return 0;
+}
+
+int dx_SetHookWinProc(STUB_WNDPROC win_proc) {
+    g_hook_win_proc = win_proc;
+    return 0;
+}
+
+int dx_SetRestoreGraphCallback(void (*callback)(void)) {
+    g_restore_graph_callback = callback;
+    return 0;
+}
+
+int dx_SetGraphicsDeviceLostCallbackFunction(void (*callback)(void *data), void *callback_data) {
+    g_device_lost_callback = callback;
+    g_device_lost_callback_data = callback_data;
+    return 0;
+}
+
+int dx_ScreenFlip(void) { return 0; }
+int dx_SetDrawScreen(int draw_screen) { (void)draw_screen; return 0; }
+int dx_ClearDrawScreen(RECT *clear_rect) { (void)clear_rect; return 0; }
+int dx_ChangeWindowMode(int flag) { (void)flag; return 0; }
+int dx_SetMainWindowText(const char *window_text) { (void)window_text; return 0; }
+int dx_SetUseCharCodeFormat(int char_code_format) { (void)char_code_format; return 0; }
+int dx_SetUseASyncLoadFlag(int flag) { (void)flag; return 0; }
+int dx_SetOutApplicationLogValidFlag(int flag) { (void)flag; return 0; }
+int dx_SetApplicationLogSaveDirectory(const char *dir_path) { (void)dir_path; return 0; }
+int dx_SetWaitVSyncFlag(int flag) { (void)flag; return 0; }
+
+/* SetGraphMode で設定された画面の大きさ (GetScreenState で返す) */
+static int g_screen_size_x = 640;
+static int g_screen_size_y = 480;
+static int g_color_bit_depth = 32;
+
+/* DX_CHANGESCREEN_OK */
+int dx_SetGraphMode(int screen_size_x, int screen_size_y, int color_bit_depth, int refresh_rate) {
+    (void)refresh_rate;
+    g_screen_size_x = screen_size_x;
+    g_screen_size_y = screen_size_y;
+    g_color_bit_depth = color_bit_depth;
+    return 0;
+}
+
+/* KEY_INPUT_ESCAPE */
+int dx_WaitKey(void) { return 1; }
+int dx_CheckHitKey(int key_code) { (void)key_code; return 1; }
+
+int dx_GetMousePoint(int *x_buf, int *y_buf) {
+    *x_buf = 0;
+    *y_buf = 0;
+    return 0;
+}
+
+int dx_GetJoypadAnalogInput(int *x_buf, int *y_buf, int input_type) {
+    (void)input_type;
+    *x_buf = 0;
+    *y_buf = 0;
+    return 0;
+}
+
+int dx_KeyInputString(int x, int y, int char_max_length, char *str_buffer, int cancel_valid_flag) {
+    (void)x;
+    (void)y;
+    (void)cancel_valid_flag;
+    if (char_max_length > 0) {
+        str_buffer[0] = '\0';
+    }
+    return 1;
+}
+
+int dx_GetColor(int red, int green, int blue) {
+    return ((red & 0xff) << 16) | ((green & 0xff) << 8) | (blue & 0xff);
+}
+
+int dx_DrawString(int x, int y, const char *string, int color) {
+    (void)x;
+    (void)y;
+    (void)string;
+    (void)color;
+    return 0;
+}
+
+/* 図形描画関数、GetNowCount、SetWindowText は dxlib-rs-gen で生成した関数のテストで使用する */
+int dx_DrawLine(int x1, int y1, int x2, int y2, unsigned int color, int thickness) {
+    (void)x1;
+    (void)y1;
+    (void)x2;
+    (void)y2;
+    (void)color;
+    return thickness > 0 ? 0 : -1;
+}
+
+int dx_DrawBox(int x1, int y1, int x2, int y2, unsigned int color, int fill_flag) {
+    (void)color;
+    (void)fill_flag;
+    return x1 <= x2 && y1 <= y2 ? 0 : -1;
+}
+
+int dx_DrawCircle(int x, int y, int r, unsigned int color, int fill_flag, int line_thickness) {
+    (void)x;
+    (void)y;
+    (void)color;
+    (void)fill_flag;
+    return r >= 0 && line_thickness > 0 ? 0 : -1;
+}
+
+/* 呼び出しごとに 1 ミリ秒進むカウンタ */
+int dx_GetNowCount(int use_rdtsc_flag) {
+    static int count = 0;
+    (void)use_rdtsc_flag;
+    return ++count;
+}
+
+int dx_SetWindowText(const char *window_text) {
+    (void)window_text;
+    return 0;
+}
+
+/* 書式指定の関数は、書式化した文字列の長さを返す (%s が展開されたことをテストで確認する) */
+static int stub_format_length(const char *format_string, va_list args) {
+    return vsnprintf(NULL, 0, format_string, args);
+}
+
+int dx_printfDx(const char *format_string, ...) {
+    va_list args;
+    int len;
+    va_start(args, format_string);
+    len = stub_format_length(format_string, args);
+    va_end(args);
+    return len;
+}
+
+int dx_DrawFormatString(int x, int y, int color, const char *format_string, ...) {
+    va_list args;
+    int len;
+    (void)x;
+    (void)y;
+    (void)color;
+    va_start(args, format_string);
+    len = stub_format_length(format_string, args);
+    va_end(args);
+    return len;
+}
+
+int dx_DrawFormatStringToHandle(int x, int y, int color, int font_handle, const char *format_string, ...) {
+    va_list args;
+    int len;
+    (void)x;
+    (void)y;
+    (void)color;
+    if (font_handle < 0) {
+        return -1;
+    }
+    va_start(args, format_string);
+    len = stub_format_length(format_string, args);
+    va_end(args);
+    return len;
+}
+
+int dx_GetScreenState(int *size_x, int *size_y, int *color_bit_depth) {
+    *size_x = g_screen_size_x;
+    *size_y = g_screen_size_y;
+    *color_bit_depth = g_color_bit_depth;
+    return 0;
+}
+
+/* 1文字 8x16 ドットとして計算する */
+int dx_GetDrawStringSize(int *size_x, int *size_y, int *line_count, const char *string, int str_len, int vertical_flag) {
+    (void)string;
+    (void)vertical_flag;
+    *size_x = str_len * 8;
+    *size_y = 16;
+    *line_count = 1;
+    return 0;
+}
+
+static int stub_load(const char *file_name, unsigned char *handles) {
+    FILE *fp = fopen(file_name, "rb");
+    if (fp == NULL || g_next_handle >= STUB_MAX_HANDLE) {
+        if (fp != NULL) {
+            fclose(fp);
+        }
+        return -1;
+    }
+    fclose(fp);
+    handles[g_next_handle] = 1;
+    return g_next_handle++;
+}
+
+static int stub_delete(int handle, unsigned char *handles) {
+    if (handle <= 0 || handle >= STUB_MAX_HANDLE || !handles[handle]) {
+        return -1;
+    }
+    handles[handle] = 0;
+    return 0;
+}
+
+int dx_LoadGraph(const char *file_name) { return stub_load(file_name, g_graphs); }
+int dx_LoadSoundMem(const char *file_name) { return stub_load(file_name, g_sounds); }
+
+int dx_DeleteGraph(int gr_handle, int log_out_flag) {
+    (void)log_out_flag;
+    return stub_delete(gr_handle, g_graphs);
+}
+
+int dx_DeleteSoundMem(int sound_handle, int log_out_flag) {
+    (void)log_out_flag;
+    return stub_delete(sound_handle, g_sounds);
+}
+
+int dx_GetGraphNum(void) {
+    int num = 0;
+    for (int i = 0; i < STUB_MAX_HANDLE; i++) {
+        num += g_graphs[i];
+    }
+    return num;
+}
+
+static int g_blend_mode = 0;
+static int g_blend_param = 255;
+
+int dx_SetDrawBlendMode(int blend_mode, int blend_param) {
+    g_blend_mode = blend_mode;
+    g_blend_param = blend_param;
+    return 0;
+}
+
+int dx_GetDrawBlendMode(int *blend_mode, int *blend_param) {
+    *blend_mode = g_blend_mode;
+    *blend_param = g_blend_param;
+    return 0;
+}
+
+int dx_DrawGraph(int x, int y, int gr_handle, int trans_flag) {
+    (void)x;
+    (void)y;
+    (void)trans_flag;
+    return gr_handle > 0 ? 0 : -1;
+}
+
+/* グラフィックは全て 32x32 として扱う */
+int dx_GetGraphSize(int gr_handle, int *size_x, int *size_y) {
+    if (gr_handle <= 0) {
+        return -1;
+    }
+    *size_x = 32;
+    *size_y = 32;
+    return 0;
+}
+
+int dx_PlaySoundMem(int sound_handle, int play_type, int top_position_flag) {
+    (void)play_type;
+    (void)top_position_flag;
+    return sound_handle > 0 ? 0 : -1;
+}
+
+static FILE *stub_file(int file_handle) {
+    if (file_handle < 1 || file_handle > STUB_MAX_FILE) {
+        return NULL;
+    }
+    return g_files[file_handle - 1];
+}
+
+int dx_FileRead_open(const char *file_path, int async) {
+    (void)async;
+    for (int i = 0; i < STUB_MAX_FILE; i++) {
+        if (g_files[i] == NULL) {
+            g_files[i] = fopen(file_path, "rb");
+            return g_files[i] != NULL ? i + 1 : -1;
+        }
+    }
+    return -1;
+}
+
+long dx_FileRead_size(const char *file_path) {
+    FILE *fp = fopen(file_path, "rb");
+    long size;
+    if (fp == NULL) {
+        return -1;
+    }
+    fseek(fp, 0, SEEK_END);
+    size = ftell(fp);
+    fclose(fp);
+    return size;
+}
+
+int dx_FileRead_close(int file_handle) {
+    FILE *fp = stub_file(file_handle);
+    if (fp == NULL) {
+        return -1;
+    }
+    fclose(fp);
+    g_files[file_handle - 1] = NULL;
+    return 0;
+}
+
+long dx_FileRead_tell(int file_handle) {
+    FILE *fp = stub_file(file_handle);
+    return fp != NULL ? ftell(fp) : -1;
+}
+
+int dx_FileRead_seek(int file_handle, long offset, int origin) {
+    FILE *fp = stub_file(file_handle);
+    if (fp == NULL) {
+        return -1;
+    }
+    return fseek(fp, offset, origin) == 0 ? 0 : -1;
+}
+
+int dx_FileRead_read(void *buffer, int read_size, int file_handle) {
+    FILE *fp = stub_file(file_handle);
+    if (fp == NULL || read_size < 0) {
+        return -1;
+    }
+    fread(buffer, 1, (size_t)read_size, fp);
+    return 0;
+}
+
+/* DxLib と同様に末尾の改行はバッファに含めない */
+int dx_FileRead_gets(char *buffer, int num, int file_handle) {
+    FILE *fp = stub_file(file_handle);
+    size_t len;
+    if (fp == NULL || num <= 0 || fgets(buffer, num, fp) == NULL) {
+        return -1;
+    }
+    len = strlen(buffer);
+    while (len > 0 && (buffer[len - 1] == '\n' || buffer[len - 1] == '\r')) {
+        buffer[--len] = '\0';
+    }
+    return (int)len;
+}
@@ -1,6 +1,41 @@
 use std::env;
+use std::path::Path;
 
 fn main() {
-    let out_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    println!("cargo:rustc-link-search=native={}\\DxLib", out_dir);
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+
+    if target_os == "windows" {
+        // DxLib_x64.lib (インポートライブラリ) を参照する
+        println!(
+            "cargo:rustc-link-search=native={}",
+            Path::new(&manifest_dir).join("DxLib").display()
+        );
+    } else {
+        // Windows 以外では DxLib が存在しないため、C のスタブを libDxLib_x64.a としてビルドする
+        // リンク自体は dxlib_gen! が生成する #[link(name = "DxLib_x64")] で行われる
+        let out_dir = env::var("OUT_DIR").unwrap();
+        println!("cargo:rerun-if-changed=DxLib/stub/dxlib_stub.c");
+        cc::Build::new()
+            .file("DxLib/stub/dxlib_stub.c")
+            .cargo_metadata(false)
+            .compile("DxLib_x64");
+        println!("cargo:rustc-link-search=native={}", out_dir);
+
+        // 動的ロードのテスト用に、同じスタブを共有ライブラリとしてもビルドする
+        // (リンク検索パスと別のディレクトリに置き、静的リンクの方で拾われないようにする)
+        let dylib_dir = Path::new(&out_dir).join("dylib");
+        std::fs::create_dir_all(&dylib_dir).unwrap();
+        let dylib_path = dylib_dir.join("libDxLib_x64.so");
+        let compiler = cc::Build::new().pic(true).get_compiler();
+        let status = compiler
+            .to_command()
+            .args(["-shared", "-o"])
+            .arg(&dylib_path)
+            .arg("DxLib/stub/dxlib_stub.c")
+            .status()
+            .unwrap();
+        assert!(status.success(), "failed to build {}", dylib_path.display());
+        println!("cargo:rustc-env=DXLIB_STUB_DYLIB={}", dylib_path.display());
+    }
 }
@@ -0,0 +1,319 @@
+// src/coverage.rs
+// DxLib_x64.def のエクスポートと dxlib_gen! で宣言済みの関数の比較
+//  cargo run -p dxlib-rs-gen -- coverage --source src/dxlib.rs [--format json]
+//  ・missing : DLL にあるが宣言されていない関数 (宣言済みの関数の A/W 版の場合は variant_of)
+//  ・extra : 宣言されているが DLL にない関数 (綴りの誤りと思われる場合は suggestion、A/W 版の場合は kind = "variant")
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Missing {
+    pub name: String,
+    // 宣言済みの関数の A/W 版 (DrawStringW -> DrawString)
+    pub variant_of: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraKind {
+    // DLL に近い名前の関数がある
+    Misspelled,
+    // A/W の有無のみが異なる関数が DLL にある
+    Variant,
+    Unknown,
+}
+
+impl ExtraKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ExtraKind::Misspelled => "misspelled",
+            ExtraKind::Variant => "variant",
+            ExtraKind::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extra {
+    pub name: String,
+    pub kind: ExtraKind,
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub exports: usize,
+    pub covered: usize,
+    pub missing: Vec<Missing>,
+    pub extra: Vec<Extra>,
+}
+
+// 末尾の A/W を除いた名前 (小文字で終わる名前の後の A/W のみ、DrawStringW -> DrawString)
+fn strip_variant(name: &str) -> Option<&str> {
+    let base = name.strip_suffix(['A', 'W'])?;
+    base.ends_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        .then_some(base)
+}
+
+// A/W の有無のみが異なる名前が names にあれば返す
+fn find_variant<'a>(name: &str, names: &'a BTreeSet<String>) -> Option<&'a String> {
+    let base = strip_variant(name).unwrap_or(name);
+    [base.to_string(), format!("{}A", base), format!("{}W", base)]
+        .into_iter()
+        .filter(|candidate| candidate != name)
+        .find_map(|candidate| names.get(&candidate))
+}
+
+// 大文字小文字を区別しない編集距離
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let b: Vec<char> = b.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// 編集距離が 2 以下で最も近い名前
+fn find_misspelling<'a>(name: &str, names: &'a BTreeSet<String>) -> Option<&'a String> {
+    names
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+pub fn coverage_report(exports: &BTreeSet<String>, declared: &BTreeSet<String>) -> CoverageReport {
+    let missing = exports
+        .difference(declared)
+        .map(|name| Missing {
+            name: name.clone(),
+            variant_of: find_variant(name, declared).cloned(),
+        })
+        .collect();
+    let extra = declared
+        .difference(exports)
+        .map(|name| {
+            let (kind, suggestion) = if let Some(variant) = find_variant(name, exports) {
+                (ExtraKind::Variant, Some(variant.clone()))
+            } else if let Some(candidate) = find_misspelling(name, exports) {
+                (ExtraKind::Misspelled, Some(candidate.clone()))
+            } else {
+                (ExtraKind::Unknown, None)
+            };
+            Extra {
+                name: name.clone(),
+                kind,
+                suggestion,
+            }
+        })
+        .collect();
+    CoverageReport {
+        exports: exports.len(),
+        covered: exports.intersection(declared).count(),
+        missing,
+        extra,
+    }
+}
+
+impl CoverageReport {
+    pub fn percent(&self) -> f64 {
+        if self.exports == 0 {
+            return 100.0;
+        }
+        self.covered as f64 * 100.0 / self.exports as f64
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(
+            text,
+            "coverage: {}/{} ({:.1}%)",
+            self.covered,
+            self.exports,
+            self.percent()
+        );
+        if !self.extra.is_empty() {
+            let _ = writeln!(text, "\nextra ({}):", self.extra.len());
+            for extra in &self.extra {
+                match &extra.suggestion {
+                    Some(suggestion) => {
+                        let _ = writeln!(
+                            text,
+                            "  {} ({}, did you mean {}?)",
+                            extra.name,
+                            extra.kind.as_str(),
+                            suggestion
+                        );
+                    }
+                    None => {
+                        let _ = writeln!(text, "  {} (not exported)", extra.name);
+                    }
+                }
+            }
+        }
+        if !self.missing.is_empty() {
+            let _ = writeln!(text, "\nmissing ({}):", self.missing.len());
+            for missing in &self.missing {
+                match &missing.variant_of {
+                    Some(base) => {
+                        let _ = writeln!(text, "  {} (variant of {})", missing.name, base);
+                    }
+                    None => {
+                        let _ = writeln!(text, "  {}", missing.name);
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        let string = |value: &str| format!("\"{}\"", json_escape(value));
+        let optional = |value: &Option<String>| value.as_deref().map_or("null".to_string(), string);
+        let missing: Vec<String> = self
+            .missing
+            .iter()
+            .map(|missing| {
+                format!(
+                    "    {{ \"name\": {}, \"variant_of\": {} }}",
+                    string(&missing.name),
+                    optional(&missing.variant_of)
+                )
+            })
+            .collect();
+        let extra: Vec<String> = self
+            .extra
+            .iter()
+            .map(|extra| {
+                format!(
+                    "    {{ \"name\": {}, \"kind\": {}, \"suggestion\": {} }}",
+                    string(&extra.name),
+                    string(extra.kind.as_str()),
+                    optional(&extra.suggestion)
+                )
+            })
+            .collect();
+        let list = |items: &[String]| {
+            if items.is_empty() {
+                "[]".to_string()
+            } else {
+                format!("[\n{}\n  ]", items.join(",\n"))
+            }
+        };
+        format!(
+            "{{\n  \"exports\": {},\n  \"covered\": {},\n  \"percent\": {:.2},\n  \"missing\": {},\n  \"extra\": {}\n}}\n",
+            self.exports,
+            self.covered,
+            self.percent(),
+            list(&missing),
+            list(&extra)
+        )
+    }
+}
+
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let exports = names(&[
+            "DxLib_Init",
+            "DrawString",
+            "DrawStringW",
+            "LoadGraph",
+            "Paint",
+        ]);
+        let declared = names(&[
+            "DxLib_Init",
+            "DrawString",
+            "LoadGrpah",
+            "GetColorW",
+            "MyHelper",
+        ]);
+        let report = coverage_report(&exports, &declared);
+        assert_eq!(report.covered, 2);
+        assert_eq!(report.percent(), 40.0);
+        assert_eq!(
+            report.missing,
+            vec![
+                Missing {
+                    name: "DrawStringW".to_string(),
+                    variant_of: Some("DrawString".to_string()),
+                },
+                Missing {
+                    name: "LoadGraph".to_string(),
+                    variant_of: None,
+                },
+                Missing {
+                    name: "Paint".to_string(),
+                    variant_of: None,
+                },
+            ]
+        );
+        let extra: Vec<_> = report
+            .extra
+            .iter()
+            .map(|extra| (extra.name.as_str(), extra.kind, extra.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            extra,
+            vec![
+                ("GetColorW", ExtraKind::Unknown, None),
+                ("LoadGrpah", ExtraKind::Misspelled, Some("LoadGraph")),
+                ("MyHelper", ExtraKind::Unknown, None),
+            ]
+        );
+
+        let report = coverage_report(&names(&["DrawString"]), &names(&["DrawStringA"]));
+        assert_eq!(report.extra[0].kind, ExtraKind::Variant);
+        assert_eq!(report.extra[0].suggestion.as_deref(), Some("DrawString"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let report = coverage_report(&names(&["DrawString", "Paint"]), &names(&["DrawStrng"]));
+        assert_eq!(
+            report.to_json(),
+            r#"{
+  "exports": 2,
+  "covered": 0,
+  "percent": 0.00,
+  "missing": [
+    { "name": "DrawString", "variant_of": null },
+    { "name": "Paint", "variant_of": null }
+  ],
+  "extra": [
+    { "name": "DrawStrng", "kind": "misspelled", "suggestion": "DrawString" }
+  ]
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,77 @@
+// src/declared.rs
+// Rust のソース (dxlib.rs 等) の dxlib_gen!,cffi_gen! で宣言済みの関数名の読み取り
+// マクロの外の fn、コメント内の fn は除き、#[alias] で別名にした関数も C の関数名で数える
+use std::collections::BTreeSet;
+
+const MACROS: &[&str] = &["dxlib_gen!", "cffi_gen!"];
+
+pub fn declared_functions(source: &str) -> BTreeSet<String> {
+    let code: String = source
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut names = BTreeSet::new();
+    let mut rest = code.as_str();
+    while let Some((start, macro_name)) = MACROS
+        .iter()
+        .filter_map(|name| rest.find(name).map(|start| (start, name)))
+        .min()
+    {
+        rest = &rest[start + macro_name.len()..];
+        let Some(open) = rest.find('{') else {
+            break;
+        };
+        // 対応する } までがマクロの入力
+        let mut depth = 0usize;
+        let mut end = rest.len();
+        for (i, c) in rest.char_indices().skip_while(|(i, _)| *i < open) {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = i;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut words = rest[open..end]
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| !word.is_empty());
+        while let Some(word) = words.next() {
+            if word == "fn"
+                && let Some(name) = words.next()
+            {
+                names.insert(name.to_string());
+            }
+        }
+        rest = &rest[end..];
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declared_functions() {
+        let source = "\
+fn default_rect_ptr() -> *mut RECT { todo!() }
+dxlib_gen! {
+    // fn Commented() -> i32,
+    /// ライブラリの初期化
+    fn DxLib_Init() -> i32,
+    fn DrawString<S: AsRef<str>>(x: i32, y: i32, string: S, color: i32) -> i32,
+    #[alias = \"load\"] fn LoadGraph(file_name: impl AsRef<str>) -> i32,
+}
+mod ffi { cffi_gen! { \"Other\", fn OtherInit() -> i32, } }";
+        assert_eq!(
+            declared_functions(source).into_iter().collect::<Vec<_>>(),
+            vec!["DrawString", "DxLib_Init", "LoadGraph", "OtherInit"]
+        );
+    }
+}
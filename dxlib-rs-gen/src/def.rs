@@ -0,0 +1,32 @@
+// src/def.rs
+// DxLib_x64.def のエクスポートの読み取り
+//  LIBRARY DxLib_x64.dll
+//  EXPORTS
+//  	dx_DxLib_Init
+
+// EXPORTS 以降の関数名を、先頭の prefix (dx_) を除いて記述順に返す
+pub fn parse_def(text: &str, prefix: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "EXPORTS")
+        .skip(1)
+        // 序数等 (dx_Name @1 NONAME) は関数名のみ
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| !name.starts_with(';'))
+        .map(|name| name.strip_prefix(prefix).unwrap_or(name).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_def() {
+        let def = "LIBRARY DxLib_x64.dll\n\nEXPORTS\n\tdx_DxLib_Init\n\tdx_DrawString @2\n; comment\n\tOther\n";
+        assert_eq!(
+            parse_def(def, "dx_"),
+            vec!["DxLib_Init", "DrawString", "Other"]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+// src/header.rs
+// DxLib.h の関数宣言の読み取り
+//  extern int DrawString( int x, int y, const TCHAR *String, unsigned int Color, unsigned int EdgeColor = 0 ) ; // 文字列を描画する
+// プリプロセッサの条件は評価せず、全ての extern 宣言を対象にする (DLL にない関数は .def との照合で除かれる)
+
+// C の型 (const TCHAR * -> base: "TCHAR", is_const: true, pointer: 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CType {
+    pub base: String,
+    pub is_const: bool,
+    pub pointer: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CParam {
+    pub ty: CType,
+    pub name: String,
+    // 既定引数の式 (= 以降)
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CFunction {
+    pub name: String,
+    pub return_type: CType,
+    pub params: Vec<CParam>,
+    // 宣言の行末の // コメント
+    pub comment: Option<String>,
+}
+
+// 生成できなかった関数と理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    pub name: String,
+    pub reason: String,
+}
+
+impl Skipped {
+    pub fn new(name: &str, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+// ヘッダーのバイト列を文字列にする (DxLib.h は Shift-JIS のため、UTF-8 でない場合は Shift-JIS として読む)
+pub fn decode_header(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::SHIFT_JIS.decode(bytes).0.into_owned(),
+    }
+}
+
+// ヘッダー内の extern 関数宣言を宣言順に読み取る
+pub fn parse_header(text: &str) -> Vec<Result<CFunction, Skipped>> {
+    let mut functions = Vec::new();
+    let mut in_block_comment = false;
+    // 複数行にまたがる宣言
+    let mut pending: Option<String> = None;
+
+    for line in text.lines() {
+        let (code, comment) = split_comment(line, &mut in_block_comment);
+        let code = code.trim();
+        if code.starts_with('#') {
+            continue;
+        }
+        let mut declaration = match pending.take() {
+            Some(mut declaration) => {
+                declaration.push(' ');
+                declaration.push_str(code);
+                declaration
+            }
+            None if is_extern_declaration(code) => code.to_string(),
+            None => continue,
+        };
+        let Some(end) = declaration.find(';') else {
+            pending = Some(declaration);
+            continue;
+        };
+        declaration.truncate(end);
+        // 変数の宣言 (extern int DX_... ;) は対象外
+        if declaration.contains('(') {
+            functions.push(parse_declaration(&declaration, comment));
+        }
+    }
+    functions
+}
+
+fn is_extern_declaration(code: &str) -> bool {
+    code.strip_prefix("extern")
+        .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+        && !code.starts_with("extern \"C\"")
+}
+
+// 行をコードと行末の // コメントに分ける (/* */ のコメントは除く)
+fn split_comment(line: &str, in_block_comment: &mut bool) -> (String, Option<String>) {
+    let mut code = String::new();
+    let mut rest = line;
+    loop {
+        if *in_block_comment {
+            match rest.find("*/") {
+                Some(end) => {
+                    *in_block_comment = false;
+                    rest = &rest[end + 2..];
+                }
+                None => return (code, None),
+            }
+        }
+        let line_comment = rest.find("//");
+        let block_comment = rest.find("/*");
+        match (line_comment, block_comment) {
+            (Some(line_start), block_start) if block_start.is_none_or(|b| line_start < b) => {
+                code.push_str(&rest[..line_start]);
+                let comment = rest[line_start + 2..].trim();
+                let comment = (!comment.is_empty()).then(|| comment.to_string());
+                return (code, comment);
+            }
+            (_, Some(block_start)) => {
+                code.push_str(&rest[..block_start]);
+                code.push(' ');
+                *in_block_comment = true;
+                rest = &rest[block_start + 2..];
+            }
+            _ => {
+                code.push_str(rest);
+                return (code, None);
+            }
+        }
+    }
+}
+
+// extern を除いた宣言 (戻り値の型 関数名( 引数 ))
+fn parse_declaration(declaration: &str, comment: Option<String>) -> Result<CFunction, Skipped> {
+    let declaration = declaration.trim_start().trim_start_matches("extern").trim();
+    let open = declaration.find('(').unwrap_or(declaration.len());
+    let head = declaration[..open].trim_end();
+    let name_start = head
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let name = &head[name_start..];
+    if name.is_empty() {
+        return Err(Skipped::new(declaration, "関数名を読み取れません"));
+    }
+    // 関数ポインタを返す関数、関数ポインタの変数等
+    let close = declaration.rfind(')').unwrap_or(0);
+    if close < open || !declaration[close + 1..].trim().is_empty() {
+        return Err(Skipped::new(name, "関数ポインタを含む宣言は未対応です"));
+    }
+    let return_type = parse_type(&head[..name_start])
+        .ok_or_else(|| Skipped::new(name, "戻り値の型を読み取れません"))?;
+
+    let params_text = declaration[open + 1..close].trim();
+    let mut params = Vec::new();
+    if !params_text.is_empty() && params_text != "void" {
+        for (i, param) in split_params(params_text).iter().enumerate() {
+            params.push(parse_param(param, i).map_err(|reason| Skipped::new(name, reason))?);
+        }
+    }
+    Ok(CFunction {
+        name: name.to_string(),
+        return_type,
+        params,
+        comment,
+    })
+}
+
+// 引数を , で区切る (括弧内の , は区切らない)
+fn split_params(text: &str) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                params.push(std::mem::take(&mut current).trim().to_string());
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    params.push(current.trim().to_string());
+    params
+}
+
+fn parse_param(text: &str, index: usize) -> Result<CParam, String> {
+    if text == "..." {
+        return Err("可変長引数は未対応です".to_string());
+    }
+    if text.contains("(*") || text.contains("( *") {
+        return Err("関数ポインタの引数は未対応です".to_string());
+    }
+    let (declarator, default) = match text.split_once('=') {
+        Some((declarator, default)) => (declarator.trim(), Some(default.trim().to_string())),
+        None => (text, None),
+    };
+    // 配列の引数 (int Buf[ 4 ]) はポインタとして扱う
+    let (declarator, is_array) = match declarator.find('[') {
+        Some(bracket) => (declarator[..bracket].trim_end(), true),
+        None => (declarator, false),
+    };
+    let name_start = declarator
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let (type_text, name) = declarator.split_at(name_start);
+    let mut ty = parse_type(type_text);
+    // 引数名のない宣言 (int) は型のみ
+    if ty.is_none() || name.is_empty() || is_type_word(name) {
+        ty = parse_type(declarator);
+        return match ty {
+            Some(ty) => Ok(CParam {
+                ty,
+                name: format!("Arg{}", index + 1),
+                default,
+            }),
+            None => Err(format!("引数 `{}` を読み取れません", text)),
+        };
+    }
+    let mut ty = ty.unwrap();
+    if is_array {
+        ty.pointer += 1;
+    }
+    Ok(CParam {
+        ty,
+        name: name.to_string(),
+        default,
+    })
+}
+
+// 型名として使われる語 (引数名の省略の判定用)
+fn is_type_word(word: &str) -> bool {
+    matches!(
+        word,
+        "int" | "char" | "short" | "long" | "float" | "double" | "void" | "unsigned" | "signed"
+    )
+}
+
+fn parse_type(text: &str) -> Option<CType> {
+    let pointer = text.matches('*').count();
+    let mut is_const = false;
+    let mut words = Vec::new();
+    let text = text.replace(['*', '&'], " ");
+    for word in text.split_whitespace() {
+        match word {
+            "const" => is_const = true,
+            // 呼び出し規約等の修飾子
+            "extern" | "static" | "inline" | "__cdecl" | "__stdcall" | "WINAPI" => {}
+            _ => words.push(word),
+        }
+    }
+    if words.is_empty() {
+        return None;
+    }
+    Some(CType {
+        base: words.join(" "),
+        is_const,
+        pointer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ty(base: &str, is_const: bool, pointer: usize) -> CType {
+        CType {
+            base: base.to_string(),
+            is_const,
+            pointer,
+        }
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let header = "\
+#ifndef DX_NON_FONT
+extern\tint\t\t\tDrawString(\t\t\t\tint x, int y, const TCHAR *String, unsigned int Color, unsigned int EdgeColor = 0 ) ;\t\t// 文字列を描画する
+#endif
+/* extern int Hidden( void ) ; */
+extern\tint\t\t\tDxLib_Init( void ) ;
+extern\tint\t\t\tGetGraphSize( int GrHandle, int *SizeX,
+\t\t\t\t\t\tint *SizeY ) ;\t// グラフィックのサイズを得る
+extern int DX_VARIABLE ;
+extern\tint\t\t\tprintfDx( const TCHAR *FormatString , ... ) ;
+";
+        let functions = parse_header(header);
+        assert_eq!(functions.len(), 4);
+
+        let draw_string = functions[0].as_ref().unwrap();
+        assert_eq!(draw_string.name, "DrawString");
+        assert_eq!(draw_string.return_type, ty("int", false, 0));
+        assert_eq!(draw_string.comment.as_deref(), Some("文字列を描画する"));
+        assert_eq!(draw_string.params.len(), 5);
+        assert_eq!(draw_string.params[2].ty, ty("TCHAR", true, 1));
+        assert_eq!(draw_string.params[2].name, "String");
+        assert_eq!(draw_string.params[3].ty, ty("unsigned int", false, 0));
+        assert_eq!(draw_string.params[4].default.as_deref(), Some("0"));
+
+        let init = functions[1].as_ref().unwrap();
+        assert_eq!(init.name, "DxLib_Init");
+        assert!(init.params.is_empty() && init.comment.is_none());
+
+        // 複数行の宣言
+        let get_graph_size = functions[2].as_ref().unwrap();
+        assert_eq!(get_graph_size.params[2].name, "SizeY");
+        assert_eq!(get_graph_size.params[2].ty, ty("int", false, 1));
+        assert_eq!(
+            get_graph_size.comment.as_deref(),
+            Some("グラフィックのサイズを得る")
+        );
+
+        assert_eq!(functions[3].as_ref().unwrap_err().name, "printfDx");
+    }
+
+    #[test]
+    fn test_decode_header() {
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode("// 文字列を描画する");
+        assert_eq!(decode_header(&bytes), "// 文字列を描画する");
+    }
+}
@@ -0,0 +1,308 @@
+// src/main.rs
+// DxLib.h から dxlib_gen! の関数シグネチャを生成する
+//  cargo run -p dxlib-rs-gen -- --header path/to/DxLib.h --exclude src/dxlib.rs --output src/dxlib_all.rs
+// DxLib_x64.def にエクスポートされている関数のみを生成し、生成できなかった関数は理由を末尾のコメントに残す
+// (DxLib の更新時は新しい DxLib.h,DxLib_x64.def で再生成する)
+// coverage を指定した場合は、DxLib_x64.def のエクスポートと宣言済みの関数を比較する (coverage.rs)
+//  cargo run -p dxlib-rs-gen -- coverage --source src/dxlib.rs --format json
+mod coverage;
+mod declared;
+mod def;
+mod header;
+mod signature;
+
+use anyhow::{Context, Result, bail};
+use header::Skipped;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+usage: dxlib-rs-gen --header <DxLib.h> [--def <DxLib_x64.def>] [--prefix <dx_>]
+                    [--exclude <file.rs>]... [--output <file.rs>]
+       dxlib-rs-gen coverage [--def <DxLib_x64.def>] [--prefix <dx_>]
+                    [--source <file.rs>]... [--format text|json] [--output <file>]";
+
+#[derive(PartialEq, Eq)]
+enum Command {
+    Generate,
+    Coverage,
+}
+
+struct Options {
+    command: Command,
+    header: Option<PathBuf>,
+    def: PathBuf,
+    // .def の関数名の先頭に付いている文字列 (dxlib_gen! の func_name_top_prefix と同じ)
+    prefix: String,
+    // 宣言済みの関数を生成しないファイル (手書きのシグネチャを優先する)
+    exclude: Vec<PathBuf>,
+    // coverage で宣言済みの関数を読み取るファイル (未指定の場合は src/dxlib.rs)
+    sources: Vec<PathBuf>,
+    json: bool,
+    // 未指定の場合は標準出力
+    output: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Options> {
+    let mut args = std::env::args().skip(1).peekable();
+    let command = if args.next_if(|arg| arg == "coverage").is_some() {
+        Command::Coverage
+    } else {
+        Command::Generate
+    };
+    let mut header = None;
+    let mut def = PathBuf::from("DxLib/DxLib_x64.def");
+    let mut prefix = "dx_".to_string();
+    let mut exclude = Vec::new();
+    let mut sources = Vec::new();
+    let mut json = false;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("missing value for {}\n{}", arg, USAGE))
+        };
+        match (&command, arg.as_str()) {
+            (Command::Generate, "--header") => header = Some(PathBuf::from(value()?)),
+            (Command::Generate, "--exclude") => exclude.push(PathBuf::from(value()?)),
+            (Command::Coverage, "--source") => sources.push(PathBuf::from(value()?)),
+            (Command::Coverage, "--format") => {
+                json = match value()?.as_str() {
+                    "json" => true,
+                    "text" => false,
+                    format => bail!("unknown format {}\n{}", format, USAGE),
+                }
+            }
+            (_, "--def") => def = PathBuf::from(value()?),
+            (_, "--prefix") => prefix = value()?,
+            (_, "--output") => output = Some(PathBuf::from(value()?)),
+            _ => bail!("unknown argument {}\n{}", arg, USAGE),
+        }
+    }
+    if command == Command::Generate && header.is_none() {
+        bail!("--header is required\n{}", USAGE);
+    }
+    if sources.is_empty() {
+        sources.push(PathBuf::from("src/dxlib.rs"));
+    }
+    Ok(Options {
+        command,
+        header,
+        def,
+        prefix,
+        exclude,
+        sources,
+        json,
+        output,
+    })
+}
+
+fn read_exports(options: &Options) -> Result<BTreeSet<String>> {
+    let def_text = std::fs::read_to_string(&options.def)
+        .with_context(|| format!("failed to read {}", options.def.display()))?;
+    Ok(def::parse_def(&def_text, &options.prefix)
+        .into_iter()
+        .collect())
+}
+
+fn read_declared(paths: &[PathBuf]) -> Result<BTreeSet<String>> {
+    let mut declared = BTreeSet::new();
+    for path in paths {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        declared.extend(declared::declared_functions(&source));
+    }
+    Ok(declared)
+}
+
+fn write_output(output: Option<&Path>, text: &str) -> Result<()> {
+    match output {
+        Some(path) => std::fs::write(path, text)
+            .with_context(|| format!("failed to write {}", path.display())),
+        // パイプ先が閉じられた場合 (| head 等) も panic しない
+        None => std::io::stdout()
+            .write_all(text.as_bytes())
+            .context("failed to write to stdout"),
+    }
+}
+
+fn main() -> Result<()> {
+    let options = parse_args()?;
+    match options.command {
+        Command::Generate => generate(&options),
+        Command::Coverage => {
+            let report = coverage::coverage_report(
+                &read_exports(&options)?,
+                &read_declared(&options.sources)?,
+            );
+            let text = if options.json {
+                report.to_json()
+            } else {
+                report.to_text()
+            };
+            write_output(options.output.as_deref(), &text)
+        }
+    }
+}
+
+fn generate(options: &Options) -> Result<()> {
+    let header_path = options.header.as_ref().unwrap();
+    let header_bytes = std::fs::read(header_path)
+        .with_context(|| format!("failed to read {}", header_path.display()))?;
+    let exports = read_exports(options)?;
+    let excluded = read_declared(&options.exclude)?;
+
+    let Generated {
+        signatures,
+        skipped,
+        declared,
+    } = generate_signatures(&header::decode_header(&header_bytes), &exports, &excluded);
+    let source = generate_source(options, &signatures, &skipped);
+    write_output(options.output.as_deref(), &source)?;
+
+    let undeclared = exports
+        .iter()
+        .filter(|name| !declared.contains(*name) && !excluded.contains(*name))
+        .count();
+    eprintln!(
+        "generated {} functions, skipped {}, excluded {}, exports not found in header {}",
+        signatures.len(),
+        skipped.len(),
+        exports.intersection(&excluded).count(),
+        undeclared
+    );
+    Ok(())
+}
+
+// ヘッダーから生成したシグネチャと、生成できなかった関数
+struct Generated {
+    signatures: Vec<String>,
+    skipped: Vec<Skipped>,
+    // ヘッダーで宣言されていた(エクスポートされている)関数
+    declared: BTreeSet<String>,
+}
+
+fn generate_signatures(
+    header_text: &str,
+    exports: &BTreeSet<String>,
+    excluded: &BTreeSet<String>,
+) -> Generated {
+    let mut signatures = Vec::new();
+    let mut skipped = Vec::new();
+    let mut declared = BTreeSet::new();
+    for function in header::parse_header(header_text) {
+        let name = match &function {
+            Ok(function) => function.name.clone(),
+            Err(skip) => skip.name.clone(),
+        };
+        // DLL にない関数 (マクロ、C++ のみの関数等) は出力しない
+        if !exports.contains(&name) || excluded.contains(&name) {
+            continue;
+        }
+        // C++ のオーバーロードは最初の宣言のみ
+        if !declared.insert(name.clone()) {
+            skipped.push(Skipped::new(
+                &name,
+                "オーバーロードは最初の宣言のみ生成します",
+            ));
+            continue;
+        }
+        match function.and_then(|function| signature::to_signature(&function)) {
+            Ok(signature) => signatures.push(signature),
+            Err(skip) => skipped.push(skip),
+        }
+    }
+    Generated {
+        signatures,
+        skipped,
+        declared,
+    }
+}
+
+// 生成するモジュールのソース (dxlib.rs と同じ use を持つ)
+fn generate_source(options: &Options, signatures: &[String], skipped: &[Skipped]) -> String {
+    let file_name = |path: &PathBuf| {
+        path.file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+    };
+    let mut source = String::new();
+    let _ = writeln!(
+        source,
+        "// このファイルは dxlib-rs-gen で {} ({} と照合) から生成されています。直接編集しないでください",
+        options.header.as_ref().map(file_name).unwrap_or_default(),
+        file_name(&options.def)
+    );
+    source.push_str(
+        "\
+#![allow(non_snake_case)]
+// 生成される関数によっては使用しない
+#![allow(unused_imports)]
+#![allow(clippy::too_many_arguments)]
+
+use crate::charcode::*;
+use crate::dxlib_constants::*;
+use crate::dxlib_error::*;
+use crate::dxlib_types::*;
+use crate::utils::*;
+
+use dxlib_rs_macro::dxlib_gen;
+
+dxlib_gen! {
+    \"DxLib_x64\",
+",
+    );
+    for signature in signatures {
+        for line in signature.lines() {
+            let _ = writeln!(source, "    {}", line);
+        }
+    }
+    source.push_str("}\n");
+    if !skipped.is_empty() {
+        source.push_str("\n// 生成できなかった関数\n");
+        for skip in skipped {
+            let _ = writeln!(source, "// {}: {}", skip.name, skip.reason);
+        }
+    }
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // tests/fixtures の DxLib.h の抜粋から生成した結果が、チェックインされた dxlib_excerpt.rs と一致する
+    // (dxlib_excerpt.rs は dxlib-rs のテストで dxlib_gen! としてコンパイル、呼び出しされる)
+    // 生成処理を変更した場合は、ワークスペースのルートで下記を実行して更新する
+    //  cargo run -p dxlib-rs-gen -- --header dxlib-rs-gen/tests/fixtures/DxLib_excerpt.h --exclude src/dxlib.rs --output dxlib-rs-gen/tests/fixtures/dxlib_excerpt.rs
+    #[test]
+    fn test_generate_excerpt() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let options = Options {
+            command: Command::Generate,
+            header: Some(PathBuf::from("dxlib-rs-gen/tests/fixtures/DxLib_excerpt.h")),
+            def: root.join("DxLib/DxLib_x64.def"),
+            prefix: "dx_".to_string(),
+            exclude: vec![root.join("src/dxlib.rs")],
+            sources: Vec::new(),
+            json: false,
+            output: None,
+        };
+        let exports = read_exports(&options).unwrap();
+        let excluded = read_declared(&options.exclude).unwrap();
+        let header_bytes = std::fs::read(root.join(options.header.as_ref().unwrap())).unwrap();
+        let generated =
+            generate_signatures(&header::decode_header(&header_bytes), &exports, &excluded);
+
+        // dxlib.rs で宣言済みの GetColor、.def にない NotExported は生成しない
+        assert!(!generated.declared.contains("NotExported"));
+        assert!(excluded.contains("GetColor"));
+        let source = generate_source(&options, &generated.signatures, &generated.skipped);
+        let expected =
+            std::fs::read_to_string(root.join("dxlib-rs-gen/tests/fixtures/dxlib_excerpt.rs"))
+                .unwrap();
+        assert_eq!(source, expected);
+    }
+}
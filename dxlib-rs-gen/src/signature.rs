@@ -0,0 +1,305 @@
+// src/signature.rs
+// C の関数宣言から dxlib_gen! の関数シグネチャを作成する
+//  extern int ChangeWindowMode( int Flag = TRUE ) ; // ウインドウモードを変更する
+//  -> /// ウインドウモードを変更する
+//     fn ChangeWindowMode(#[default = "true"] flag: Option<bool>) -> i32,
+// 型の対応は dxlib.rs の手書きのシグネチャに合わせる
+//  ・const TCHAR *,const char * -> impl AsRef<str>
+//  ・TCHAR *,int * 等 -> *mut c_char,*mut i32 等の生ポインタ (書き込み先の容量がわからないため)
+//  ・dxlib_types の構造体 -> 値はそのまま、const T * は &T、T * は &mut T
+//  ・名前が Flag で終わる int の引数 -> bool (既定値の TRUE,FALSE は true,false)
+//  ・float,double,ポインタを返す関数 -> #[error_condition = "false"] (構造体を返す関数は生成しない)
+//  ・既定引数 -> #[default = "..."] の Option<T> (文字列、構造体の参照の場合は既定値なしの引数)
+use crate::header::{CFunction, CParam, CType, Skipped};
+
+// dxlib_types で dx_arg_repr_c! が実装されている構造体
+const STRUCT_TYPES: &[&str] = &[
+    "RECT",
+    "VECTOR",
+    "COLOR_U8",
+    "COLOR_F",
+    "MATERIALPARAM",
+    "VERTEX3D",
+    "HITRESULT_LINE",
+    "DATEDATA",
+    "XAUDIO2FX_REVERB_PARAMETERS",
+    "IPDATA",
+    "MATRIX",
+];
+
+// Windows のハンドル型 (*mut c_void として扱う)
+const HANDLE_TYPES: &[&str] = &[
+    "HANDLE",
+    "HWND",
+    "HINSTANCE",
+    "HMODULE",
+    "HICON",
+    "HBITMAP",
+    "HDC",
+    "HRGN",
+    "HMENU",
+    "HFONT",
+];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod",
+    "move", "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "yield",
+];
+
+// r# を付けても識別子にできないキーワード (末尾に _ を付ける)
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "super"];
+
+// ポインタでない C の型に対応する Rust の型
+fn primitive_type(base: &str) -> Option<&'static str> {
+    Some(match base {
+        "int" | "signed int" | "long int" | "BOOL" => "i32",
+        "unsigned int" | "unsigned" | "UINT" | "DWORD" => "u32",
+        "short" | "short int" => "i16",
+        "unsigned short" | "WORD" => "u16",
+        "char" | "TCHAR" => "std::os::raw::c_char",
+        "signed char" => "i8",
+        "unsigned char" | "BYTE" => "u8",
+        "wchar_t" => "u16",
+        "float" => "f32",
+        "double" => "f64",
+        "long" => "std::os::raw::c_long",
+        "unsigned long" => "std::os::raw::c_ulong",
+        "long long" | "LONGLONG" | "__int64" => "i64",
+        "unsigned long long" | "ULONGLONG" => "u64",
+        "size_t" | "DWORD_PTR" | "ULONG_PTR" => "usize",
+        "LONG_PTR" | "INT_PTR" => "isize",
+        "void" => "std::os::raw::c_void",
+        _ if HANDLE_TYPES.contains(&base) => "*mut std::os::raw::c_void",
+        _ => return None,
+    })
+}
+
+fn raw_pointer_type(ty: &CType) -> Result<String, String> {
+    let pointee = if STRUCT_TYPES.contains(&ty.base.as_str()) {
+        ty.base.as_str()
+    } else {
+        primitive_type(&ty.base).ok_or_else(|| format!("未対応の型 `{}` です", ty.base))?
+    };
+    let mut rust_type = pointee.to_string();
+    for level in 0..ty.pointer {
+        // const は最も内側のポインタのみに付いているとみなす
+        let mutability = if level == 0 && ty.is_const {
+            "const"
+        } else {
+            "mut"
+        };
+        rust_type = format!("*{} {}", mutability, rust_type);
+    }
+    Ok(rust_type)
+}
+
+fn is_string_type(ty: &CType) -> bool {
+    ty.pointer == 1 && ty.is_const && matches!(ty.base.as_str(), "TCHAR" | "char")
+}
+
+fn is_flag(param: &CParam) -> bool {
+    param.ty.pointer == 0 && param.ty.base == "int" && param.name.ends_with("Flag")
+}
+
+// 戻り値の型と、-1 をエラーとして判定できない型か
+fn return_type(ty: &CType) -> Result<(String, bool), String> {
+    match (ty.base.as_str(), ty.pointer) {
+        ("void", 0) => Err("戻り値のない関数は未対応です".to_string()),
+        (base, 0) if STRUCT_TYPES.contains(&base) => {
+            Err(format!("構造体 `{}` を返す関数は未対応です", base))
+        }
+        ("float" | "double", 0) => Ok((primitive_type(&ty.base).unwrap().to_string(), true)),
+        (base, 0) => primitive_type(base)
+            .map(|rust_type| (rust_type.to_string(), HANDLE_TYPES.contains(&base)))
+            .ok_or_else(|| format!("未対応の型 `{}` です", base)),
+        _ => Ok((raw_pointer_type(ty)?, true)),
+    }
+}
+
+// 引数の型と、既定値を Option<T> として指定できるか
+fn param_type(param: &CParam) -> Result<(String, bool), String> {
+    let ty = &param.ty;
+    if is_string_type(ty) {
+        return Ok(("impl AsRef<str>".to_string(), false));
+    }
+    if is_flag(param) {
+        return Ok(("bool".to_string(), true));
+    }
+    match (ty.base.as_str(), ty.pointer) {
+        ("void", 0) => Err("void の引数は未対応です".to_string()),
+        (base, 0) if STRUCT_TYPES.contains(&base) => Ok((base.to_string(), false)),
+        (base, 1) if STRUCT_TYPES.contains(&base) => {
+            let reference = if ty.is_const { "&" } else { "&mut " };
+            Ok((format!("{}{}", reference, base), false))
+        }
+        (base, 0) => primitive_type(base)
+            .map(|rust_type| (rust_type.to_string(), true))
+            .ok_or_else(|| format!("未対応の型 `{}` です", base)),
+        _ => Ok((raw_pointer_type(ty)?, true)),
+    }
+}
+
+// C の既定引数を Rust の式にする
+fn default_value(param: &CParam, rust_type: &str, value: &str) -> String {
+    match value {
+        "TRUE" | "1" if rust_type == "bool" => "true".to_string(),
+        "FALSE" | "0" if rust_type == "bool" => "false".to_string(),
+        "NULL" | "0" if rust_type.starts_with("*const") => "std::ptr::null()".to_string(),
+        "NULL" | "0" if rust_type.starts_with("*mut") => "std::ptr::null_mut()".to_string(),
+        // 1.0f -> 1.0, 1.f -> 1.0, 0 -> 0.0
+        _ if matches!(param.ty.base.as_str(), "float" | "double") => {
+            let value = value.trim_end_matches(['f', 'F']);
+            if value.ends_with('.') {
+                format!("{}0", value)
+            } else if value.parse::<i64>().is_ok() {
+                format!("{}.0", value)
+            } else {
+                value.to_string()
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
+// DrawScreen -> draw_screen, XBuf -> x_buf
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    if NON_RAW_KEYWORDS.contains(&snake.as_str()) {
+        format!("{}_", snake)
+    } else if RUST_KEYWORDS.contains(&snake.as_str()) {
+        format!("r#{}", snake)
+    } else {
+        snake
+    }
+}
+
+// dxlib_gen! の 1 関数分のシグネチャ (行頭のインデントなし、末尾の , を含む)
+pub fn to_signature(function: &CFunction) -> Result<String, Skipped> {
+    let skip = |reason: String| Skipped::new(&function.name, reason);
+    let (return_type, is_not_status) = return_type(&function.return_type).map_err(skip)?;
+
+    let mut params = Vec::new();
+    for param in &function.params {
+        let (rust_type, is_optional) =
+            param_type(param).map_err(|reason| skip(format!("引数 {}: {}", param.name, reason)))?;
+        let name = snake_case(&param.name);
+        params.push(match &param.default {
+            Some(value) if is_optional => format!(
+                "#[default = \"{}\"] {}: Option<{}>",
+                default_value(param, &rust_type, value).replace('"', "\\\""),
+                name,
+                rust_type
+            ),
+            _ => format!("{}: {}", name, rust_type),
+        });
+    }
+
+    let mut signature = String::new();
+    if let Some(comment) = &function.comment {
+        signature.push_str(&format!("/// {}\n", comment));
+    }
+    // 戻り値が状態 (-1 でエラー) でない関数はエラーを判定しない
+    if is_not_status {
+        signature.push_str("#[error_condition = \"false\"]\n");
+    }
+    signature.push_str(&format!(
+        "fn {}({}) -> {},",
+        function.name,
+        params.join(", "),
+        return_type
+    ));
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::parse_header;
+
+    fn signature(declaration: &str) -> Result<String, Skipped> {
+        let function = parse_header(declaration).remove(0)?;
+        to_signature(&function)
+    }
+
+    #[test]
+    fn test_to_signature() {
+        assert_eq!(
+            signature(
+                "extern int ChangeWindowMode( int Flag = TRUE ) ; // ウインドウモードを変更する"
+            )
+            .unwrap(),
+            "/// ウインドウモードを変更する\nfn ChangeWindowMode(#[default = \"true\"] flag: Option<bool>) -> i32,"
+        );
+        assert_eq!(
+            signature("extern int DrawString( int x, int y, const TCHAR *String, unsigned int Color, unsigned int EdgeColor = 0 ) ;").unwrap(),
+            "fn DrawString(x: i32, y: i32, string: impl AsRef<str>, color: u32, #[default = \"0\"] edge_color: Option<u32>) -> i32,"
+        );
+        assert_eq!(
+            signature("extern int ClearDrawScreen( const RECT *ClearRect = NULL ) ;").unwrap(),
+            "fn ClearDrawScreen(clear_rect: &RECT) -> i32,"
+        );
+        assert_eq!(
+            signature("extern int GetGraphSize( int GrHandle, int *SizeX, int *SizeY ) ;").unwrap(),
+            "fn GetGraphSize(gr_handle: i32, size_x: *mut i32, size_y: *mut i32) -> i32,"
+        );
+        assert_eq!(
+            signature(
+                "extern int SetDrawArea( int x1, int y1, void *Param = NULL, float Scale = 1.0f ) ;"
+            )
+            .unwrap(),
+            "fn SetDrawArea(x1: i32, y1: i32, #[default = \"std::ptr::null_mut()\"] param: Option<*mut std::os::raw::c_void>, #[default = \"1.0\"] scale: Option<f32>) -> i32,"
+        );
+        assert_eq!(
+            signature(
+                "extern int SetCameraPositionAndTarget_UpVecY( VECTOR Position, VECTOR Target ) ;"
+            )
+            .unwrap(),
+            "fn SetCameraPositionAndTarget_UpVecY(position: VECTOR, target: VECTOR) -> i32,"
+        );
+        assert_eq!(
+            signature("extern float GetFPS( void ) ; // フレームレートを得る").unwrap(),
+            "/// フレームレートを得る\n#[error_condition = \"false\"]\nfn GetFPS() -> f32,"
+        );
+    }
+
+    #[test]
+    fn test_to_signature_skipped() {
+        for declaration in [
+            "extern void SetGraphMode( int x ) ;",
+            "extern VECTOR VGet( float x, float y, float z ) ;",
+            "extern int SetCallback( void ( *Callback )( void *Data ) ) ;",
+            "extern int SetFont( LOGFONT *Font ) ;",
+            "extern int printfDx( const TCHAR *FormatString , ... ) ;",
+        ] {
+            assert!(signature(declaration).is_err(), "{}", declaration);
+        }
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("DrawScreen"), "draw_screen");
+        assert_eq!(snake_case("XBuf"), "x_buf");
+        assert_eq!(snake_case("x1"), "x1");
+        assert_eq!(snake_case("RGBColor"), "rgb_color");
+        assert_eq!(snake_case("Type"), "r#type");
+        assert_eq!(snake_case("Crate"), "crate_");
+        assert_eq!(snake_case("Super"), "super_");
+        assert_eq!(snake_case("Self"), "self_");
+    }
+}
@@ -0,0 +1,21 @@
+// DxLib.h の抜粋 (dxlib-rs-gen のテスト用、宣言の書式は DxLib.h のまま)
+// 生成結果は dxlib_excerpt.rs で、dxlib-rs のテストで dxlib_gen! としてコンパイルされる
+
+#ifndef DX_NON_GRAPH
+
+// 図形描画関数
+extern	int			DrawLine(			int x1, int y1, int x2, int y2, unsigned int Color, int Thickness = 1 ) ;				// 線を描画する
+extern	int			DrawBox(			int x1, int y1, int x2, int y2, unsigned int Color, int FillFlag ) ;					// 矩形を描画する
+extern	int			DrawCircle(			int x, int y, int r, unsigned int Color,
+										int FillFlag = TRUE, int LineThickness = 1 ) ;										// 円を描画する
+extern	unsigned int	GetColor(		int Red, int Green, int Blue ) ;														// DrawPixel 等の描画関数で使用するカラー値を取得する
+
+#endif // DX_NON_GRAPH
+
+/*
+extern	int			Commented(			void ) ;
+*/
+extern	int			GetNowCount(		int UseRDTSCFlag = FALSE ) ;															// ミリ秒単位の精度を持つカウンタの現在の値を得る
+extern	int			SetWindowText(		const TCHAR *WindowText ) ;																// メインウインドウのウインドウテキストを変更する
+extern	VECTOR		VGet(				float x, float y, float z ) ;															// ベクトル値の生成
+extern	int			NotExported(		void ) ;																				// DxLib_x64.def にない関数
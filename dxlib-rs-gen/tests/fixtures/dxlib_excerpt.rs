@@ -0,0 +1,30 @@
+// このファイルは dxlib-rs-gen で DxLib_excerpt.h (DxLib_x64.def と照合) から生成されています。直接編集しないでください
+#![allow(non_snake_case)]
+// 生成される関数によっては使用しない
+#![allow(unused_imports)]
+#![allow(clippy::too_many_arguments)]
+
+use crate::charcode::*;
+use crate::dxlib_constants::*;
+use crate::dxlib_error::*;
+use crate::dxlib_types::*;
+use crate::utils::*;
+
+use dxlib_rs_macro::dxlib_gen;
+
+dxlib_gen! {
+    "DxLib_x64",
+    /// 線を描画する
+    fn DrawLine(x1: i32, y1: i32, x2: i32, y2: i32, color: u32, #[default = "1"] thickness: Option<i32>) -> i32,
+    /// 矩形を描画する
+    fn DrawBox(x1: i32, y1: i32, x2: i32, y2: i32, color: u32, fill_flag: bool) -> i32,
+    /// 円を描画する
+    fn DrawCircle(x: i32, y: i32, r: i32, color: u32, #[default = "true"] fill_flag: Option<bool>, #[default = "1"] line_thickness: Option<i32>) -> i32,
+    /// ミリ秒単位の精度を持つカウンタの現在の値を得る
+    fn GetNowCount(#[default = "false"] use_rdtsc_flag: Option<bool>) -> i32,
+    /// メインウインドウのウインドウテキストを変更する
+    fn SetWindowText(window_text: impl AsRef<str>) -> i32,
+}
+
+// 生成できなかった関数
+// VGet: 構造体 `VECTOR` を返す関数は未対応です
@@ -0,0 +1,445 @@
+// src/api.rs
+use crate::codegen::GeneratedFunction;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    FnArg, GenericArgument, GenericParam, Generics, Pat, PatType, PathArguments, Type,
+    TypeImplTrait, TypeParamBound,
+};
+
+// #![api_trait = "DxLibApi"] のトレイトのメソッド
+// impl Trait の引数はトレイトオブジェクトとして呼び出せるように具体的な型にする
+//  fn DrawString(x: i32, y: i32, string: impl AsRef<str>, color: i32) -> anyhow::Result<i32, DxLibError>
+//  -> fn DrawString(&self, x: i32, y: i32, string: &str, color: i32) -> anyhow::Result<i32, DxLibError>
+pub struct ApiMethod {
+    name: Ident,
+    // メソッドの宣言 (fn Name(&self, ...) -> R where ...)
+    sig: TokenStream,
+    // メソッドの引数名
+    arg_idents: Vec<Ident>,
+    // ラップ関数の引数からメソッドの引数への変換
+    prepare_stmts: Vec<TokenStream>,
+    dispatch_args: Vec<TokenStream>,
+    // メソッドの引数からラップ関数の引数への変換
+    real_args: Vec<TokenStream>,
+    // 生ポインタの引数があるラップ関数 (unsafe fn) のメソッド
+    is_unsafe: bool,
+}
+
+// impl Trait の引数の、メソッドでの型
+enum Erased {
+    // impl AsRef<T> -> &T
+    AsRef(Type),
+    // impl AsMut<T> -> &mut T
+    AsMut(Type),
+    // impl ToString, impl Display -> &str
+    ToString,
+    // impl Into<T> -> T
+    Into(Type),
+}
+
+impl ApiMethod {
+    pub fn new(
+        name: &Ident,
+        wrapper_args: &[TokenStream],
+        return_type: &TokenStream,
+        generics: &Generics,
+        is_unsafe: bool,
+    ) -> syn::Result<Self> {
+        if let Some(param) = generics
+            .params
+            .iter()
+            .find(|param| matches!(param, GenericParam::Const(_)))
+        {
+            return Err(syn::Error::new_spanned(
+                param,
+                "#![api_trait] を指定した場合は const パラメータを使用できません",
+            ));
+        }
+
+        let mut args = Vec::new();
+        let mut arg_idents = Vec::new();
+        let mut prepare_stmts = Vec::new();
+        let mut dispatch_args = Vec::new();
+        let mut real_args = Vec::new();
+        for wrapper_arg in wrapper_args {
+            let FnArg::Typed(PatType { pat, ty, .. }) = syn::parse2(wrapper_arg.clone())? else {
+                continue;
+            };
+            let Pat::Ident(pat_ident) = &*pat else {
+                continue;
+            };
+            let ident = &pat_ident.ident;
+            arg_idents.push(ident.clone());
+
+            let (reference, inner) = match &*ty {
+                Type::Reference(reference) => (Some(reference), &*reference.elem),
+                _ => (None, &*ty),
+            };
+            let Some(impl_trait) = impl_trait_of(inner) else {
+                args.push(quote! { #ident: #ty });
+                dispatch_args.push(quote! { #ident });
+                real_args.push(quote! { #ident });
+                continue;
+            };
+            let Some(erased) = erased_bound(impl_trait) else {
+                return Err(syn::Error::new_spanned(
+                    &ty,
+                    "#![api_trait] では未対応の引数の型です",
+                ));
+            };
+
+            let lifetime = reference.and_then(|reference| reference.lifetime.as_ref());
+            let source = if reference.is_some() {
+                quote! { #ident }
+            } else {
+                quote! { &#ident }
+            };
+            let (method_ty, dispatch_arg) = match erased {
+                Erased::AsRef(target) => (
+                    quote! { &#lifetime #target },
+                    quote! { AsRef::<#target>::as_ref(#source) },
+                ),
+                Erased::AsMut(target) => {
+                    let source = if reference.is_some() {
+                        quote! { #ident }
+                    } else {
+                        prepare_stmts.push(quote! { let mut #ident = #ident; });
+                        quote! { &mut #ident }
+                    };
+                    (
+                        quote! { &#lifetime mut #target },
+                        quote! { AsMut::<#target>::as_mut(#source) },
+                    )
+                }
+                Erased::ToString => (
+                    quote! { &#lifetime str },
+                    quote! { &ToString::to_string(#source) },
+                ),
+                Erased::Into(target) => {
+                    (quote! { #target }, quote! { Into::<#target>::into(#ident) })
+                }
+            };
+            args.push(quote! { #ident: #method_ty });
+            dispatch_args.push(dispatch_arg);
+            // &impl Trait は Sized の型を要求するため、参照をさらに参照で渡す
+            real_args.push(match reference {
+                Some(reference) if reference.mutability.is_some() => {
+                    quote! { &mut &mut *#ident }
+                }
+                Some(_) => quote! { &#ident },
+                None => quote! { #ident },
+            });
+        }
+
+        let where_clause = &generics.where_clause;
+        let unsafety = is_unsafe.then(|| quote! { unsafe });
+        Ok(Self {
+            name: name.clone(),
+            sig: quote! {
+                #unsafety fn #name #generics(&self, #(#args),*) -> #return_type #where_clause
+            },
+            arg_idents,
+            prepare_stmts,
+            dispatch_args,
+            real_args,
+            is_unsafe,
+        })
+    }
+
+    // unsafe fn のメソッド、ラップ関数の呼び出しは unsafe ブロックで囲む
+    fn call(&self, call: TokenStream) -> TokenStream {
+        if self.is_unsafe {
+            quote! { unsafe { #call } }
+        } else {
+            call
+        }
+    }
+
+    // ラップ関数の先頭で、差し替えられた呼び出し先があればそちらを呼び出す
+    pub fn dispatch_stmt(&self, api: &Ident) -> TokenStream {
+        let backend = backend_ident(api);
+        let Self {
+            name,
+            prepare_stmts,
+            dispatch_args,
+            ..
+        } = self;
+        let call = self.call(quote! { __api.#name(#(#dispatch_args),*) });
+        quote! {
+            if let Some(__api) = #backend::current() {
+                #(#prepare_stmts)*
+                return #call;
+            }
+        }
+    }
+}
+
+fn real_ident(api: &Ident) -> Ident {
+    format_ident!("{}Real", api)
+}
+
+fn backend_ident(api: &Ident) -> Ident {
+    format_ident!("{}Backend", api)
+}
+
+fn impl_trait_of(ty: &Type) -> Option<&TypeImplTrait> {
+    match ty {
+        Type::ImplTrait(impl_trait) => Some(impl_trait),
+        Type::Paren(paren) => impl_trait_of(&paren.elem),
+        Type::Group(group) => impl_trait_of(&group.elem),
+        _ => None,
+    }
+}
+
+fn erased_bound(impl_trait: &TypeImplTrait) -> Option<Erased> {
+    impl_trait.bounds.iter().find_map(|bound| {
+        let TypeParamBound::Trait(trait_bound) = bound else {
+            return None;
+        };
+        let segment = trait_bound.path.segments.last()?;
+        let target = match &segment.arguments {
+            PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(GenericArgument::Type(ty)) => Some(ty.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        match (segment.ident.to_string().as_str(), target) {
+            ("AsRef", Some(target)) => Some(Erased::AsRef(target)),
+            ("AsMut", Some(target)) => Some(Erased::AsMut(target)),
+            ("ToString" | "Display", _) => Some(Erased::ToString),
+            ("Into", Some(target)) => Some(Erased::Into(target)),
+            _ => None,
+        }
+    })
+}
+
+// トレイト、実際のライブラリを呼び出す実装、呼び出し先の切り替えを生成する
+// 生成されるラップ関数は #{api}Backend::current() が Some の場合はそちらを呼び出す
+pub fn generate_api(api: &Ident, functions: &[GeneratedFunction]) -> TokenStream {
+    let real = real_ident(api);
+    let backend = backend_ident(api);
+    let upper = api.to_string().to_uppercase();
+    let thread_ident = format_ident!("__{}_THREAD", upper);
+    let global_ident = format_ident!("__{}_GLOBAL", upper);
+    let active_ident = format_ident!("__{}_ACTIVE", upper);
+    let bypass_ident = format_ident!("__{}_BYPASS", upper);
+
+    let methods: Vec<&ApiMethod> = functions
+        .iter()
+        .filter_map(|function| function.api_method.as_ref())
+        .collect();
+    let sigs: Vec<_> = methods.iter().map(|method| &method.sig).collect();
+    let docs: Vec<_> = methods
+        .iter()
+        .map(|method| {
+            if method.is_unsafe {
+                format!(
+                    " [`{0}`] の呼び出し\n\n # Safety\n\n [`{0}`] と同じ条件で呼び出す必要がある",
+                    method.name
+                )
+            } else {
+                format!(" [`{}`] の呼び出し", method.name)
+            }
+        })
+        .collect();
+    // 既定の実装、DxLibApiReal の実装、Arc<A> の実装のメソッドの本体
+    let default_calls: Vec<_> = methods
+        .iter()
+        .map(|method| {
+            let (name, arg_idents) = (&method.name, &method.arg_idents);
+            method.call(quote! { #real.#name(#(#arg_idents),*) })
+        })
+        .collect();
+    let real_calls: Vec<_> = methods
+        .iter()
+        .map(|method| {
+            let (name, real_args) = (&method.name, &method.real_args);
+            let call = method.call(quote! { #name(#(#real_args),*) });
+            quote! { #backend::real(|| #call) }
+        })
+        .collect();
+    let arc_calls: Vec<_> = methods
+        .iter()
+        .map(|method| {
+            let (name, arg_idents) = (&method.name, &method.arg_idents);
+            method.call(quote! { (**self).#name(#(#arg_idents),*) })
+        })
+        .collect();
+
+    let trait_doc = format!(
+        " 生成された関数の呼び出し先 ({}::scoped 等で差し替える)\n\n 既定の実装は {} (実際のライブラリ) を呼び出すため、必要なメソッドのみ実装すればよい",
+        backend, real
+    );
+    let real_doc = format!(" 実際のライブラリを呼び出す {} の実装", api);
+    let backend_doc = format!(
+        " {} の呼び出し先の切り替え (スレッドごとの設定が全スレッド共通の設定より優先される)",
+        api
+    );
+
+    quote! {
+        #[doc = #trait_doc]
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        pub trait #api {
+            #(
+                #[doc = #docs]
+                #sigs {
+                    #default_calls
+                }
+            )*
+        }
+
+        #[doc = #real_doc]
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct #real;
+
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        impl #api for #real {
+            #(
+                #sigs {
+                    #real_calls
+                }
+            )*
+        }
+
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        impl<A: #api + ?Sized> #api for std::sync::Arc<A> {
+            #(
+                #sigs {
+                    #arc_calls
+                }
+            )*
+        }
+
+        thread_local! {
+            static #thread_ident: std::cell::RefCell<Option<std::rc::Rc<dyn #api>>> =
+                const { std::cell::RefCell::new(None) };
+            static #bypass_ident: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+        }
+        static #global_ident: std::sync::RwLock<Option<std::sync::Arc<dyn #api + Send + Sync>>> =
+            std::sync::RwLock::new(None);
+        // 一度も差し替えられていない場合は thread_local、RwLock を参照しない
+        static #active_ident: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+        #[doc = #backend_doc]
+        pub struct #backend;
+
+        impl #backend {
+            /// このスレッドの呼び出し先を設定し、以前の呼び出し先を返す (None の場合は元に戻す)
+            pub fn set_thread(api: Option<std::rc::Rc<dyn #api>>) -> Option<std::rc::Rc<dyn #api>> {
+                #active_ident.store(true, std::sync::atomic::Ordering::Relaxed);
+                #thread_ident.replace(api)
+            }
+
+            /// 全スレッド共通の呼び出し先を設定し、以前の呼び出し先を返す (None の場合は元に戻す)
+            pub fn set_global(
+                api: Option<std::sync::Arc<dyn #api + Send + Sync>>,
+            ) -> Option<std::sync::Arc<dyn #api + Send + Sync>> {
+                #active_ident.store(true, std::sync::atomic::Ordering::Relaxed);
+                let mut global = #global_ident.write().unwrap_or_else(|e| e.into_inner());
+                std::mem::replace(&mut *global, api)
+            }
+
+            /// f の実行中のみ、このスレッドの呼び出し先を api にする (panic した場合も元に戻す)
+            pub fn scoped<R>(api: std::rc::Rc<dyn #api>, f: impl FnOnce() -> R) -> R {
+                struct Restore(Option<std::rc::Rc<dyn #api>>);
+                impl Drop for Restore {
+                    fn drop(&mut self) {
+                        #backend::set_thread(self.0.take());
+                    }
+                }
+                let _restore = Restore(Self::set_thread(Some(api)));
+                f()
+            }
+
+            /// 現在の呼び出し先 (差し替えられていない場合は None)
+            #[doc(hidden)]
+            pub fn current() -> Option<std::rc::Rc<dyn #api>> {
+                if !#active_ident.load(std::sync::atomic::Ordering::Relaxed) || #bypass_ident.get() {
+                    return None;
+                }
+                if let Some(api) = #thread_ident.with_borrow(|api| api.clone()) {
+                    return Some(api);
+                }
+                let global = #global_ident.read().unwrap_or_else(|e| e.into_inner()).clone()?;
+                Some(std::rc::Rc::new(global))
+            }
+
+            /// f の実行中は差し替えを無視して実際のライブラリを呼び出す
+            #[doc(hidden)]
+            pub fn real<R>(f: impl FnOnce() -> R) -> R {
+                struct Restore(bool);
+                impl Drop for Restore {
+                    fn drop(&mut self) {
+                        #bypass_ident.set(self.0);
+                    }
+                }
+                let _restore = Restore(#bypass_ident.replace(true));
+                f()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::format_ident;
+
+    #[test]
+    fn test_api_method() {
+        let method = ApiMethod::new(
+            &format_ident!("Print"),
+            &[
+                quote! { x: i32 },
+                quote! { text: impl AsRef<str> + Debug },
+                quote! { buffer: &mut (impl AsMut<[u8]>) },
+                quote! { label: impl ToString },
+            ],
+            &quote! { anyhow::Result<i32, DxLibError> },
+            &Generics::default(),
+            false,
+        )
+        .unwrap();
+        let expected = quote! {
+            fn Print(&self, x: i32, text: &str, buffer: &mut [u8], label: &str) -> anyhow::Result<i32, DxLibError>
+        };
+        assert_eq!(method.sig.to_string(), expected.to_string());
+
+        let dispatch = method.dispatch_stmt(&format_ident!("Api"));
+        let expected = quote! {
+            if let Some(__api) = ApiBackend::current() {
+                return __api.Print(
+                    x,
+                    AsRef::<str>::as_ref(&text),
+                    AsMut::<[u8]>::as_mut(buffer),
+                    &ToString::to_string(&label)
+                );
+            }
+        };
+        assert_eq!(dispatch.to_string(), expected.to_string());
+
+        let real_args = &method.real_args;
+        let expected = quote! { x, text, &mut &mut *buffer, label };
+        assert_eq!(quote! { #(#real_args),* }.to_string(), expected.to_string());
+        // 生ポインタの引数がある関数は unsafe fn のメソッドにし、unsafe ブロックで呼び出す
+        let method = ApiMethod::new(
+            &format_ident!("Read"),
+            &[quote! { buffer: *mut u8 }],
+            &quote! { i32 },
+            &Generics::default(),
+            true,
+        )
+        .unwrap();
+        let expected = quote! { unsafe fn Read(&self, buffer: *mut u8) -> i32 };
+        assert_eq!(method.sig.to_string(), expected.to_string());
+        let dispatch = method.dispatch_stmt(&format_ident!("Api"));
+        let expected = quote! {
+            if let Some(__api) = ApiBackend::current() {
+                return unsafe { __api.Read(buffer) };
+            }
+        };
+        assert_eq!(dispatch.to_string(), expected.to_string());
+    }
+}
@@ -0,0 +1,1586 @@
+// src/codegen.rs
+use crate::FunctionWithAttrs;
+use crate::api::ApiMethod;
+use crate::config::{FnRole, GenConfig};
+use crate::context::{ContextMethod, Receiver, context_error_value, receiver_of, state_ident};
+use crate::doc::{doc_tokens, error_condition_doc, generate_doc};
+use crate::generics::{resolve_generics, with_static_lifetimes};
+use crate::handle::extract_handle_attribute;
+use crate::utils::*;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{FnArg, Pat, PatType, Type, TypePath, TypeReference};
+
+// 生成された関数 (動的ロード時の関数テーブルの生成にも使用する)
+pub struct GeneratedFunction {
+    pub tokens: TokenStream,
+    pub extern_name: Ident,
+    pub fn_ptr_type: TokenStream,
+    // #![api_trait] のトレイトのメソッド
+    pub api_method: Option<ApiMethod>,
+    // #![context] の型のメソッド
+    pub context_method: Option<ContextMethod>,
+}
+
+// ラップ関数から C 関数を呼び出す方法
+#[derive(Clone, Copy)]
+pub enum CallMode<'a> {
+    // extern 宣言をリンクして呼び出す
+    Extern,
+    // 指定されたローダーの関数テーブルから呼び出す
+    Loader(&'a Ident),
+    // C 関数を呼び出さず、record::record_call で呼び出しを記録する
+    Record,
+}
+
+// 1つの関数シグネチャから extern 宣言(もしくは関数テーブル参照)とラップ関数を生成する
+pub fn generate_function(
+    config: &GenConfig,
+    func: &FunctionWithAttrs,
+    mode: CallMode,
+    cfg: &TokenStream,
+) -> syn::Result<GeneratedFunction> {
+    let FunctionWithAttrs { attrs, sig } = func;
+    validate_signature(func)?;
+    // 型パラメータはトレイト境界の impl Trait として扱う
+    let sig = &resolve_generics(sig)?;
+    let wrapper_name = extract_alias_attribute(attrs)?
+        .map(|alias| format_ident!("{}", alias))
+        .unwrap_or_else(|| sig.ident.clone());
+    let extern_name = format_ident!(
+        "{}{}{}",
+        config.func_name_top_prefix,
+        sig.ident,
+        config.func_name_down_prefix
+    );
+    let role = config.role_of(func)?;
+
+    let generics = &sig.generics;
+    let where_clause = &sig.generics.where_clause;
+
+    let mut wrapper_args = Vec::new();
+    let mut extern_args = Vec::new();
+    let mut convert_stmts = Vec::new();
+    let mut call_idents = Vec::new();
+    // #[out] 引数 (呼び出し後に値を取り出して戻り値にする)
+    let mut out_idents = Vec::new();
+    let mut out_types = Vec::new();
+    // 呼び出し成功後に実行する処理 (#[out_string] のデコード、文字コードの記録等)
+    let mut post_stmts = Vec::new();
+    // ラップ関数の引数 (#[out],#[out_string],#[len_of] 以外)
+    let mut call_args = Vec::new();
+
+    let Some(return_type) = get_return_type(sig) else {
+        return Err(syn::Error::new_spanned(
+            &sig.ident,
+            "戻り値の型を指定してください (戻り値のない関数は未対応です)",
+        ));
+    };
+
+    let error_condition =
+        extract_error_condition(attrs)?.unwrap_or_else(|| quote! { result as i32 == -1i32 });
+    let is_not_result = is_not_result_attribute(attrs);
+    let error_type = &config.error_type;
+    // #![context] の初期化の状態は、初期化、終了の関数の呼び出し成功時に更新する
+    let receiver = receiver_of(config, role, attrs, is_not_result)?;
+    if let Some(context) = &config.context {
+        let state = state_ident(context);
+        match receiver {
+            Some(Receiver::Init) => post_stmts.push(quote! { #state.initialize(); }),
+            Some(Receiver::Finalize) => post_stmts.push(quote! { #state.finalize(); }),
+            _ => {}
+        }
+    }
+    // bool、#[dx_enum] の戻り値は i32 として受け取って変換する
+    let is_bool_return = is_bool_type(return_type);
+    let is_enum_return = is_dx_enum_attribute(attrs);
+    if is_enum_return && is_not_result {
+        return Err(syn::Error::new_spanned(
+            &sig.ident,
+            "#[dx_enum] の戻り値は #[not_result] と併用できません",
+        ));
+    }
+    let extern_return_type = if is_bool_return || is_enum_return {
+        quote! { i32 }
+    } else {
+        quote! { #return_type }
+    };
+    // #[not_result] の関数がエラーの場合に返す値
+    let fail_value = if is_bool_return {
+        quote! { false }
+    } else {
+        quote! { -1 }
+    };
+    // DxEnum の列挙型への変換 (対応する列挙子がない場合はエラーを返す)
+    let enum_value = |ty: &dyn quote::ToTokens, raw: &Ident| {
+        let function_name = wrapper_name.to_string();
+        let error_value = config.enum_error.as_ref().map_or_else(
+            || {
+                quote! {
+                    <#error_type>::from(anyhow::anyhow!(
+                        "Invalid value {} for {} in {}",
+                        #raw, stringify!(#ty), #function_name
+                    ))
+                }
+            },
+            |e| quote! { #e(#function_name, stringify!(#ty), #raw) },
+        );
+        quote! {
+            match <#ty as DxEnum>::from_raw(#raw) {
+                Some(value) => value,
+                None => return Err(#error_value),
+            }
+        }
+    };
+    // #[wide] の関数は文字列を UTF-16 (*const u16) で渡す
+    let is_wide = is_wide_attribute(attrs);
+    let (string_holder, string_char) = if is_wide {
+        (quote! { WideStringHolder }, quote! { u16 })
+    } else {
+        (quote! { CStringHolder }, quote! { c_char })
+    };
+
+    // 文字列の引数を保持する変数の宣言を生成する
+    // 途中にNUL文字が含まれている場合はエラーを返す (#[lossy] の場合はそこまでで切り詰める)
+    let is_lossy = is_lossy_attribute(attrs);
+    let string_holder_stmt = |binding: TokenStream,
+                              holder_ty: &TokenStream,
+                              args: TokenStream,
+                              param: &Ident,
+                              param_attrs: &[syn::Attribute]| {
+        if is_lossy || is_lossy_attribute(param_attrs) {
+            return quote! {
+                let #binding = #holder_ty::new_lossy(#args);
+            };
+        }
+        let error_arm = if is_not_result {
+            quote! { Err(_) => return #fail_value }
+        } else {
+            let function_name = wrapper_name.to_string();
+            let param_name = param.to_string();
+            let error_value = config.nul_error.as_ref().map_or_else(
+                || {
+                    quote! {
+                        <#error_type>::from(anyhow::anyhow!(
+                            "Interior NUL byte at position {} in {}({})",
+                            e.position, #function_name, #param_name
+                        ))
+                    }
+                },
+                |f| quote! { #f(#function_name, #param_name, e.position) },
+            );
+            quote! { Err(e) => return Err(#error_value) }
+        };
+        quote! {
+            let #binding = match #holder_ty::new(#args) {
+                Ok(holder) => holder,
+                #error_arm,
+            };
+        }
+    };
+    // 文字列の引数 (エラー時には変換前の文字列を引数の値として表示する)
+    let mut string_args = Vec::new();
+    // IntoDxArg,DxArgMut で変換する引数と、エラー時に表示する型名
+    let mut trait_args: Vec<(Ident, String)> = Vec::new();
+    // bool、Option<T>、#[dx_enum] の引数と、エラー時、トレース時に表示する変換前の値 (記録時は C に渡す値を記録する)
+    let mut original_args: Vec<(Ident, TokenStream)> = Vec::new();
+    // #[callback] の引数 (エラー時、記録時はクロージャの有無のみを表示する)
+    let mut callback_args = Vec::new();
+    let mut string_arg_stmt = |holder_ident: &Ident,
+                               value: TokenStream,
+                               param: &Ident,
+                               param_attrs: &[syn::Attribute]| {
+        string_args.push((param.clone(), holder_ident.clone()));
+        string_holder_stmt(
+            quote! { #holder_ident },
+            &string_holder,
+            value,
+            param,
+            param_attrs,
+        )
+    };
+    // C側で書き込まれた文字列のデコードに失敗した場合のエラー値
+    let string_error = |param: &Ident| {
+        let function_name = wrapper_name.to_string();
+        let param_name = param.to_string();
+        config.string_error.as_ref().map_or_else(
+            || {
+                quote! {
+                    <#error_type>::from(e.context(format!(
+                        "Invalid string data in {}({})",
+                        #function_name, #param_name
+                    )))
+                }
+            },
+            |f| quote! { #f(#function_name, #param_name, e) },
+        )
+    };
+
+    // #[len_of] で参照するため、先に #[out_string] の容量を集めておく
+    let mut out_string_capacities = Vec::new();
+    // &mut String の引数 (#[len_of] で実行時のバッファの容量を参照する)
+    let mut in_out_strings = Vec::new();
+    // 生ポインタの引数 (#[len_of] で長さを指定した場合はスライス版の関数を生成する)
+    let mut raw_pointers = Vec::new();
+    for arg in sig.inputs.iter() {
+        if let FnArg::Typed(PatType { pat, ty, attrs, .. }) = arg
+            && let Pat::Ident(pi) = &**pat
+        {
+            if let Some(capacity) = extract_out_string_capacity(attrs)? {
+                out_string_capacities.push((pi.ident.clone(), capacity));
+            } else if is_mut_string(ty) {
+                in_out_strings.push(pi.ident.clone());
+            } else if let Some(slice_ty) = raw_pointer_slice(ty) {
+                raw_pointers.push((pi.ident.clone(), (**ty).clone(), slice_ty));
+            }
+        }
+    }
+    // スライス版の関数で、スライスの長さを渡す引数と対象の引数
+    let mut slice_lens = Vec::new();
+    // #[len_of] の引数と対象の引数 (バッファの確保後に設定する)
+    let mut len_targets = Vec::new();
+
+    for arg in sig.inputs.iter() {
+        if let FnArg::Typed(PatType { pat, ty, attrs, .. }) = arg {
+            // パターン付き引数は validate_signature でエラーにしている
+            let Pat::Ident(pi) = &**pat else {
+                continue;
+            };
+            let ident = &pi.ident;
+
+            if is_char_code_format_attribute(attrs) {
+                // 以降の文字列の変換で使用する文字コードとして記録する
+                post_stmts.push(quote! {
+                    set_char_code_format(#ident);
+                });
+            }
+
+            if is_out_attribute(attrs) {
+                // #[out] の場合は *mut T を渡し、呼び出し後の値を戻り値にする
+                if is_not_result {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "#[out] は #[not_result] と併用できません",
+                    ));
+                }
+                if is_bool_type(ty) || is_dx_enum_attribute(attrs) {
+                    // bool、#[dx_enum] の場合は i32 で受け取って変換する
+                    let raw_ident = format_ident!("__{}_raw", ident);
+                    extern_args.push(quote! { #ident: *mut i32 });
+                    convert_stmts.push(quote! {
+                        let mut #raw_ident: i32 = 0;
+                    });
+                    let value = if is_bool_type(ty) {
+                        quote! { #raw_ident != 0 }
+                    } else {
+                        enum_value(ty, &raw_ident)
+                    };
+                    post_stmts.push(quote! {
+                        let #ident: #ty = #value;
+                    });
+                    call_idents.push(quote! { &mut #raw_ident });
+                } else {
+                    extern_args.push(quote! { #ident: *mut #ty });
+                    convert_stmts.push(quote! {
+                        let mut #ident: #ty = Default::default();
+                    });
+                    call_idents.push(quote! { &mut #ident });
+                }
+                out_idents.push(ident.clone());
+                out_types.push(quote! { #ty });
+                continue;
+            }
+
+            if let Some(capacity) = extract_out_string_capacity(attrs)? {
+                // #[out_string(capacity = N)] の場合はバッファを確保して渡し、
+                // 書き込まれた文字列を String として戻り値にする
+                if is_not_result {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "#[out_string] は #[not_result] と併用できません",
+                    ));
+                }
+                if !matches!(&**ty, Type::Path(TypePath { path, .. }) if path.is_ident("String")) {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "#[out_string] の引数は String 型で指定してください",
+                    ));
+                }
+                extern_args.push(quote! { #ident: *mut #string_char });
+
+                // 終端のNUL文字の分を余分に確保する
+                // (UTF-16,UTF-32 の文字コードでも収まるように1文字4バイトで確保する)
+                let buffer_ident = format_ident!("__{}_buffer", ident);
+                convert_stmts.push(if is_wide {
+                    quote! {
+                        let mut #buffer_ident: Vec<u16> = vec![0; (#capacity) as usize + 1];
+                    }
+                } else {
+                    quote! {
+                        let mut #buffer_ident: Vec<u8> = vec![0; ((#capacity) as usize + 1) * 4];
+                    }
+                });
+                let decode_fn = if is_wide {
+                    quote! { decode_wide_c_string }
+                } else {
+                    quote! { decode_c_string }
+                };
+
+                let string_error = string_error(ident);
+                post_stmts.push(quote! {
+                    let #ident = match #decode_fn(&#buffer_ident) {
+                        Ok(value) => value,
+                        Err(e) => return Err(#string_error),
+                    };
+                });
+
+                call_idents.push(quote! { #buffer_ident.as_mut_ptr() as *mut #string_char });
+                out_idents.push(ident.clone());
+                out_types.push(quote! { String });
+                continue;
+            }
+
+            if let Some(target) = extract_len_of(attrs)?
+                && raw_pointers.iter().any(|(name, _, _)| *name == target)
+            {
+                // 生ポインタの長さの場合は、この関数では引数として残し、
+                // ポインタをスライスで受け取るスライス版の関数でスライスの長さを渡す
+                slice_lens.push((ident.clone(), (**ty).clone(), target));
+            } else if let Some(target) = extract_len_of(attrs)? {
+                // #[len_of(buffer)] の場合は対象の引数のバッファサイズ(文字列の場合は変換後の長さ)を自動的に渡す
+                // (文字列の引数は後に宣言される場合があるため、全ての引数を処理した後に決める)
+                extern_args.push(quote! { #ident: #ty });
+                len_targets.push((ident.clone(), (**ty).clone(), target));
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // エラー時に表示する引数
+            call_args.push(ident.clone());
+
+            if is_callback_attribute(attrs)
+                && let Some((dyn_ty, fn_args)) = extract_callback(ty)
+            {
+                // #[callback] の場合はクロージャを呼び出すトランポリンの関数ポインタを渡し (None の場合は NULL)、
+                // 登録中に C 側から呼び出される場合に備えて、呼び出し前にクロージャを関数ごとの CallbackSlot に登録する
+                // (失敗した場合は以前に登録したクロージャに戻し、渡されたクロージャは破棄する)
+                let abi = config.abi_of(attrs)?;
+                let inputs = fn_args.inputs.iter().collect::<Vec<_>>();
+                let output = &fn_args.output;
+                let input_idents = (0..inputs.len())
+                    .map(|i| format_ident!("__arg{}", i))
+                    .collect::<Vec<_>>();
+                let slot_ident =
+                    format_ident!("__{}_CALLBACK", ident.unraw().to_string().to_uppercase());
+                let trampoline_ident = format_ident!("__{}_trampoline", ident.unraw());
+                let registration_ident = format_ident!("__{}_registration", ident.unraw());
+                let fn_ptr_ty = quote! { unsafe extern #abi fn(#(#inputs),*) #output };
+                wrapper_args.push(quote! { #ident: #ty });
+                extern_args.push(quote! { #ident: Option<#fn_ptr_ty> });
+                convert_stmts.push(quote! {
+                    static #slot_ident: CallbackSlot<#dyn_ty> = CallbackSlot::new();
+                    extern #abi fn #trampoline_ident(#(#input_idents: #inputs),*) #output {
+                        #slot_ident.invoke(|f| f(#(#input_idents),*))
+                    }
+                    let (#ident, #registration_ident): (Option<#fn_ptr_ty>, _) = match #ident {
+                        Some(callback) => (Some(#trampoline_ident), #slot_ident.register(Some(callback))),
+                        None => (None, #slot_ident.register(None)),
+                    };
+                });
+                post_stmts.push(quote! {
+                    #registration_ident.commit();
+                });
+                callback_args.push(ident.clone());
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // #[handle] で宣言されたハンドル型の参照 (&GraphHandle 等) は i32 として渡す
+            if let Type::Reference(TypeReference {
+                elem,
+                mutability: None,
+                ..
+            }) = &**ty
+                && let Type::Path(TypePath { path, .. }) = &**elem
+                && config
+                    .handles
+                    .iter()
+                    .any(|handle| path.is_ident(&handle.ty))
+            {
+                wrapper_args.push(quote! { #ident: #ty });
+                extern_args.push(quote! { #ident: i32 });
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_raw();
+                });
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // bool は TRUE/FALSE、#[dx_enum] は列挙子の値の i32 として渡す
+            let to_raw = |value: &Ident| {
+                if is_dx_enum_attribute(attrs) {
+                    Some(quote! { DxEnum::to_raw(#value) })
+                } else {
+                    None
+                }
+            };
+
+            if let Some(inner_ty) = is_option(ty) {
+                // Option<T> の場合
+                wrapper_args.push(quote! { #ident: Option<#inner_ty> });
+
+                if let Some(message) = unsupported_option_message(inner_ty) {
+                    return Err(syn::Error::new_spanned(inner_ty, message));
+                }
+                let default_expr =
+                    extract_default_expr(attrs)?.unwrap_or_else(|| quote! { Default::default() });
+
+                // エラー時、トレース時は既定値で置き換える前の None/Some(値) を表示する
+                let some_ident = format_ident!("__{}_some", ident);
+                convert_stmts.push(quote! {
+                    let #some_ident = #ident.is_some();
+                    let #ident = match #ident {
+                        Some(value) => value,
+                        None => #default_expr,
+                    };
+                });
+
+                let value_ident = format_ident!("__{}_value", ident);
+                let value = match to_raw(ident) {
+                    Some(raw) => {
+                        convert_stmts
+                            .push(quote! { let #value_ident = #ident; let #ident = #raw; });
+                        extern_args.push(quote! { #ident: i32 });
+                        &value_ident
+                    }
+                    None if is_bool_type(inner_ty) => {
+                        convert_stmts.push(
+                            quote! { let #value_ident = #ident; let #ident = #ident as i32; },
+                        );
+                        extern_args.push(quote! { #ident: i32 });
+                        &value_ident
+                    }
+                    None => {
+                        extern_args.push(quote! { #ident: #inner_ty });
+                        ident
+                    }
+                };
+                original_args.push((
+                    ident.clone(),
+                    quote! {
+                        if #some_ident {
+                            format!("Some({:?})", #value)
+                        } else {
+                            String::from("None")
+                        }
+                    },
+                ));
+                call_idents.push(quote! { #ident });
+
+                continue;
+            }
+
+            if is_bool_type(ty) || is_dx_enum_attribute(attrs) {
+                let raw = to_raw(ident).unwrap_or_else(|| quote! { #ident as i32 });
+                wrapper_args.push(quote! { #ident: #ty });
+                extern_args.push(quote! { #ident: i32 });
+                // bool、列挙型は Copy のため、エラー時、トレース時に表示する変換前の値を残しておく
+                let value_ident = format_ident!("__{}_value", ident);
+                convert_stmts.push(quote! {
+                    let #value_ident = #ident;
+                    let #ident = #raw;
+                });
+                original_args.push((ident.clone(), quote! { format!("{:?}", #value_ident) }));
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            if is_impl_as_ref_type(ty) {
+                // まず ty 自体が参照型かどうかを判定
+                if let Type::Reference(ref_type) = &**ty
+                    && let Some(inner_ty) = extract_as_ref_generic(&ref_type.elem)
+                {
+                    // &impl AsRef<str>,&mut impl AsRef<str> のどちらも *const c_char として渡す
+                    if let Type::Path(type_path) = inner_ty
+                        && type_path.path.segments.last().is_some_and(|segment| {
+                            segment.ident == "str" || segment.ident == "String"
+                        })
+                    {
+                        wrapper_args.push(quote! { #ident: #ty });
+
+                        extern_args.push(quote! {
+                            #ident: *const #string_char
+                        });
+
+                        let holder_ident = format_ident!("__{}_holder", ident);
+                        convert_stmts.push(string_arg_stmt(
+                            &holder_ident,
+                            quote! { #ident.as_ref() },
+                            ident,
+                            attrs,
+                        ));
+                        convert_stmts.push(quote! {
+                            let #ident = #holder_ident.as_ptr();
+                        });
+
+                        call_idents.push(quote! { #ident });
+                        continue;
+                    }
+                }
+
+                // 通常の impl AsRef<T> 型（参照ではない）
+                if let Some(inner_ty) = extract_as_ref_generic(ty) {
+                    if let Type::Path(type_path) = inner_ty
+                        && type_path.path.segments.last().is_some_and(|segment| {
+                            segment.ident == "str" || segment.ident == "String"
+                        })
+                    {
+                        wrapper_args.push(quote! { #ident: #ty });
+
+                        extern_args.push(quote! {
+                            #ident: *const #string_char
+                        });
+
+                        let holder_ident = format_ident!("__{}_holder", ident);
+                        convert_stmts.push(string_arg_stmt(
+                            &holder_ident,
+                            quote! { #ident.as_ref() },
+                            ident,
+                            attrs,
+                        ));
+                        convert_stmts.push(quote! {
+                            let #ident = #holder_ident.as_ptr();
+                        });
+
+                        call_idents.push(quote! { #ident });
+                        continue;
+                    }
+
+                    // 汎用パターン（*const T）
+                    wrapper_args.push(quote! { #ident: #ty });
+
+                    extern_args.push(quote! {
+                        #ident: *const #inner_ty
+                    });
+
+                    convert_stmts.push(quote! {
+                        let #ident = #ident.as_ref().as_ptr();
+                    });
+
+                    call_idents.push(quote! { #ident });
+                    continue;
+                }
+            } else if is_impl_as_mut_type(ty) {
+                // まず `ty` 自体が参照型かどうかを判定する
+                if let Type::Reference(ref_type) = &**ty {
+                    if ref_type.mutability.is_some() {
+                        // &mut impl AsMut<[T]> にマッチ
+                        if let Some(inner_ty) = extract_as_mut_generic(&ref_type.elem)
+                            && let Type::Slice(slice) = inner_ty
+                        {
+                            let elem_ty = &slice.elem;
+
+                            wrapper_args.push(quote! { #ident: #ty });
+
+                            extern_args.push(quote! {
+                                #ident: *mut #elem_ty
+                            });
+
+                            convert_stmts.push(quote! {
+                                let #ident = #ident.as_mut().as_mut_ptr();
+                            });
+
+                            call_idents.push(quote! { #ident });
+                            continue;
+                        }
+                    } else {
+                        // &impl AsMut<[T]> にマッチ
+                        if let Some(inner_ty) = extract_as_mut_generic(&ref_type.elem)
+                            && let Type::Slice(slice) = inner_ty
+                        {
+                            let elem_ty = &slice.elem;
+
+                            wrapper_args.push(quote! { #ident: #ty });
+
+                            extern_args.push(quote! {
+                                #ident: *mut #elem_ty
+                            });
+
+                            convert_stmts.push(quote! {
+                                let #ident = #ident.as_mut().as_mut_ptr();
+                            });
+
+                            call_idents.push(quote! { #ident });
+                            continue;
+                        }
+                    }
+                }
+
+                // 通常の impl AsMut<[T]> 型の処理
+                if let Some(inner_ty) = extract_as_mut_generic(ty)
+                    && let Type::Slice(slice) = inner_ty
+                {
+                    let elem_ty = &slice.elem;
+
+                    wrapper_args.push(quote! { #ident: #ty });
+
+                    extern_args.push(quote! {
+                        #ident: *mut #elem_ty
+                    });
+
+                    convert_stmts.push(quote! {
+                        let #ident = #ident.as_mut().as_mut_ptr();
+                    });
+
+                    call_idents.push(quote! { #ident });
+                    continue;
+                }
+            }
+
+            if is_impl_to_string(ty) {
+                wrapper_args.push(quote! { #ident: #ty });
+                extern_args.push(quote! { #ident: *const #string_char });
+
+                let holder_ident = format_ident!("__{}_holder", ident);
+                convert_stmts.push(string_arg_stmt(
+                    &holder_ident,
+                    quote! { #ident.to_string() },
+                    ident,
+                    attrs,
+                ));
+                convert_stmts.push(quote! {
+                    let #ident = #holder_ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            if is_impl_display(ty) {
+                wrapper_args.push(quote! { #ident: #ty });
+                extern_args.push(quote! { #ident: *const #string_char });
+
+                let holder_ident = format_ident!("__{}_holder", ident);
+                convert_stmts.push(string_arg_stmt(
+                    &holder_ident,
+                    quote! { #ident.to_string() },
+                    ident,
+                    attrs,
+                ));
+                convert_stmts.push(quote! {
+                    let #ident = #holder_ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // `impl Into<Vec<T>>` の場合、不変と可変を分けて処理
+            if is_impl_trait_into_vec(ty) {
+                // Vec<T> の T を取得
+                let inner_ty = extract_vec_inner_type_from_impl_trait(ty);
+
+                if let Some(inner_ty) = inner_ty {
+                    // 不変Vec<T> → *const T
+                    if is_ref_vec_type(ty).is_some() {
+                        wrapper_args.push(quote! { #ident: impl Into<Vec<#inner_ty>> });
+                        extern_args.push(quote! { #ident: *const #inner_ty });
+
+                        convert_stmts.push(quote! {
+                            let #ident = #ident.as_ptr();
+                        });
+
+                        call_idents.push(quote! { #ident });
+                        continue;
+                    }
+                    // 可変Vec<T> → *mut T
+                    else if is_mut_ref_vec_type(ty).is_some() {
+                        wrapper_args.push(quote! { #ident: impl Into<Vec<#inner_ty>> });
+                        extern_args.push(quote! { #ident: *mut #inner_ty });
+
+                        convert_stmts.push(quote! {
+                            let #ident = #ident.as_mut_ptr();
+                        });
+
+                        call_idents.push(quote! { #ident });
+                        continue;
+                    }
+                }
+            }
+            // 配列の場合は、*const Tに変換
+            if let Some((inner_ty, n)) = extract_array(ty) {
+                wrapper_args.push(quote! { #ident: [#inner_ty;#n] });
+                extern_args.push(quote! { #ident: *const #inner_ty });
+
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            } else if let Some((inner_ty, n)) = extract_mut_array(ty) {
+                wrapper_args.push(quote! { #ident: &mut [#inner_ty;#n] });
+                extern_args.push(quote! { #ident: *mut #inner_ty });
+
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_mut_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+            // 不変スライスの場合は、*const Tに変換
+            if is_slice(ty) {
+                let inner_ty = extract_slice(ty);
+                wrapper_args.push(quote! { #ident: &[#inner_ty] });
+                extern_args.push(quote! { #ident: *const #inner_ty });
+
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            // 可変スライスの場合は、*mut Tに変換
+            } else if is_mut_slice(ty) {
+                let inner_ty = extract_mut_slice(ty);
+                wrapper_args.push(quote! { #ident: &mut [#inner_ty] });
+                extern_args.push(quote! { #ident: *mut #inner_ty });
+
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_mut_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+            // Vec<T>の場合は、*const Tに変換
+            if is_vec_type(ty) {
+                let inner_ty = extract_vec_inner_type(ty);
+                wrapper_args.push(quote! { #ident: Vec<#inner_ty> });
+                extern_args.push(quote! { #ident: *const #inner_ty });
+
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            // Vec<T>の場合は、*mut Tに変換
+            } else if is_mut_vec_type(ty) {
+                let inner_ty = extract_vec_inner_type(ty);
+                wrapper_args.push(quote! { #ident: &mut Vec<#inner_ty> });
+                extern_args.push(quote! { #ident: *mut #inner_ty });
+
+                convert_stmts.push(quote! {
+                    let #ident = #ident.as_mut_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // &str の場合は *const c_char に変換
+            if let Type::Reference(TypeReference {
+                elem,
+                mutability: None,
+                ..
+            }) = &**ty
+                && let Type::Path(TypePath { path, .. }) = &**elem
+                && path.is_ident("str")
+            {
+                wrapper_args.push(quote! { #ident: &str });
+                extern_args.push(quote! { #ident: *const #string_char });
+
+                let holder_ident = format_ident!("__{}_holder", ident);
+                convert_stmts.push(string_arg_stmt(
+                    &holder_ident,
+                    quote! { #ident.to_string() },
+                    ident,
+                    attrs,
+                ));
+                convert_stmts.push(quote! {
+                    let #ident = #holder_ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // String の場合は *const c_char に変換
+            if let Type::Path(TypePath { path, .. }) = &**ty
+                && path.is_ident("String")
+            {
+                wrapper_args.push(quote! { #ident: String });
+                extern_args.push(quote! { #ident: *const #string_char });
+
+                let holder_ident = format_ident!("__{}_holder", ident);
+                convert_stmts.push(string_arg_stmt(
+                    &holder_ident,
+                    quote! { #ident.to_string() },
+                    ident,
+                    attrs,
+                ));
+                convert_stmts.push(quote! {
+                    let #ident = #holder_ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // &mut String の場合は書き込み可能なバッファ (*mut c_char) を渡し、
+            // 呼び出し成功後に書き込まれた文字列で元の String を更新する
+            if is_mut_string(ty) {
+                wrapper_args.push(quote! { #ident: &mut String });
+                extern_args.push(quote! { #ident: *mut #string_char });
+
+                let buffer_ident = format_ident!("__{}_buffer", ident);
+                let buffer_ty = if is_wide {
+                    quote! { WideStringBuffer }
+                } else {
+                    quote! { StringBuffer }
+                };
+                // #[capacity = N] と String::capacity() の大きい方を書き込める文字数とする
+                let capacity = match extract_capacity(attrs)? {
+                    Some(capacity) => quote! { #ident.capacity().max((#capacity) as usize) },
+                    None => quote! { #ident.capacity() },
+                };
+                convert_stmts.push(string_holder_stmt(
+                    quote! { mut #buffer_ident },
+                    &buffer_ty,
+                    quote! { #ident.as_str(), #capacity },
+                    ident,
+                    attrs,
+                ));
+
+                let error_arm = if is_not_result {
+                    quote! { Err(_) => return #fail_value }
+                } else {
+                    let string_error = string_error(ident);
+                    quote! { Err(e) => return Err(#string_error) }
+                };
+                post_stmts.push(quote! {
+                    *#ident = match #buffer_ident.decode() {
+                        Ok(value) => value,
+                        #error_arm,
+                    };
+                });
+
+                call_idents.push(quote! { #buffer_ident.as_mut_ptr() });
+                continue;
+            }
+
+            // &String の場合は *const c_char に変換
+            if let Type::Reference(TypeReference { elem, .. }) = &**ty
+                && let Type::Path(TypePath { path, .. }) = &**elem
+                && path.is_ident("String")
+            {
+                wrapper_args.push(quote! { #ident: &String });
+                extern_args.push(quote! { #ident: *const #string_char });
+                let holder_ident = format_ident!("__{}_holder", ident);
+                convert_stmts.push(string_arg_stmt(
+                    &holder_ident,
+                    quote! { #ident.to_string() },
+                    ident,
+                    attrs,
+                ));
+                convert_stmts.push(quote! {
+                    let #ident = #holder_ident.as_ptr();
+                });
+
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+            // C に渡せない型はエラーにする
+            if let Some(message) = unsupported_type_message(ty) {
+                return Err(syn::Error::new_spanned(ty, message));
+            }
+            // 数値、ポインタ等はそのまま渡す
+            if is_c_type(ty) {
+                wrapper_args.push(quote! { #ident: #ty });
+                extern_args.push(quote! { #ident: #ty });
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+
+            // それ以外の型は IntoDxArg (&mut T は DxArgMut) で変換して渡す
+            // extern 宣言では参照のライフタイムを 'static にした型で Raw を求める
+            wrapper_args.push(quote! { #ident: #ty });
+            trait_args.push((ident.clone(), format!("<{}>", doc_tokens(ty))));
+            if let Type::Reference(TypeReference {
+                elem,
+                mutability: Some(_),
+                ..
+            }) = &**ty
+            {
+                let raw_ty = with_static_lifetimes(elem);
+                extern_args.push(quote! { #ident: <#raw_ty as DxArgMut>::Raw });
+                convert_stmts.push(quote! {
+                    let #ident = DxArgMut::as_dx_arg_mut(#ident);
+                });
+                call_idents.push(quote! { #ident });
+                continue;
+            }
+            let raw_ty = with_static_lifetimes(ty);
+            extern_args.push(quote! { #ident: <#raw_ty as IntoDxArg>::Raw });
+            let arg_ident = format_ident!("__{}_arg", ident);
+            let error_arm = if is_not_result {
+                quote! { Err(_) => return #fail_value }
+            } else {
+                let message = format!("Invalid argument {} in {}", ident.unraw(), wrapper_name);
+                quote! { Err(e) => return Err(<#error_type>::from(e.context(#message))) }
+            };
+            convert_stmts.push(quote! {
+                let #arg_ident = match IntoDxArg::into_dx_arg(#ident) {
+                    Ok(arg) => arg,
+                    #error_arm,
+                };
+            });
+            call_idents.push(quote! { #arg_ident.raw });
+        }
+    }
+
+    let mut len_stmts = Vec::new();
+    for (ident, ty, target) in &len_targets {
+        let capacity = if let Some((_, capacity)) = out_string_capacities
+            .iter()
+            .find(|(name, _)| name == target)
+        {
+            quote! { (#capacity) }
+        } else if in_out_strings.contains(target) {
+            let buffer_ident = format_ident!("__{}_buffer", target);
+            quote! { #buffer_ident.capacity() }
+        } else if let Some((_, holder_ident)) =
+            string_args.iter().find(|(param, _)| param == target)
+        {
+            quote! { #holder_ident.len() }
+        } else {
+            return Err(syn::Error::new_spanned(
+                target,
+                "#[len_of] には #[out_string]、&mut String、文字列か生ポインタの引数を指定してください",
+            ));
+        };
+        len_stmts.push(quote! {
+            let #ident = #capacity as #ty;
+        });
+    }
+
+    // #[format] の関数は ... の前の文字列の引数に書式化済みの文字列を受け取り、
+    // 書式文字列を "%s" に固定して文字列を可変長引数として渡す (文字列内の % が書式として解釈されない)
+    let format_macro = extract_format_macro(attrs)?;
+    if let Some(macro_name) = &format_macro {
+        let format_param = call_args.last().filter(|ident| {
+            string_args.iter().any(|(param, _)| param == *ident)
+                && sig.inputs.last().is_some_and(|arg| {
+                    matches!(arg, FnArg::Typed(PatType { pat, .. })
+                        if matches!(&**pat, Pat::Ident(pi) if pi.ident == **ident))
+                })
+        });
+        if format_param.is_none() {
+            return Err(syn::Error::new_spanned(
+                macro_name,
+                "#[format] の関数は ... の前の引数を文字列 (impl AsRef<str>,&str,String 等) にしてください",
+            ));
+        }
+        let pattern = if is_wide {
+            quote! { [b'%' as u16, b's' as u16, 0].as_ptr() }
+        } else {
+            quote! { c"%s".as_ptr() }
+        };
+        let text = call_idents.pop().unwrap();
+        call_idents.push(pattern);
+        call_idents.push(text);
+        extern_args.push(quote! { ... });
+    }
+
+    // エラー時、記録時に表示する引数名と値
+    // 文字列は変換前の文字列、IntoDxArg,DxArgMut で変換した引数は Debug を要求しないため型名にする
+    let arg_values = |fallback: &dyn Fn(&Ident) -> TokenStream| {
+        call_args
+            .iter()
+            .map(|ident| {
+                let value = if let Some((_, holder_ident)) =
+                    string_args.iter().find(|(param, _)| param == ident)
+                {
+                    quote! { format!("{:?}", #holder_ident.as_str()) }
+                } else if let Some((_, type_name)) =
+                    trait_args.iter().find(|(param, _)| param == ident)
+                {
+                    quote! { String::from(#type_name) }
+                } else if callback_args.contains(ident) {
+                    quote! { String::from(if #ident.is_some() { "<callback>" } else { "None" }) }
+                } else {
+                    fallback(ident)
+                };
+                (ident.unraw(), value)
+            })
+            .collect::<Vec<_>>()
+    };
+    let arg_tuples = |fallback: &dyn Fn(&Ident) -> TokenStream| {
+        arg_values(fallback).into_iter().map(|(name, value)| {
+            let name = name.to_string();
+            quote! { (#name, #value) }
+        })
+    };
+
+    let abi = config.abi_of(attrs)?;
+    // extern 宣言、もしくは関数テーブルから呼び出す関数を決める
+    let (extern_block, resolve_stmt, callee) = match mode {
+        CallMode::Extern => {
+            let link_attr = config.link_attribute(config.link_kind_of(attrs)?);
+            let extern_block = quote! {
+                #cfg
+                #link_attr
+                unsafe extern #abi {
+                    fn #extern_name(#(#extern_args),*) -> #extern_return_type;
+                }
+            };
+            (extern_block, None, quote! { #extern_name })
+        }
+        CallMode::Loader(loader) => {
+            let symbol_name = extern_name.to_string();
+            let not_available = if !is_not_result {
+                let error_value = config.symbol_error.as_ref().map_or_else(
+                    || {
+                        quote! {
+                            <#error_type>::from(anyhow::anyhow!("Symbol {} is not available", #symbol_name))
+                        }
+                    },
+                    |e| quote! { #e(#symbol_name) },
+                );
+                quote! { return Err(#error_value) }
+            } else {
+                quote! { return #fail_value }
+            };
+            let resolve_stmt = quote! {
+                let __extern_fn = match #loader::get().and_then(|table| table.#extern_name) {
+                    Some(f) => f,
+                    None => #not_available,
+                };
+            };
+            (
+                TokenStream::new(),
+                Some(resolve_stmt),
+                quote! { __extern_fn },
+            )
+        }
+        CallMode::Record => (TokenStream::new(), None, TokenStream::new()),
+    };
+    // 変換前の引数の値 (Debug 形式)
+    let original_value = |ident: &Ident| {
+        original_args
+            .iter()
+            .find(|(original, _)| original == ident)
+            .map_or_else(
+                || quote! { format!("{:?}", #ident) },
+                |(_, value)| value.clone(),
+            )
+    };
+    // 記録時は変換後の引数を記録し、設定された戻り値を C 関数の戻り値とする
+    let (record_stmt, call_expr) = match mode {
+        CallMode::Record => {
+            let function_name = wrapper_name.to_string();
+            let args = arg_tuples(&|ident| quote! { RecordArg::record_arg(&#ident) });
+            (
+                Some(quote! {
+                    let __record_args: Vec<(&'static str, String)> = vec![#(#args),*];
+                }),
+                quote! {
+                    record_call::<_, #extern_return_type>(#function_name, __record_args, (#(#call_idents,)*))
+                },
+            )
+        }
+        _ => (None, quote! { unsafe { #callee(#(#call_idents),*) } }),
+    };
+
+    // #[handle] の関数は戻り値をハンドル型にする
+    let handle_ty = match extract_handle_attribute(attrs)? {
+        Some((ty, _)) if is_not_result || !out_idents.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "#[handle] は #[not_result],#[out],#[out_string] と併用できません",
+            ));
+        }
+        Some((ty, _)) => Some(ty),
+        None => None,
+    };
+
+    // #[out] 引数がある場合は、その値(複数の場合はタプル)を戻り値にする
+    let (ok_type, ok_value) = match out_idents.len() {
+        0 => match &handle_ty {
+            Some(ty) => (quote! { #ty }, quote! { #ty::from_raw(result) }),
+            None if is_bool_return => (quote! { bool }, quote! { result != 0 }),
+            None if is_enum_return => (
+                quote! { #return_type },
+                enum_value(return_type, &format_ident!("result")),
+            ),
+            None => (quote! { #return_type }, quote! { result }),
+        },
+        1 => (quote! { #(#out_types)* }, quote! { #(#out_idents)* }),
+        _ => (quote! { (#(#out_types),*) }, quote! { (#(#out_idents),*) }),
+    };
+
+    // 生ポインタの引数がある場合は unsafe fn にする (ドキュメントに # Safety を追加する)
+    let is_unsafe = !raw_pointer_params(sig).is_empty();
+    let unsafety = is_unsafe.then(|| quote! { unsafe });
+
+    // #![api_trait] の指定がある場合は、差し替えられた呼び出し先へ転送する
+    let wrapper_return = if is_not_result {
+        quote! { #return_type }
+    } else {
+        quote! { anyhow::Result<#ok_type, #error_type> }
+    };
+    let api_method = match &config.api_trait {
+        Some(_) => Some(ApiMethod::new(
+            &wrapper_name,
+            &wrapper_args,
+            &wrapper_return,
+            generics,
+            is_unsafe,
+        )?),
+        None => None,
+    };
+    let dispatch_stmt = config
+        .api_trait
+        .as_ref()
+        .zip(api_method.as_ref())
+        .map(|(api, method)| method.dispatch_stmt(api));
+    let context_method = match receiver {
+        Some(receiver) => Some(ContextMethod::new(
+            &wrapper_name,
+            receiver,
+            &wrapper_args,
+            &wrapper_return,
+            generics,
+            is_unsafe,
+            attrs,
+        )?),
+        None => None,
+    };
+    // #[context] の関数は、#![context_check] の条件(既定はデバッグビルド)で初期化済みか、
+    // 初期化したスレッドからの呼び出しかを検査する (記録時は C 関数を呼び出さないため検査しない)
+    let context_check_stmt = match (&config.context, receiver, mode) {
+        (_, _, CallMode::Record) => None,
+        (Some(context), Some(receiver), _) if receiver.is_checked() => {
+            let state = state_ident(context);
+            let check = &config.context_check;
+            let function_name = wrapper_name.to_string();
+            let error_return = if is_not_result {
+                quote! { return #fail_value }
+            } else {
+                let error_value = context_error_value(config, &function_name);
+                quote! { return Err(#error_value) }
+            };
+            Some(quote! {
+                #[cfg(#check)]
+                if let Err(e) = #state.check() {
+                    #error_return;
+                }
+            })
+        }
+        _ => None,
+    };
+
+    let (doc, call_block) = if !is_not_result {
+        // 役割ごとのエラー値 (未指定の場合は call_error の構造化されたエラー、それもない場合は汎用エラー)
+        let role_error = match role {
+            FnRole::Init => config.init_error.as_ref(),
+            FnRole::Finalize => config.finalize_error.as_ref(),
+            FnRole::Normal => None,
+        };
+        let call_error = config.call_error_of(attrs)?;
+        let error_name = match (role_error, &call_error) {
+            (Some(e), _) => doc_tokens(e),
+            (None, Some(path)) => doc_tokens(path),
+            (None, None) => doc_tokens(error_type),
+        };
+        let doc = generate_doc(
+            func,
+            &extern_name,
+            &format!("{}は `{}` を返す", error_condition_doc(attrs), error_name),
+        );
+        let error_value = match (role_error, call_error) {
+            (Some(e), _) => quote! { #e },
+            // 関数名、戻り値、引数を持つ構造化されたエラー
+            (None, Some(path)) => {
+                let function_name = wrapper_name.to_string();
+                let args = arg_tuples(&original_value);
+                quote! {
+                    #path {
+                        function: #function_name,
+                        code: result as i64,
+                        args: vec![#(#args),*],
+                    }
+                }
+            }
+            (None, None) => quote! {
+                <#error_type>::from(anyhow::anyhow!("Error in {}", stringify!(#wrapper_name)))
+            },
+        };
+
+        let call_block = quote! {
+            {
+                let result: #extern_return_type = #call_expr;
+                if #error_condition {
+                    return Err(#error_value);
+                } else {
+                    #(#post_stmts)*
+                    return Ok(#ok_value);
+                }
+            }
+        };
+        (doc, call_block)
+    } else {
+        let doc = generate_doc(
+            func,
+            &extern_name,
+            &format!(
+                "{}は `{}` を返す",
+                error_condition_doc(attrs),
+                if is_bool_return { "false" } else { "-1" }
+            ),
+        );
+        let call_block = quote! {
+            {
+                let result: #extern_return_type = #call_expr;
+                if #error_condition {
+                    return #fail_value;
+                } else {
+                    #(#post_stmts)*
+                    return #ok_value;
+                }
+            }
+        };
+        (doc, call_block)
+    };
+
+    // #![trace_feature] が有効な場合は、呼び出しをクロージャで包んで戻り値と経過時間を tracing に出力する
+    // (引数の変換に失敗した場合など、C 関数を呼び出す前のエラーは出力しない)
+    let call_block = match &config.trace_feature {
+        Some(feature) if !is_trace_skip_attribute(attrs)? => {
+            let function_name = wrapper_name.to_string();
+            let fields = arg_values(&original_value)
+                .into_iter()
+                .map(|(name, value)| quote! { #name = %#value });
+            quote! {
+                #[cfg(feature = #feature)]
+                {
+                    let __trace_start = std::time::Instant::now();
+                    let __result = (|| -> #wrapper_return { #call_block })();
+                    tracing::debug!(
+                        function = #function_name,
+                        #(#fields,)*
+                        result = ?__result,
+                        elapsed = ?__trace_start.elapsed(),
+                    );
+                    return __result;
+                }
+                #[cfg(not(feature = #feature))]
+                {
+                    #call_block
+                }
+            }
+        }
+        _ => call_block,
+    };
+    let wrapper_fn = quote! {
+        #cfg
+        #doc
+        pub #unsafety fn #wrapper_name #generics( #(#wrapper_args),* ) -> #wrapper_return #where_clause {
+            #dispatch_stmt
+            #context_check_stmt
+            #resolve_stmt
+            #(#convert_stmts)*
+            #(#len_stmts)*
+            #record_stmt
+
+            #call_block
+        }
+    };
+
+    // #[len_of] で生ポインタの長さを指定した関数は、ポインタをスライスで受け取り、
+    // スライスの長さを渡すスライス版の関数を生成する
+    //  FileRead_read(buffer: *mut c_void, read_size: i32, ..) -> FileRead_read_slice(buffer: &mut [u8], ..)
+    let slice_fn = if slice_lens.is_empty() {
+        None
+    } else {
+        let slice_name = format_ident!("{}_slice", wrapper_name);
+        let mut slice_args = Vec::new();
+        let mut slice_call_args = Vec::new();
+        let mut slice_len_stmts = Vec::new();
+        let mut remaining_pointers = raw_pointer_params(sig);
+        for wrapper_arg in &wrapper_args {
+            let FnArg::Typed(PatType { pat, .. }) = syn::parse2(wrapper_arg.clone())? else {
+                continue;
+            };
+            let Pat::Ident(pi) = &*pat else {
+                continue;
+            };
+            let ident = &pi.ident;
+            if let Some((_, len_ty, target)) = slice_lens.iter().find(|(len, _, _)| len == ident) {
+                // 長さが引数の型に収まらない場合は、最大値 (スライスより短い長さ) を渡す
+                slice_len_stmts.push(quote! {
+                    let #ident = <#len_ty>::try_from(#target.len()).unwrap_or(<#len_ty>::MAX);
+                });
+            } else if let Some((_, ptr_ty, slice_ty)) = raw_pointers
+                .iter()
+                .find(|(name, _, _)| name == ident)
+                .filter(|_| slice_lens.iter().any(|(_, _, target)| target == ident))
+            {
+                let Type::Ptr(ptr) = ptr_ty else {
+                    continue;
+                };
+                let as_ptr = if ptr.mutability.is_some() {
+                    quote! { #ident.as_mut_ptr() }
+                } else {
+                    quote! { #ident.as_ptr() }
+                };
+                slice_args.push(quote! { #ident: #slice_ty });
+                slice_call_args.push(if is_c_void(&ptr.elem) {
+                    quote! { #as_ptr as #ptr_ty }
+                } else {
+                    as_ptr
+                });
+                remaining_pointers.retain(|name| *name != ident);
+                continue;
+            } else {
+                slice_args.push(wrapper_arg.clone());
+            }
+            slice_call_args.push(quote! { #ident });
+        }
+        let doc = format!(
+            " [`{}`] のスライス版 ({} にスライスの長さを渡す)",
+            wrapper_name,
+            slice_lens
+                .iter()
+                .map(|(len, _, target)| format!("`{}` に `{}`", target.unraw(), len.unraw()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        // スライス以外の生ポインタの引数が残る場合は unsafe fn のままにする
+        let (slice_unsafety, safety_doc) = if remaining_pointers.is_empty() {
+            (None, None)
+        } else {
+            (
+                unsafety.clone(),
+                Some(format!(
+                    " # Safety\n\n [`{}`] と同じ条件で呼び出す必要がある",
+                    wrapper_name
+                )),
+            )
+        };
+        let safety_doc = safety_doc.map(|doc| quote! { #[doc = ""] #[doc = #doc] });
+        let user_docs = attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+        Some(quote! {
+            #cfg
+            #(#user_docs)*
+            #[doc = ""]
+            #[doc = #doc]
+            #safety_doc
+            pub #slice_unsafety fn #slice_name #generics( #(#slice_args),* ) -> #wrapper_return #where_clause {
+                #(#slice_len_stmts)*
+                // スライスの範囲を指すポインタと、その長さを渡す
+                unsafe { #wrapper_name(#(#slice_call_args),*) }
+            }
+        })
+    };
+
+    // #[format] の関数を Rust の書式 (format!) で呼び出すマクロ
+    //  draw_format_string!(x, y, color, "score: {}", score) -> DrawFormatString(x, y, color, format!("score: {}", score))
+    let format_macro = format_macro.map(|macro_name| {
+        let path = config.macro_module.as_ref().map(|module| {
+            let segments = module
+                .value()
+                .split("::")
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| format_ident!("{}", segment))
+                .collect::<Vec<_>>();
+            quote! { $crate:: #(#segments::)* }
+        });
+        let params = call_args[..call_args.len() - 1]
+            .iter()
+            .map(|ident| format_ident!("{}", ident.unraw()))
+            .collect::<Vec<_>>();
+        let usage = params
+            .iter()
+            .map(|param| param.to_string())
+            .chain(["\"...\"".to_string(), "args...".to_string()])
+            .collect::<Vec<_>>()
+            .join(", ");
+        let doc = format!(
+            " [`{}`] を Rust の書式 (`format!`) で呼び出す\n\n `{}!({})`",
+            wrapper_name, macro_name, usage
+        );
+        quote! {
+            #cfg
+            #[doc = #doc]
+            #[macro_export]
+            macro_rules! #macro_name {
+                (#($#params:expr,)* $($arg:tt)+) => {
+                    #path #wrapper_name(#($#params,)* ::std::format!($($arg)+))
+                };
+            }
+        }
+    });
+
+    Ok(GeneratedFunction {
+        tokens: quote! {
+            #extern_block
+            #wrapper_fn
+            #slice_fn
+            #format_macro
+        },
+        fn_ptr_type: quote! { unsafe extern #abi fn(#(#extern_args),*) -> #extern_return_type },
+        extern_name,
+        api_method,
+        context_method,
+    })
+}
+
+// 関数シグネチャ全体の検証 (未対応の属性、引数の形式)
+fn validate_signature(func: &FunctionWithAttrs) -> syn::Result<()> {
+    let FunctionWithAttrs { attrs, sig } = func;
+    validate_attributes(attrs, FUNCTION_ATTRIBUTES, PARAM_ATTRIBUTES, "関数")?;
+    match (&sig.variadic, extract_format_macro(attrs)?) {
+        (Some(variadic), None) => {
+            return Err(syn::Error::new_spanned(
+                variadic,
+                "可変長引数は #[format(macro = \"マクロ名\")] を指定した関数のみ使用できます",
+            ));
+        }
+        (None, Some(macro_name)) => {
+            return Err(syn::Error::new_spanned(
+                macro_name,
+                "#[format] の関数は最後の引数を ... (可変長引数) にしてください",
+            ));
+        }
+        _ => {}
+    }
+    for arg in sig.inputs.iter() {
+        match arg {
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "self は引数に指定できません",
+                ));
+            }
+            FnArg::Typed(PatType { pat, ty, attrs, .. }) => {
+                validate_attributes(attrs, PARAM_ATTRIBUTES, FUNCTION_ATTRIBUTES, "引数")?;
+                if !matches!(&**pat, Pat::Ident(pi) if pi.subpat.is_none()) {
+                    return Err(syn::Error::new_spanned(
+                        pat,
+                        "パターン付き引数は未対応です (引数名を指定してください)",
+                    ));
+                }
+                if is_callback_attribute(attrs) && extract_callback(ty).is_none() {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "#[callback] の引数は Option<Box<dyn FnMut(引数) -> 戻り値 + Send>> の形式で指定してください",
+                    ));
+                }
+                if let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("default"))
+                    && is_option(ty).is_none()
+                {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "#[default] は Option<T> の引数にのみ指定できます",
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+    use syn::LitStr;
+
+    // 生成されたコードを期待するコードと比較する
+    fn assert_generated(config: &GenConfig, signature: &str, expected: TokenStream) {
+        let func: FunctionWithAttrs = syn::parse_str(signature).unwrap();
+        let generated = generate_function(config, &func, CallMode::Extern, &quote! {}).unwrap();
+        assert_eq!(generated.tokens.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_mut_string() {
+        let config = GenConfig::dxlib_preset(LitStr::new("DxLib_x64", Span::call_site()));
+        assert_generated(
+            &config,
+            "fn Edit(#[capacity = 16] buffer: &mut String, #[len_of(buffer)] size: i32) -> i32",
+            quote! {
+                #[link(name = "DxLib_x64")]
+                unsafe extern "system" {
+                    fn dx_Edit(buffer: *mut c_char, size: i32) -> i32;
+                }
+                #[doc = " # エラー"]
+                #[doc = ""]
+                #[doc = " 戻り値が `-1` の場合は `DxLibError::Call` を返す"]
+                #[doc = ""]
+                #[doc = " C の関数: `Edit` (シンボル名 `dx_Edit`)"]
+                pub fn Edit(buffer: &mut String) -> anyhow::Result<i32, DxLibError> {
+                    let mut __buffer_buffer =
+                        match StringBuffer::new(buffer.as_str(), buffer.capacity().max((16) as usize)) {
+                            Ok(holder) => holder,
+                            Err(e) => return Err(DxLibError::InteriorNul("Edit", "buffer", e.position)),
+                        };
+                    let size = __buffer_buffer.capacity() as i32;
+                    {
+                        let result: i32 = unsafe { dx_Edit(__buffer_buffer.as_mut_ptr(), size) };
+                        if result as i32 == -1i32 {
+                            return Err(DxLibError::Call {
+                                function: "Edit",
+                                code: result as i64,
+                                args: vec![("buffer", format!("{:?}", buffer))],
+                            });
+                        } else {
+                            *buffer = match __buffer_buffer.decode() {
+                                Ok(value) => value,
+                                Err(e) => return Err(DxLibError::InvalidString("Edit", "buffer", e)),
+                            };
+                            return Ok(result);
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_mut_string_wide_lossy() {
+        assert_generated(
+            &GenConfig::cffi_default(),
+            "#[wide] #[lossy] #[not_result] fn EditW(buffer: &mut String) -> i32",
+            quote! {
+                unsafe extern "C" {
+                    fn EditW(buffer: *mut u16) -> i32;
+                }
+                #[doc = " # エラー"]
+                #[doc = ""]
+                #[doc = " 戻り値が `-1` の場合は `-1` を返す"]
+                #[doc = ""]
+                #[doc = " C の関数: `EditW`"]
+                pub fn EditW(buffer: &mut String) -> i32 {
+                    let mut __buffer_buffer = WideStringBuffer::new_lossy(buffer.as_str(), buffer.capacity());
+                    {
+                        let result: i32 = unsafe { EditW(__buffer_buffer.as_mut_ptr()) };
+                        if result as i32 == -1i32 {
+                            return -1;
+                        } else {
+                            *buffer = match __buffer_buffer.decode() {
+                                Ok(value) => value,
+                                Err(_) => return -1,
+                            };
+                            return result;
+                        }
+                    }
+                }
+            },
+        );
+    }
+}
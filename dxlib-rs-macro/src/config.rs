@@ -0,0 +1,336 @@
+// src/config.rs
+use crate::FunctionWithAttrs;
+use crate::handle::HandleSpec;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    Attribute, Expr, ExprLit, Ident, Lit, LitStr, Meta, MetaNameValue, Path, Type, parse_str,
+};
+
+// cffi_gen! / dxlib_gen! の生成設定
+// マクロ先頭の #![...] (内部属性) で上書きされる
+pub struct GenConfig {
+    // リンクするライブラリ名 (None の場合は #[link] を付けない)
+    pub library_name: Option<LitStr>,
+    // extern 側の関数名の先頭に付ける文字列
+    pub func_name_top_prefix: String,
+    // extern 側の関数名の末尾に付ける文字列
+    pub func_name_down_prefix: String,
+    // Result のエラー型 (From<anyhow::Error> を実装している必要がある)
+    pub error_type: Type,
+    // #[role = "init"] の関数がエラーになった場合に返す値
+    pub init_error: Option<Expr>,
+    // #[role = "finalize"] の関数がエラーになった場合に返す値
+    pub finalize_error: Option<Expr>,
+    // extern ブロックの呼び出し規約 ("system", "C", "stdcall")
+    pub abi: LitStr,
+    // リンク方法
+    pub link_kind: LinkKind,
+    // 動的ロードを行う場合の関数テーブルの型名 (None の場合は extern をリンクする)
+    pub dynamic_loader: Option<Ident>,
+    // 指定された場合、このフィーチャーが有効な時のみ動的ロードを行う
+    pub dynamic_loader_feature: Option<LitStr>,
+    // 指定された場合、このフィーチャーが有効な時は呼び出しごとに tracing のイベントを出力する
+    pub trace_feature: Option<LitStr>,
+    // 指定された場合、このフィーチャーが有効な時は C 関数を呼び出さずに呼び出しを記録する (record モジュール)
+    pub record_feature: Option<LitStr>,
+    // 指定された場合、全ての関数をメソッドに持つこの名前のトレイトと、
+    // 呼び出し先を差し替えるための実装(#{名前}Real)、切り替え(#{名前}Backend)を生成する
+    pub api_trait: Option<Ident>,
+    // 指定された場合、#[role = "init"] の関数で作成するこの名前の型(!Send)と、
+    // #[context] の関数をメソッドに持つ impl を生成する
+    pub context: Option<Ident>,
+    // #[context] の関数で、初期化済みか、初期化したスレッドからの呼び出しかを検査する cfg の条件
+    pub context_check: TokenStream,
+    // #[format] で生成するマクロから関数を呼び出す際の、$crate からのモジュールのパス ("dxlib" -> $crate::dxlib::関数名)
+    // 未指定の場合は関数名のみで呼び出す (マクロの呼び出し元で関数が use されている必要がある)
+    pub macro_module: Option<LitStr>,
+    // ライブラリの読み込みに失敗した場合に返す値 (ライブラリのパス, anyhow::Error を受け取る)
+    pub load_error: Option<Expr>,
+    // 見つからない関数がある場合に返す値 (関数名の Vec<&'static str> を受け取る)
+    pub missing_symbols_error: Option<Expr>,
+    // 呼び出した関数がライブラリに存在しない場合に返す値 (関数名を受け取る)
+    pub symbol_error: Option<Expr>,
+    // #[out_string] の文字列のデコードに失敗した場合に返す値 (関数名, 引数名, anyhow::Error を受け取る)
+    pub string_error: Option<Expr>,
+    // 文字列の引数の途中にNUL文字が含まれている場合に返す値 (関数名, 引数名, NUL文字の位置を受け取る)
+    pub nul_error: Option<Expr>,
+    // 関数がエラーを返した場合のエラーの構造体(もしくは列挙子)のパス
+    // function: &'static str, code: i64, args: Vec<(&'static str, String)> のフィールドで生成される
+    pub call_error: Option<Path>,
+    // #[dx_enum] の値に対応する列挙子がない場合に返す値 (関数名, 型名, 値を受け取る)
+    pub enum_error: Option<Expr>,
+    // #[context] の関数の呼び出し時の検査に失敗した場合に返す値 (関数名, utils::ContextError を受け取る)
+    pub context_error: Option<Expr>,
+    // #[handle(...)] で宣言されたハンドル型 (引数の &GraphHandle 等を i32 として渡す)
+    pub handles: Vec<HandleSpec>,
+    // #[role] の指定がない場合に関数名から決める役割
+    pub preset_roles: Vec<(&'static str, FnRole)>,
+}
+
+impl GenConfig {
+    // cffi_gen! の既定値 (接頭辞なし、anyhow::Error)
+    pub fn cffi_default() -> Self {
+        Self {
+            library_name: None,
+            func_name_top_prefix: String::new(),
+            func_name_down_prefix: String::new(),
+            error_type: parse_str("anyhow::Error").unwrap(),
+            init_error: None,
+            finalize_error: None,
+            abi: LitStr::new("C", Span::call_site()),
+            link_kind: LinkKind::Import,
+            dynamic_loader: None,
+            dynamic_loader_feature: None,
+            trace_feature: None,
+            record_feature: None,
+            api_trait: None,
+            context: None,
+            context_check: quote! { debug_assertions },
+            macro_module: None,
+            load_error: None,
+            missing_symbols_error: None,
+            symbol_error: None,
+            string_error: None,
+            nul_error: None,
+            call_error: None,
+            enum_error: None,
+            context_error: None,
+            handles: Vec::new(),
+            preset_roles: Vec::new(),
+        }
+    }
+
+    // dxlib_gen! 用のプリセット
+    pub fn dxlib_preset(lib_name: LitStr) -> Self {
+        Self {
+            library_name: Some(lib_name),
+            func_name_top_prefix: "dx_".to_string(),
+            func_name_down_prefix: String::new(),
+            error_type: parse_str("DxLibError").unwrap(),
+            init_error: Some(parse_str("DxLibError::InitializeError").unwrap()),
+            finalize_error: Some(parse_str("DxLibError::FinalizeError").unwrap()),
+            abi: LitStr::new("system", Span::call_site()),
+            link_kind: LinkKind::Import,
+            dynamic_loader: None,
+            dynamic_loader_feature: None,
+            trace_feature: None,
+            record_feature: None,
+            api_trait: None,
+            context: None,
+            context_check: quote! { debug_assertions },
+            macro_module: None,
+            load_error: Some(parse_str("DxLibError::LoadError").unwrap()),
+            missing_symbols_error: Some(parse_str("DxLibError::MissingSymbols").unwrap()),
+            symbol_error: Some(parse_str("DxLibError::SymbolNotAvailable").unwrap()),
+            string_error: Some(parse_str("DxLibError::InvalidString").unwrap()),
+            nul_error: Some(parse_str("DxLibError::InteriorNul").unwrap()),
+            call_error: Some(parse_str("DxLibError::Call").unwrap()),
+            enum_error: Some(parse_str("DxLibError::InvalidEnumValue").unwrap()),
+            context_error: Some(parse_str("DxLibError::Context").unwrap()),
+            handles: Vec::new(),
+            preset_roles: vec![
+                ("DxLib_Init", FnRole::Init),
+                ("DxLib_End", FnRole::Finalize),
+            ],
+        }
+    }
+
+    // #![name = "value"] 形式の内部属性を設定に反映する
+    pub fn apply_attributes(&mut self, attrs: &[Attribute]) -> syn::Result<()> {
+        for attr in attrs {
+            let value = attribute_str_value(attr)?;
+            let path = attr.path();
+            if path.is_ident("library_name") {
+                self.library_name = Some(value);
+            } else if path.is_ident("func_name_top_prefix") {
+                self.func_name_top_prefix = value.value();
+            } else if path.is_ident("func_name_down_prefix") {
+                self.func_name_down_prefix = value.value();
+            } else if path.is_ident("as_result_error_type") {
+                self.error_type = value.parse()?;
+            } else if path.is_ident("init_error") {
+                self.init_error = Some(value.parse()?);
+            } else if path.is_ident("finalize_error") {
+                self.finalize_error = Some(value.parse()?);
+            } else if path.is_ident("abi") {
+                self.abi = validate_abi(value)?;
+            } else if path.is_ident("link_kind") {
+                self.link_kind = LinkKind::parse(&value)?;
+            } else if path.is_ident("dynamic_loader") {
+                self.dynamic_loader = Some(value.parse()?);
+            } else if path.is_ident("dynamic_loader_feature") {
+                self.dynamic_loader_feature = Some(value);
+            } else if path.is_ident("trace_feature") {
+                self.trace_feature = Some(value);
+            } else if path.is_ident("record_feature") {
+                self.record_feature = Some(value);
+            } else if path.is_ident("api_trait") {
+                self.api_trait = Some(value.parse()?);
+            } else if path.is_ident("context") {
+                self.context = Some(value.parse()?);
+            } else if path.is_ident("context_check") {
+                self.context_check = value.parse()?;
+            } else if path.is_ident("macro_module") {
+                self.macro_module = Some(value);
+            } else if path.is_ident("load_error") {
+                self.load_error = Some(value.parse()?);
+            } else if path.is_ident("missing_symbols_error") {
+                self.missing_symbols_error = Some(value.parse()?);
+            } else if path.is_ident("symbol_error") {
+                self.symbol_error = Some(value.parse()?);
+            } else if path.is_ident("string_error") {
+                self.string_error = Some(value.parse()?);
+            } else if path.is_ident("nul_error") {
+                self.nul_error = Some(value.parse()?);
+            } else if path.is_ident("call_error") {
+                self.call_error = Some(value.parse()?);
+            } else if path.is_ident("enum_error") {
+                self.enum_error = Some(value.parse()?);
+            } else if path.is_ident("context_error") {
+                self.context_error = Some(value.parse()?);
+            } else {
+                return Err(syn::Error::new_spanned(path, "未対応の属性です"));
+            }
+        }
+        Ok(())
+    }
+
+    // 関数ごとの #[abi = "..."]、もしくは全体の設定から呼び出し規約を決める
+    pub fn abi_of(&self, attrs: &[Attribute]) -> syn::Result<LitStr> {
+        for attr in attrs {
+            if attr.path().is_ident("abi") {
+                return validate_abi(attribute_str_value(attr)?);
+            }
+        }
+        Ok(self.abi.clone())
+    }
+
+    // 関数ごとの #[call_error = "..."]、もしくは全体の設定からエラーのパスを決める
+    pub fn call_error_of(&self, attrs: &[Attribute]) -> syn::Result<Option<Path>> {
+        for attr in attrs {
+            if attr.path().is_ident("call_error") {
+                return attribute_str_value(attr)?.parse().map(Some);
+            }
+        }
+        Ok(self.call_error.clone())
+    }
+
+    // 関数ごとの #[link_kind = "..."]、もしくは全体の設定からリンク方法を決める
+    pub fn link_kind_of(&self, attrs: &[Attribute]) -> syn::Result<LinkKind> {
+        for attr in attrs {
+            if attr.path().is_ident("link_kind") {
+                return LinkKind::parse(&attribute_str_value(attr)?);
+            }
+        }
+        Ok(self.link_kind)
+    }
+
+    // extern ブロックに付ける #[link(...)] を生成する
+    pub fn link_attribute(&self, link_kind: LinkKind) -> Option<TokenStream> {
+        let lib_name = self.library_name.as_ref()?;
+        match link_kind {
+            LinkKind::Import => Some(quote! { #[link(name = #lib_name)] }),
+            LinkKind::Static => Some(quote! { #[link(name = #lib_name, kind = "static")] }),
+            LinkKind::RawDylib => Some(quote! { #[link(name = #lib_name, kind = "raw-dylib")] }),
+            LinkKind::None => None,
+        }
+    }
+
+    // #[role = "..."] の指定、もしくはプリセットから関数の役割を決める
+    pub fn role_of(&self, func: &FunctionWithAttrs) -> syn::Result<FnRole> {
+        if let Some(role) = FnRole::from_attributes(&func.attrs)? {
+            return Ok(role);
+        }
+        Ok(self
+            .preset_roles
+            .iter()
+            .find(|(name, _)| func.sig.ident == name)
+            .map(|(_, role)| *role)
+            .unwrap_or(FnRole::Normal))
+    }
+}
+
+// extern ブロックのリンク方法
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    // インポートライブラリ(.lib)、共有ライブラリ(.so)を通常通りリンクする
+    Import,
+    // 静的ライブラリとしてリンクする
+    Static,
+    // インポートライブラリなしで DLL を直接参照する (Windows のみ)
+    RawDylib,
+    // #[link] を付けない (build.rs 等で別途リンクする)
+    None,
+}
+
+impl LinkKind {
+    fn parse(value: &LitStr) -> syn::Result<Self> {
+        match value.value().as_str() {
+            "import" => Ok(LinkKind::Import),
+            "static" => Ok(LinkKind::Static),
+            "raw-dylib" => Ok(LinkKind::RawDylib),
+            "none" => Ok(LinkKind::None),
+            _ => Err(syn::Error::new_spanned(
+                value,
+                "link_kind には \"import\", \"static\", \"raw-dylib\", \"none\" のいずれかを指定してください",
+            )),
+        }
+    }
+}
+
+fn validate_abi(value: LitStr) -> syn::Result<LitStr> {
+    match value.value().as_str() {
+        "system" | "C" | "stdcall" => Ok(value),
+        _ => Err(syn::Error::new_spanned(
+            value,
+            "abi には \"system\", \"C\", \"stdcall\" のいずれかを指定してください",
+        )),
+    }
+}
+
+// 関数の役割 (エラー時に返すバリアントの切り替えに使用)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FnRole {
+    Normal,
+    Init,
+    Finalize,
+}
+
+impl FnRole {
+    pub fn from_attributes(attrs: &[Attribute]) -> syn::Result<Option<Self>> {
+        for attr in attrs {
+            if attr.path().is_ident("role") {
+                let value = attribute_str_value(attr)?;
+                return match value.value().as_str() {
+                    "init" => Ok(Some(FnRole::Init)),
+                    "finalize" => Ok(Some(FnRole::Finalize)),
+                    "normal" => Ok(Some(FnRole::Normal)),
+                    _ => Err(syn::Error::new_spanned(
+                        value,
+                        "role には \"init\", \"finalize\", \"normal\" のいずれかを指定してください",
+                    )),
+                };
+            }
+        }
+        Ok(None)
+    }
+}
+
+// #[name = "value"] の "value" を取り出す
+pub fn attribute_str_value(attr: &Attribute) -> syn::Result<LitStr> {
+    if let Meta::NameValue(MetaNameValue {
+        value: Expr::Lit(ExprLit {
+            lit: Lit::Str(lit_str),
+            ..
+        }),
+        ..
+    }) = &attr.meta
+    {
+        return Ok(lit_str.clone());
+    }
+    Err(syn::Error::new_spanned(
+        attr,
+        "#[name = \"value\"] の形式で指定してください",
+    ))
+}
@@ -0,0 +1,290 @@
+// src/context.rs
+use crate::codegen::GeneratedFunction;
+use crate::config::{FnRole, GenConfig};
+use crate::utils::extract_context_attribute;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, FnArg, Generics, Pat, PatType};
+
+// #![context = "DxContext"] の型でのメソッドの受け取り方
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Receiver {
+    // #[role = "init"] の関数 (成功した場合に型を作成する関連関数)
+    Init,
+    // #[role = "finalize"] の関数 (self を消費する)
+    Finalize,
+    // #[context] の関数 (&self)
+    Shared,
+    // #[context(mut)] の関数 (&mut self)
+    Mut,
+}
+
+impl Receiver {
+    // 呼び出し時に初期化済みかを検査する関数か
+    pub fn is_checked(self) -> bool {
+        matches!(self, Receiver::Shared | Receiver::Mut)
+    }
+}
+
+// 関数の役割と #[context] 属性から、#![context] の型のメソッドにするかを決める
+pub fn receiver_of(
+    config: &GenConfig,
+    role: FnRole,
+    attrs: &[Attribute],
+    is_not_result: bool,
+) -> syn::Result<Option<Receiver>> {
+    let context_attr = attrs.iter().find(|attr| attr.path().is_ident("context"));
+    let mutable = extract_context_attribute(attrs)?;
+    let Some(_) = &config.context else {
+        return match context_attr {
+            Some(attr) => Err(syn::Error::new_spanned(
+                attr,
+                "#[context] を指定する場合は #![context = \"型名\"] を指定してください",
+            )),
+            None => Ok(None),
+        };
+    };
+    match (role, mutable) {
+        (FnRole::Init | FnRole::Finalize, Some(_)) => Err(syn::Error::new_spanned(
+            context_attr,
+            "#[role = \"init\"],#[role = \"finalize\"] の関数には #[context] を指定できません",
+        )),
+        (FnRole::Init, None) if is_not_result => Err(syn::Error::new_spanned(
+            attrs.iter().find(|attr| attr.path().is_ident("not_result")),
+            "#![context] を指定した場合は初期化の関数に #[not_result] を指定できません",
+        )),
+        (FnRole::Init, None) => Ok(Some(Receiver::Init)),
+        (FnRole::Finalize, None) => Ok(Some(Receiver::Finalize)),
+        (FnRole::Normal, Some(true)) => Ok(Some(Receiver::Mut)),
+        (FnRole::Normal, Some(false)) => Ok(Some(Receiver::Shared)),
+        (FnRole::Normal, None) => Ok(None),
+    }
+}
+
+// 初期化の状態を保持する static の名前 (DxContext -> __DXCONTEXT_STATE)
+pub fn state_ident(context: &Ident) -> Ident {
+    format_ident!("__{}_STATE", context.to_string().to_uppercase())
+}
+
+// 検査に失敗した場合のエラーの値 (e: ContextError)
+// #![context_error] を指定した場合は #error(関数名, e)、未指定の場合は anyhow::Error から変換する
+pub fn context_error_value(config: &GenConfig, function_name: &str) -> TokenStream {
+    let error_type = &config.error_type;
+    config.context_error.as_ref().map_or_else(
+        || quote! { <#error_type>::from(anyhow::anyhow!("{}() {}", #function_name, e)) },
+        |error| quote! { #error(#function_name, e) },
+    )
+}
+
+// #![context] の型のメソッド
+// ラップ関数と同じ引数で、ラップ関数を呼び出す
+//  fn DrawString(x: i32, ..) -> anyhow::Result<i32, DxLibError>
+//  -> impl DxContext { pub fn DrawString(&self, x: i32, ..) -> anyhow::Result<i32, DxLibError> }
+pub struct ContextMethod {
+    name: Ident,
+    receiver: Receiver,
+    args: Vec<TokenStream>,
+    arg_idents: Vec<Ident>,
+    return_type: TokenStream,
+    generics: Generics,
+    is_unsafe: bool,
+    docs: Vec<Attribute>,
+}
+
+impl ContextMethod {
+    pub fn new(
+        name: &Ident,
+        receiver: Receiver,
+        wrapper_args: &[TokenStream],
+        return_type: &TokenStream,
+        generics: &Generics,
+        is_unsafe: bool,
+        attrs: &[Attribute],
+    ) -> syn::Result<Self> {
+        let mut arg_idents = Vec::new();
+        for wrapper_arg in wrapper_args {
+            let FnArg::Typed(PatType { pat, .. }) = syn::parse2(wrapper_arg.clone())? else {
+                continue;
+            };
+            if let Pat::Ident(pat_ident) = &*pat {
+                arg_idents.push(pat_ident.ident.clone());
+            }
+        }
+        Ok(Self {
+            name: name.clone(),
+            receiver,
+            args: wrapper_args.to_vec(),
+            arg_idents,
+            return_type: return_type.clone(),
+            generics: generics.clone(),
+            is_unsafe,
+            docs: attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    fn method(&self, context: &Ident, config: &GenConfig) -> TokenStream {
+        let Self {
+            name,
+            receiver,
+            args,
+            arg_idents,
+            return_type,
+            generics,
+            is_unsafe,
+            docs,
+        } = self;
+        let error_type = &config.error_type;
+        let where_clause = &generics.where_clause;
+        let (receiver, return_type, doc) = match receiver {
+            Receiver::Init => (
+                None,
+                quote! { anyhow::Result<Self, #error_type> },
+                format!(
+                    " [`{0}`] を呼び出し、成功した場合は `{1}` を返す\n\n \
+                     既に初期化されている場合(`{1}` が存在する場合を含む)は \
+                     `ContextError::AlreadyInitialized` のエラーを返す",
+                    name, context
+                ),
+            ),
+            Receiver::Finalize => (
+                Some(quote! { self, }),
+                return_type.clone(),
+                format!(
+                    " [`{}`] を呼び出す (呼び出し後は `{}` を使用できない)\n\n \
+                     失敗した場合も初期化前の状態に戻る",
+                    name, context
+                ),
+            ),
+            Receiver::Shared => (
+                Some(quote! { &self, }),
+                return_type.clone(),
+                format!(" [`{}`] を呼び出す", name),
+            ),
+            Receiver::Mut => (
+                Some(quote! { &mut self, }),
+                return_type.clone(),
+                format!(" [`{}`] を呼び出す", name),
+            ),
+        };
+        let call = quote! { #name(#(#arg_idents),*) };
+        let (unsafety, call, safety_doc) = if *is_unsafe {
+            let safety_doc = format!(" # Safety\n\n [`{}`] と同じ条件で呼び出す必要がある", name);
+            (
+                Some(quote! { unsafe }),
+                quote! { unsafe { #call } },
+                Some(quote! { #[doc = ""] #[doc = #safety_doc] }),
+            )
+        } else {
+            (None, call, None)
+        };
+        let state = state_ident(context);
+        // 型は同時に1つのみ存在するように、初期化の関数の呼び出し前に claim し、型の Drop 時に release する
+        let body = match self.receiver {
+            Receiver::Init => {
+                let error_value = context_error_value(config, &name.to_string());
+                quote! {
+                    if !#state.claim() {
+                        let e = ContextError::AlreadyInitialized;
+                        return Err(#error_value);
+                    }
+                    match #call {
+                        Ok(_) => Ok(Self::new()),
+                        Err(e) => {
+                            #state.release();
+                            Err(e)
+                        }
+                    }
+                }
+            }
+            // 終了の関数が失敗しても型は破棄されるため、初期化前の状態に戻す (self の Drop で release される)
+            Receiver::Finalize => quote! {
+                let result = #call;
+                #state.finalize();
+                result
+            },
+            Receiver::Shared | Receiver::Mut => call,
+        };
+        quote! {
+            #(#docs)*
+            #[doc = ""]
+            #[doc = #doc]
+            #safety_doc
+            pub #unsafety fn #name #generics(#receiver #(#args),*) -> #return_type #where_clause {
+                #body
+            }
+        }
+    }
+}
+
+// #![context = "DxContext"] の型と、初期化の状態を生成する
+//  DxContext::DxLib_Init() -> 初期化に成功した場合は DxContext を返す
+//  context.DrawString(..) -> #[context] の関数を呼び出す
+//  context.DxLib_End() -> 終了の関数を呼び出し、DxContext を破棄する
+// 型は Send,Sync を実装しないため、初期化したスレッド以外へ渡すことはできない
+pub fn generate_context(
+    context: &Ident,
+    config: &GenConfig,
+    generated: &[GeneratedFunction],
+) -> TokenStream {
+    let state = state_ident(context);
+    let methods: Vec<_> = generated
+        .iter()
+        .filter_map(|function| function.context_method.as_ref())
+        .collect();
+    let Some(init) = methods
+        .iter()
+        .find(|method| method.receiver == Receiver::Init)
+    else {
+        return syn::Error::new_spanned(
+            context,
+            "#![context] を指定する場合は #[role = \"init\"] の関数が必要です",
+        )
+        .to_compile_error();
+    };
+    let methods = methods.iter().map(|method| method.method(context, config));
+    let doc = format!(
+        " 初期化済みのライブラリを表す型 (初期化したスレッドでのみ使用できる)\n\n \
+         [`{0}::{1}`] で作成し、描画、入力等の関数をメソッドとして呼び出す",
+        context, init.name
+    );
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug)]
+        pub struct #context {
+            // *const () により Send,Sync を実装しない
+            _not_send: std::marker::PhantomData<*const ()>,
+        }
+
+        // 初期化の状態 (初期化、終了の関数の呼び出し成功時と、型の作成、Drop 時に更新する)
+        static #state: ContextState = ContextState::new();
+
+        impl #context {
+            fn new() -> Self {
+                Self {
+                    _not_send: std::marker::PhantomData,
+                }
+            }
+
+            /// 初期化済みで、初期化したスレッドからの呼び出しかを確認する
+            /// (初期化されていない場合は ContextError::NotInitialized、
+            /// 別のスレッドから呼び出した場合は ContextError::OffMainThread)
+            pub fn check() -> Result<(), ContextError> {
+                #state.check()
+            }
+
+            #(#methods)*
+        }
+
+        // 終了の関数を呼び出さずに破棄した場合は初期化済みのまま残るが、
+        // 自由関数の終了の関数で終了した後に再度初期化できるように release する
+        impl Drop for #context {
+            fn drop(&mut self) {
+                #state.release();
+            }
+        }
+    }
+}
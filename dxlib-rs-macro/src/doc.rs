@@ -0,0 +1,177 @@
+// src/doc.rs
+use crate::FunctionWithAttrs;
+use crate::utils::*;
+use proc_macro2::{Ident, TokenStream};
+use quote::{ToTokens, quote};
+use syn::ext::IdentExt;
+use syn::{Expr, ExprLit, FnArg, Lit, Meta, MetaNameValue, PatType};
+
+// ラップ関数のドキュメント
+// 関数シグネチャの /// (#[doc]) をそのまま付け、#[doc_en = "..."] の英語の説明、
+// エラー条件、Option の引数の既定値、C の関数名の注記を続けて生成する
+pub fn generate_doc(
+    func: &FunctionWithAttrs,
+    extern_name: &Ident,
+    error_note: &str,
+) -> TokenStream {
+    let FunctionWithAttrs { attrs, sig } = func;
+    let mut lines: Vec<String> = Vec::new();
+
+    let doc_en: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| attr_str_value(attr, "doc_en"))
+        .collect();
+    let has_doc = attrs.iter().any(|attr| attr.path().is_ident("doc"));
+    if !doc_en.is_empty() {
+        if has_doc {
+            lines.push(String::new());
+        }
+        lines.extend(doc_en.iter().map(|line| format!(" {}", line)));
+    }
+
+    if has_doc || !doc_en.is_empty() {
+        lines.push(String::new());
+    }
+    lines.push(" # エラー".to_string());
+    lines.push(String::new());
+    lines.push(format!(" {}", error_note));
+
+    // None の場合に渡される値
+    let defaults: Vec<String> = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(PatType { pat, ty, attrs, .. }) if is_option(ty).is_some() => {
+                let value = attrs
+                    .iter()
+                    .find_map(|attr| attr_str_value(attr, "default"))
+                    .map(|value| match value.as_str() {
+                        "null" => "std::ptr::null()".to_string(),
+                        "null_mut" => "std::ptr::null_mut()".to_string(),
+                        "default" => "Default::default()".to_string(),
+                        other => other.to_string(),
+                    })
+                    .unwrap_or_else(|| "Default::default()".to_string());
+                Some(format!(
+                    " - `{}`: `None` の場合は `{}`",
+                    doc_tokens(pat),
+                    value
+                ))
+            }
+            _ => None,
+        })
+        .collect();
+    if !defaults.is_empty() {
+        lines.push(String::new());
+        lines.push(" # 既定値".to_string());
+        lines.push(String::new());
+        lines.extend(defaults);
+    }
+
+    // 生ポインタの引数がある場合は unsafe fn として生成される
+    let pointers = raw_pointer_params(sig);
+    if !pointers.is_empty() {
+        let names = pointers
+            .iter()
+            .map(|ident| format!("`{}`", ident.unraw()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(String::new());
+        lines.push(" # Safety".to_string());
+        lines.push(String::new());
+        lines.push(format!(
+            " ポインタの引数 ({}) は、C の関数が読み書きする範囲で有効である必要がある",
+            names
+        ));
+    }
+
+    lines.push(String::new());
+    if *extern_name == sig.ident {
+        lines.push(format!(" C の関数: `{}`", sig.ident));
+    } else {
+        lines.push(format!(
+            " C の関数: `{}` (シンボル名 `{}`)",
+            sig.ident, extern_name
+        ));
+    }
+
+    let user_docs = attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+    quote! {
+        #(#user_docs)*
+        #(#[doc = #lines])*
+    }
+}
+
+// エラー条件の説明 (#[error_condition] の指定がない場合は戻り値が -1)
+pub fn error_condition_doc(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .find_map(|attr| attr_str_value(attr, "error_condition"))
+        .map(|condition| format!("`{}` の場合", condition))
+        .unwrap_or_else(|| "戻り値が `-1` の場合".to_string())
+}
+
+// ドキュメントに埋め込むための、型やパスの文字列 (DxLibError :: Call -> DxLibError::Call)
+pub fn doc_tokens(tokens: &impl ToTokens) -> String {
+    tokens
+        .to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+}
+
+fn attr_str_value(attr: &syn::Attribute, name: &str) -> Option<String> {
+    if attr.path().is_ident(name)
+        && let Meta::NameValue(MetaNameValue { value, .. }) = &attr.meta
+        && let Expr::Lit(ExprLit {
+            lit: Lit::Str(lit_str),
+            ..
+        }) = value
+    {
+        return Some(lit_str.value());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::format_ident;
+
+    #[test]
+    fn test_generate_doc() {
+        let func: FunctionWithAttrs = syn::parse_str(
+            r#"
+            /// 描画先グラフィック領域の指定
+            #[doc_en = "Sets the drawing target screen."]
+            fn SetDrawScreen(#[default = "DX_SCREEN_BACK"] draw_screen: Option<i32>, flag: Option<bool>) -> i32
+            "#,
+        )
+        .unwrap();
+        let doc = generate_doc(
+            &func,
+            &format_ident!("dx_SetDrawScreen"),
+            "戻り値が `-1` の場合は `DxLibError::Call` を返す",
+        );
+        let expected = quote! {
+            #[doc = " 描画先グラフィック領域の指定"]
+            #[doc = ""]
+            #[doc = " Sets the drawing target screen."]
+            #[doc = ""]
+            #[doc = " # エラー"]
+            #[doc = ""]
+            #[doc = " 戻り値が `-1` の場合は `DxLibError::Call` を返す"]
+            #[doc = ""]
+            #[doc = " # 既定値"]
+            #[doc = ""]
+            #[doc = " - `draw_screen`: `None` の場合は `DX_SCREEN_BACK`"]
+            #[doc = " - `flag`: `None` の場合は `Default::default()`"]
+            #[doc = ""]
+            #[doc = " C の関数: `SetDrawScreen` (シンボル名 `dx_SetDrawScreen`)"]
+        };
+        assert_eq!(doc.to_string(), expected.to_string());
+    }
+}
@@ -0,0 +1,217 @@
+// src/generics.rs
+use proc_macro2::{Ident, Span};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    GenericParam, Signature, Token, Type, TypeImplTrait, TypeParamBound, TypeParen, TypePath,
+    WherePredicate,
+};
+
+// 関数シグネチャの型パラメータを、トレイト境界(where 句を含む)の impl Trait に置き換える
+//  fn DrawString<S: AsRef<str> + Debug>(x: i32, y: i32, string: S, color: i32) -> i32
+//  -> fn DrawString(x: i32, y: i32, string: impl AsRef<str> + Debug, color: i32) -> i32
+// 以降の引数変換は impl Trait と同じ規則で行われる (ライフタイム、const パラメータはそのまま残す)
+pub fn resolve_generics(sig: &Signature) -> syn::Result<Signature> {
+    let mut resolved = sig.clone();
+    let mut bounds: Vec<(Ident, Punctuated<TypeParamBound, Token![+]>)> = Vec::new();
+
+    let params = std::mem::take(&mut resolved.generics.params);
+    for param in params {
+        match param {
+            GenericParam::Type(type_param) => {
+                if let Some(default) = &type_param.default {
+                    return Err(syn::Error::new_spanned(
+                        default,
+                        "型パラメータの既定値は指定できません",
+                    ));
+                }
+                bounds.push((type_param.ident, type_param.bounds));
+            }
+            other => resolved.generics.params.push(other),
+        }
+    }
+    if bounds.is_empty() {
+        return Ok(resolved);
+    }
+
+    // where 句の境界を型パラメータの境界に追加する
+    if let Some(where_clause) = resolved.generics.where_clause.take() {
+        let mut predicates = Punctuated::<WherePredicate, Token![,]>::new();
+        for predicate in where_clause.predicates {
+            if let WherePredicate::Type(predicate_type) = &predicate
+                && let Type::Path(TypePath { qself: None, path }) = &predicate_type.bounded_ty
+                && let Some((_, param_bounds)) =
+                    bounds.iter_mut().find(|(ident, _)| path.is_ident(ident))
+            {
+                param_bounds.extend(predicate_type.bounds.iter().cloned());
+                continue;
+            }
+            predicates.push(predicate);
+        }
+        if !predicates.is_empty() {
+            resolved.generics.where_clause = Some(syn::WhereClause {
+                where_token: where_clause.where_token,
+                predicates,
+            });
+        }
+    }
+
+    for (ident, param_bounds) in &bounds {
+        if !param_bounds
+            .iter()
+            .any(|bound| matches!(bound, TypeParamBound::Trait(_)))
+        {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!("型パラメータ {} にトレイト境界を指定してください", ident),
+            ));
+        }
+    }
+
+    // 戻り値はCの関数の戻り値のため、型パラメータは使用できない
+    let mut finder = GenericFinder {
+        bounds: &bounds,
+        found: None,
+    };
+    finder.visit_return_type_mut(&mut resolved.output.clone());
+    if let Some(ident) = finder.found {
+        return Err(syn::Error::new_spanned(
+            &resolved.output,
+            format!("戻り値に型パラメータ {} は使用できません", ident),
+        ));
+    }
+
+    let mut replacer = GenericReplacer { bounds: &bounds };
+    for arg in resolved.inputs.iter_mut() {
+        replacer.visit_fn_arg_mut(arg);
+    }
+    if resolved.generics.params.is_empty() {
+        resolved.generics.lt_token = None;
+        resolved.generics.gt_token = None;
+    }
+    Ok(resolved)
+}
+
+fn as_generic_param<'a>(
+    ty: &Type,
+    bounds: &'a [(Ident, Punctuated<TypeParamBound, Token![+]>)],
+) -> Option<&'a (Ident, Punctuated<TypeParamBound, Token![+]>)> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            bounds.iter().find(|(ident, _)| path.is_ident(ident))
+        }
+        _ => None,
+    }
+}
+
+// 型パラメータを impl Trait に置き換える
+struct GenericReplacer<'a> {
+    bounds: &'a [(Ident, Punctuated<TypeParamBound, Token![+]>)],
+}
+
+impl VisitMut for GenericReplacer<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Some((_, bounds)) = as_generic_param(ty, self.bounds) {
+            *ty = Type::ImplTrait(TypeImplTrait {
+                impl_token: Default::default(),
+                bounds: bounds.clone(),
+            });
+            return;
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        visit_mut::visit_type_reference_mut(self, reference);
+        // &impl A + B は曖昧になるため &(impl A + B) にする
+        if let Type::ImplTrait(impl_trait) = &*reference.elem
+            && impl_trait.bounds.len() > 1
+        {
+            let elem = (*reference.elem).clone();
+            *reference.elem = Type::Paren(TypeParen {
+                paren_token: Default::default(),
+                elem: Box::new(elem),
+            });
+        }
+    }
+}
+
+// 型パラメータが使用されているかを調べる
+struct GenericFinder<'a> {
+    bounds: &'a [(Ident, Punctuated<TypeParamBound, Token![+]>)],
+    found: Option<Ident>,
+}
+
+impl VisitMut for GenericFinder<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Some((ident, _)) = as_generic_param(ty, self.bounds) {
+            self.found.get_or_insert_with(|| ident.clone());
+            return;
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+// 参照のライフタイムを 'static にした型 (extern 宣言、関数ポインタの型で使用する)
+pub fn with_static_lifetimes(ty: &Type) -> Type {
+    struct StaticLifetimes;
+    impl VisitMut for StaticLifetimes {
+        fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+            reference.lifetime = Some(syn::Lifetime::new("'static", Span::call_site()));
+            visit_mut::visit_type_reference_mut(self, reference);
+        }
+        fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+            *lifetime = syn::Lifetime::new("'static", lifetime.span());
+        }
+    }
+    let mut ty = ty.clone();
+    StaticLifetimes.visit_type_mut(&mut ty);
+    ty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+    use syn::parse_quote;
+
+    fn resolved(signature: &str) -> String {
+        let sig: Signature = syn::parse_str(signature).unwrap();
+        let sig = resolve_generics(&sig).unwrap();
+        quote! { #sig }.to_string()
+    }
+
+    #[test]
+    fn test_resolve_generics() {
+        let expected: Signature =
+            parse_quote! { fn DrawString(x: i32, string: impl AsRef<str> + Debug) -> i32 };
+        assert_eq!(
+            resolved("fn DrawString<S: AsRef<str> + Debug>(x: i32, string: S) -> i32"),
+            quote! { #expected }.to_string()
+        );
+        assert_eq!(
+            resolved("fn DrawString<S>(x: i32, string: S) -> i32 where S: AsRef<str>, S: Debug"),
+            quote! { #expected }.to_string()
+        );
+
+        // 参照の場合は括弧で囲む、ライフタイムはそのまま残す
+        let expected: Signature =
+            parse_quote! { fn Print<'a>(text: &'a (impl AsRef<str> + ?Sized)) -> i32 };
+        assert_eq!(
+            resolved("fn Print<'a, T>(text: &'a T) -> i32 where T: AsRef<str> + ?Sized"),
+            quote! { #expected }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_resolve_generics_error() {
+        for signature in [
+            "fn Print<T>(text: T) -> i32",
+            "fn Print<T: AsRef<str>>(text: T) -> T",
+            "fn Print<T: AsRef<str> = String>(text: T) -> i32",
+        ] {
+            let sig: Signature = syn::parse_str(signature).unwrap();
+            assert!(resolve_generics(&sig).is_err(), "{}", signature);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// src/handle.rs
+use crate::FunctionWithAttrs;
+use crate::config::GenConfig;
+use crate::context::state_ident;
+use crate::utils::*;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{FnArg, LitStr, PatType, Token, punctuated::Punctuated};
+
+// #[handle(type = GraphHandle, drop = "DeleteGraph")] で宣言されたハンドル型
+pub struct HandleSpec {
+    pub ty: Ident,
+    // Drop 時に呼び出すラップ関数
+    pub drop: Ident,
+    // 解放関数のハンドル以外の引数の数 (Default::default() を渡す)
+    pub drop_default_args: usize,
+    // 解放関数が #[context] の関数か (初期化済みの場合のみ解放関数を呼び出す)
+    pub drop_context: bool,
+}
+
+// 関数シグネチャの #[handle(...)] を集める (同じ型が複数の関数で指定されている場合は1つにまとめる)
+pub fn collect_handles(
+    fns: &Punctuated<FunctionWithAttrs, Token![,]>,
+) -> syn::Result<Vec<HandleSpec>> {
+    let mut handles: Vec<HandleSpec> = Vec::new();
+    for func in fns.iter() {
+        let Some((ty, drop)) = extract_handle_attribute(&func.attrs)? else {
+            continue;
+        };
+        let drop_ident: Ident = drop.parse()?;
+        if let Some(handle) = handles.iter().find(|handle| handle.ty == ty) {
+            if handle.drop != drop_ident {
+                return Err(syn::Error::new_spanned(
+                    &drop,
+                    format!("{} の解放関数が他の関数と異なります", ty),
+                ));
+            }
+            continue;
+        }
+
+        // 解放関数は同じマクロ内で宣言されている必要がある
+        let mut drop_fn = None;
+        for f in fns.iter() {
+            if wrapper_name_of(f)? == drop_ident {
+                drop_fn = Some(f);
+                break;
+            }
+        }
+        let Some(drop_fn) = drop_fn else {
+            return Err(syn::Error::new_spanned(
+                &drop,
+                format!("解放関数 {} が宣言されていません", drop_ident),
+            ));
+        };
+        let wrapper_params = drop_fn
+            .sig
+            .inputs
+            .iter()
+            .filter(|arg| match arg {
+                FnArg::Typed(PatType { attrs, .. }) => {
+                    !is_out_attribute(attrs)
+                        && !attrs
+                            .iter()
+                            .any(|a| a.path().is_ident("out_string") || a.path().is_ident("len_of"))
+                }
+                FnArg::Receiver(_) => false,
+            })
+            .count();
+        if wrapper_params == 0 {
+            return Err(syn::Error::new_spanned(
+                &drop,
+                format!(
+                    "解放関数 {} はハンドルを引数に取る必要があります",
+                    drop_ident
+                ),
+            ));
+        }
+        handles.push(HandleSpec {
+            ty,
+            drop: drop_ident,
+            drop_default_args: wrapper_params - 1,
+            drop_context: extract_context_attribute(&drop_fn.attrs)?.is_some(),
+        });
+    }
+    Ok(handles)
+}
+
+fn wrapper_name_of(func: &FunctionWithAttrs) -> syn::Result<Ident> {
+    Ok(extract_alias_attribute(&func.attrs)?
+        .map(|alias| format_ident!("{}", alias))
+        .unwrap_or_else(|| func.sig.ident.clone()))
+}
+
+// ハンドル型の定義を生成する
+// 型ごとに別の型になるため、GraphHandle を SoundHandle の引数に渡すことはできない
+// ハンドルは初期化したスレッドでのみ使用できるため、Send,Sync を実装しない
+pub fn generate_handles(config: &GenConfig) -> TokenStream {
+    // 解放関数が #[context] の関数の場合、ライブラリの終了後(初期化前)の Drop では解放関数を呼び出さない
+    // (記録時は C 関数を呼び出さないため検査しない)
+    let context_check = config.context.as_ref().map(|context| {
+        let state = state_ident(context);
+        let record_cfg = config
+            .record_feature
+            .as_ref()
+            .map(|feature| quote! { #[cfg(not(feature = #feature))] });
+        quote! {
+            #record_cfg
+            if #state.check().is_err() {
+                return;
+            }
+        }
+    });
+    let mut output = TokenStream::new();
+    for HandleSpec {
+        ty,
+        drop,
+        drop_default_args,
+        drop_context,
+    } in &config.handles
+    {
+        let context_check = context_check.as_ref().filter(|_| *drop_context);
+        let default_args = (0..*drop_default_args).map(|_| quote! { Default::default() });
+        let doc = format!(" Drop 時に {} で解放されるハンドル", drop);
+        output.extend(quote! {
+            #[doc = #doc]
+            #[derive(PartialEq, Eq, Hash)]
+            #[must_use]
+            pub struct #ty(i32, std::marker::PhantomData<*const ()>);
+
+            impl std::fmt::Debug for #ty {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_tuple(stringify!(#ty)).field(&self.0).finish()
+                }
+            }
+
+            impl #ty {
+                /// 生のハンドルから作成する (Drop 時に解放される)
+                pub fn from_raw(handle: i32) -> Self {
+                    Self(handle, std::marker::PhantomData)
+                }
+
+                /// 生のハンドルの値
+                pub fn as_raw(&self) -> i32 {
+                    self.0
+                }
+
+                /// 解放せずに生のハンドルに戻す
+                pub fn into_raw(self) -> i32 {
+                    let handle = self.0;
+                    std::mem::forget(self);
+                    handle
+                }
+
+                /// 解放せずに破棄する (ライブラリの終了時まで残る)
+                pub fn leak(self) {
+                    std::mem::forget(self);
+                }
+            }
+
+            impl Drop for #ty {
+                fn drop(&mut self) {
+                    #context_check
+                    let _ = #drop(self.0, #(#default_args),*);
+                }
+            }
+        });
+    }
+    output
+}
+
+// #[handle(type = GraphHandle, drop = "DeleteGraph")] の型と解放関数名
+pub fn extract_handle_attribute(attrs: &[syn::Attribute]) -> syn::Result<Option<(Ident, LitStr)>> {
+    for attr in attrs {
+        if attr.path().is_ident("handle") {
+            let mut ty = None;
+            let mut drop = None;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("type") {
+                    ty = Some(meta.value()?.parse::<Ident>()?);
+                    Ok(())
+                } else if meta.path.is_ident("drop") {
+                    drop = Some(meta.value()?.parse::<LitStr>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("#[handle] には type, drop のみ指定できます"))
+                }
+            })?;
+            return match (ty, drop) {
+                (Some(ty), Some(drop)) => Ok(Some((ty, drop))),
+                _ => Err(syn::Error::new_spanned(
+                    attr,
+                    "#[handle(type = 型名, drop = \"解放関数\")] の形式で指定してください",
+                )),
+            };
+        }
+    }
+    Ok(None)
+}
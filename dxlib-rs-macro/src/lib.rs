@@ -1,22 +1,29 @@
 // src/lib.rs
 extern crate proc_macro;
-mod dxlib_error;
+mod api;
+mod codegen;
+mod config;
+mod context;
+mod doc;
+mod generics;
+mod handle;
+mod loader;
 mod utils;
 
-use anyhow::Result;
+use codegen::CallMode;
+use config::GenConfig;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use proc_macro2::TokenTree;
+use quote::quote;
 use syn::{
-    Expr, ExprLit, FnArg, GenericArgument, Ident, Lit, LitStr, Meta, MetaNameValue, Pat, PatType,
-    PathArguments, ReturnType, Signature, Token, Type, TypeParamBound, TypePath, TypeReference,
+    Attribute, LitStr, Signature, Token,
     parse::{Parse, ParseStream},
-    parse_macro_input, parse_str,
+    parse_macro_input,
     punctuated::Punctuated,
 };
-use utils::*;
 
 // 属性付き関数
-struct FunctionWithAttrs {
+pub(crate) struct FunctionWithAttrs {
     attrs: Vec<syn::Attribute>,
     sig: syn::Signature,
 }
@@ -29,585 +36,207 @@ impl Parse for FunctionWithAttrs {
     }
 }
 
-// マクロ全体
+// 関数シグネチャの列
+// where 句の述語も , で区切られるため、次の関数の始まり(属性、fn 等)の前の , で区切る
+fn parse_functions(input: ParseStream) -> syn::Result<Punctuated<FunctionWithAttrs, Token![,]>> {
+    let tokens: proc_macro2::TokenStream = input.parse()?;
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let is_function_start = |token: Option<&TokenTree>| match token {
+        None => true,
+        Some(TokenTree::Punct(punct)) => punct.as_char() == '#',
+        Some(TokenTree::Ident(ident)) => ident == "fn" || ident == "unsafe" || ident == "extern",
+        _ => false,
+    };
+
+    let mut fns = Punctuated::new();
+    let mut current = proc_macro2::TokenStream::new();
+    // <...> の入れ子の深さ (-> の > は除く)
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !matches!(i.checked_sub(1).map(|j| &tokens[j]), Some(TokenTree::Punct(p)) if p.as_char() == '-') => {
+                    depth = depth.saturating_sub(1)
+                }
+                ',' if depth == 0 && is_function_start(tokens.get(i + 1)) => {
+                    fns.push_value(syn::parse2(std::mem::take(&mut current))?);
+                    fns.push_punct(Token![,](punct.span()));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        current.extend(std::iter::once(token.clone()));
+    }
+    if !current.is_empty() {
+        fns.push_value(syn::parse2(current)?);
+    }
+    Ok(fns)
+}
+
+// cffi_gen! 全体
+// #![library_name = "..."] 等の内部属性 + 関数シグネチャの列
+struct CffiGenInput {
+    attrs: Vec<Attribute>,
+    fns: Punctuated<FunctionWithAttrs, Token![,]>,
+}
+
+impl Parse for CffiGenInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_inner)?;
+        let fns = parse_functions(input)?;
+        Ok(CffiGenInput { attrs, fns })
+    }
+}
+
+// dxlib_gen! 全体
+// (任意の内部属性) + ライブラリ名 + 関数シグネチャの列
 struct DxlibGenInput {
+    attrs: Vec<Attribute>,
     lib_name: LitStr,
     fns: Punctuated<FunctionWithAttrs, Token![,]>,
 }
 
 impl Parse for DxlibGenInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_inner)?;
         let lib_name: LitStr = input.parse()?;
         input.parse::<Token![,]>()?;
-        let fns = Punctuated::<FunctionWithAttrs, Token![,]>::parse_terminated(input)?;
-        Ok(DxlibGenInput { lib_name, fns })
+        let fns = parse_functions(input)?;
+        Ok(DxlibGenInput {
+            attrs,
+            lib_name,
+            fns,
+        })
     }
 }
 
+// 汎用の C ライブラリ向けラッパー生成マクロ
+// 例:
+//  cffi_gen! {
+//      #![library_name = "DxLib_x64"]
+//      #![func_name_top_prefix = "dx_"]
+//      #![as_result_error_type = "DxLibError"]
+//      #![init_error = "DxLibError::InitializeError"]
+//      #[role = "init"]
+//      fn DxLib_Init() -> i32,
+//  }
+#[proc_macro]
+pub fn cffi_gen(input: TokenStream) -> TokenStream {
+    let CffiGenInput { attrs, fns } = parse_macro_input!(input as CffiGenInput);
+
+    let mut config = GenConfig::cffi_default();
+    if let Err(e) = config.apply_attributes(&attrs) {
+        return e.to_compile_error().into();
+    }
+    match handle::collect_handles(&fns) {
+        Ok(handles) => config.handles = handles,
+        Err(e) => return e.to_compile_error().into(),
+    }
+    generate(&config, &fns)
+}
+
+// DxLib 向けのプリセット (接頭辞 dx_、DxLibError、DxLib_Init/DxLib_End の役割)
 #[proc_macro]
 pub fn dxlib_gen(input: TokenStream) -> TokenStream {
-    let DxlibGenInput { lib_name, fns } = parse_macro_input!(input as DxlibGenInput);
+    let DxlibGenInput {
+        attrs,
+        lib_name,
+        fns,
+    } = parse_macro_input!(input as DxlibGenInput);
+
+    let mut config = GenConfig::dxlib_preset(lib_name);
+    if let Err(e) = config.apply_attributes(&attrs) {
+        return e.to_compile_error().into();
+    }
+    match handle::collect_handles(&fns) {
+        Ok(handles) => config.handles = handles,
+        Err(e) => return e.to_compile_error().into(),
+    }
+    generate(&config, &fns)
+}
 
-    // CString を使うための import
+fn generate(config: &GenConfig, fns: &Punctuated<FunctionWithAttrs, Token![,]>) -> TokenStream {
+    // c_char を使うための import
     let mut output = quote! {
-        use std::ffi::CString;
         use std::os::raw::c_char;
     };
-
-    for FunctionWithAttrs { attrs, sig } in fns.iter() {
-        let wrapper_name = extract_alias_attribute(attrs)
-            .map(|alias| format_ident!("{}", alias))
-            .unwrap_or_else(|| sig.ident.clone());
-        let extern_name = format_ident!("dx_{}", sig.ident.clone());
-
-        let output_ty = &sig.output;
-        let generics = &sig.generics;
-
-        let mut wrapper_args = Vec::new();
-        let mut extern_args = Vec::new();
-        let mut convert_stmts = Vec::new();
-        let mut call_idents = Vec::new();
-
-        let return_type = get_return_type(&sig).unwrap();
-
-        let error_condition =
-            extract_error_condition(attrs).unwrap_or_else(|| quote! { result as i32 == -1i32 });
-        let is_not_result = is_not_result_attribute(&attrs);
-
-        for arg in sig.inputs.iter() {
-            if let FnArg::Typed(PatType { pat, ty, attrs, .. }) = arg {
-                let ident = match &**pat {
-                    Pat::Ident(pi) => &pi.ident,
-                    _ => panic!("パターン付き引数は未対応です"),
-                };
-
-                if let Some(inner_ty) = is_option(&ty) {
-                    // Option<T> の場合
-                    wrapper_args.push(quote! { #ident: Option<#inner_ty> });
-
-                    let default_expr = extract_default_expr(attrs)
-                        .unwrap_or_else(|| quote! { Default::default() });
-
-                    convert_stmts.push(quote! {
-                        let #ident = match #ident {
-                            Some(value) => value,
-                            None => #default_expr,
-                        };
-                    });
-
-                    extern_args.push(quote! { #ident: #inner_ty });
-                    call_idents.push(quote! { #ident });
-
-                    continue;
-                }
-
-                if is_impl_as_ref_type(&ty) {
-                    // まず ty 自体が参照型かどうかを判定
-                    if let Type::Reference(ref_type) = &**ty {
-                        if let Some(inner_ty) = extract_as_ref_generic(&ref_type.elem) {
-                            if ref_type.mutability.is_none() {
-                                if let Type::Path(type_path) = inner_ty {
-                                    let ident_str =
-                                        type_path.path.segments.last().unwrap().ident.to_string();
-
-                                    if ident_str == "str" || ident_str == "String" {
-                                        wrapper_args.push(quote! {
-                                            #ident: &impl AsRef<#inner_ty>
-                                        });
-
-                                        extern_args.push(quote! {
-                                            #ident: *const ::std::os::raw::c_char
-                                        });
-
-                                        let holder_ident = format_ident!("__{}_holder", ident);
-                                        convert_stmts.push(quote! {
-                                            let #holder_ident = CStringHolder::new(#ident.as_ref());
-                                            let #ident = #holder_ident.as_ptr();
-                                        });
-
-                                        call_idents.push(quote! { #ident });
-                                        continue;
-                                    }
-                                }
-                            } else {
-                                if let Type::Path(type_path) = inner_ty {
-                                    let ident_str =
-                                        type_path.path.segments.last().unwrap().ident.to_string();
-
-                                    if ident_str == "str" || ident_str == "String" {
-                                        wrapper_args.push(quote! {
-                                            #ident: &mut impl AsRef<#inner_ty>
-                                        });
-
-                                        extern_args.push(quote! {
-                                            #ident: *const ::std::os::raw::c_char
-                                        });
-
-                                        let holder_ident = format_ident!("__{}_holder", ident);
-                                        convert_stmts.push(quote! {
-                                            let #holder_ident = CStringHolder::new(#ident.as_ref());
-                                            let #ident = #holder_ident.as_ptr();
-                                        });
-
-                                        call_idents.push(quote! { #ident });
-                                        continue;
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    // 通常の impl AsRef<T> 型（参照ではない）
-                    if let Some(inner_ty) = extract_as_ref_generic(&ty) {
-                        if let Type::Path(type_path) = inner_ty {
-                            let ident_str =
-                                type_path.path.segments.last().unwrap().ident.to_string();
-                            if ident_str == "str" || ident_str == "String" {
-                                wrapper_args.push(quote! {
-                                    #ident: impl AsRef<#inner_ty>
-                                });
-
-                                extern_args.push(quote! {
-                                    #ident: *const ::std::os::raw::c_char
-                                });
-
-                                let holder_ident = format_ident!("__{}_holder", ident);
-                                convert_stmts.push(quote! {
-                                    let #holder_ident = CStringHolder::new(#ident.as_ref());
-                                    let #ident = #holder_ident.as_ptr();
-                                });
-
-                                call_idents.push(quote! { #ident });
-                                continue;
-                            }
-                        }
-
-                        // 汎用パターン（*const T）
-                        wrapper_args.push(quote! {
-                            #ident: impl AsRef<#inner_ty>
-                        });
-
-                        extern_args.push(quote! {
-                            #ident: *const #inner_ty
-                        });
-
-                        convert_stmts.push(quote! {
-                            let #ident = #ident.as_ref().as_ptr();
-                        });
-
-                        call_idents.push(quote! { #ident });
-                        continue;
-                    }
-                } else if is_impl_as_mut_type(&ty) {
-                    // まず `ty` 自体が参照型かどうかを判定する
-                    if let Type::Reference(ref_type) = &**ty {
-                        if ref_type.mutability.is_some() {
-                            if let Some(inner_ty) = extract_as_mut_generic(&ref_type.elem) {
-                                // &mut impl AsMut<[T]> にマッチ
-                                if let Type::Slice(slice) = inner_ty {
-                                    let elem_ty = &slice.elem;
-
-                                    wrapper_args.push(quote! {
-                                        #ident: &mut impl AsMut<[#elem_ty]>
-                                    });
-
-                                    extern_args.push(quote! {
-                                        #ident: *mut #elem_ty
-                                    });
-
-                                    convert_stmts.push(quote! {
-                                        let #ident = #ident.as_mut().as_mut_ptr();
-                                    });
-
-                                    call_idents.push(quote! { #ident });
-                                    continue;
-                                }
-                            }
-                        } else {
-                            if let Some(inner_ty) = extract_as_mut_generic(&ref_type.elem) {
-                                // &impl AsMut<[T]> にマッチ
-                                if let Type::Slice(slice) = inner_ty {
-                                    let elem_ty = &slice.elem;
-
-                                    wrapper_args.push(quote! {
-                                        #ident: &impl AsMut<[#elem_ty]>
-                                    });
-
-                                    extern_args.push(quote! {
-                                        #ident: *mut #elem_ty
-                                    });
-
-                                    convert_stmts.push(quote! {
-                                        let #ident = #ident.as_mut().as_mut_ptr();
-                                    });
-
-                                    call_idents.push(quote! { #ident });
-                                    continue;
-                                }
-                            }
-                        }
-                    }
-
-                    // 通常の impl AsMut<[T]> 型の処理
-                    if let Some(inner_ty) = extract_as_mut_generic(&ty) {
-                        if let Type::Slice(slice) = inner_ty {
-                            let elem_ty = &slice.elem;
-
-                            wrapper_args.push(quote! {
-                                #ident: impl AsMut<[#elem_ty]>
-                            });
-
-                            extern_args.push(quote! {
-                                #ident: *mut #elem_ty
-                            });
-
-                            convert_stmts.push(quote! {
-                                let #ident = #ident.as_mut().as_mut_ptr();
-                            });
-
-                            call_idents.push(quote! { #ident });
-                            continue;
-                        }
-                    }
-                }
-
-                if is_impl_to_string(&ty) {
-                    wrapper_args.push(quote! { #ident: impl ToString });
-                    extern_args.push(quote! { #ident: *const c_char });
-
-                    let holder_ident = format_ident!("__{}_holder", ident);
-                    convert_stmts.push(quote! {
-                        let #holder_ident = CStringHolder::new(#ident.to_string());
-                        let #ident = #holder_ident.as_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                }
-
-                if is_impl_display(&ty) {
-                    wrapper_args.push(quote! { #ident: impl Display });
-                    extern_args.push(quote! { #ident: *const c_char });
-
-                    let holder_ident = format_ident!("__{}_holder", ident);
-                    convert_stmts.push(quote! {
-                        let #holder_ident = CStringHolder::new(#ident.to_string());
-                        let #ident = #holder_ident.as_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                }
-
-                // `impl Into<Vec<T>>` の場合、不変と可変を分けて処理
-                if is_impl_trait_into_vec(&ty) {
-                    // Vec<T> の T を取得
-                    let inner_ty = extract_vec_inner_type_from_impl_trait(&ty);
-
-                    if let Some(inner_ty) = inner_ty {
-                        // 不変Vec<T> → *const T
-                        if let Some(_) = is_ref_vec_type(&ty) {
-                            wrapper_args.push(quote! { #ident: impl Into<Vec<#inner_ty>> });
-                            extern_args.push(quote! { #ident: *const #inner_ty });
-
-                            convert_stmts.push(quote! {
-                                let #ident = #ident.as_ptr();
-                            });
-
-                            call_idents.push(quote! { #ident });
-                            continue;
-                        }
-                        // 可変Vec<T> → *mut T
-                        else if let Some(_) = is_mut_ref_vec_type(&ty) {
-                            wrapper_args.push(quote! { #ident: impl Into<Vec<#inner_ty>> });
-                            extern_args.push(quote! { #ident: *mut #inner_ty });
-
-                            convert_stmts.push(quote! {
-                                let #ident = #ident.as_mut_ptr();
-                            });
-
-                            call_idents.push(quote! { #ident });
-                            continue;
-                        }
-                    }
-                }
-                // 配列の場合は、*const Tに変換
-                if is_array(&ty) {
-                    let (inner_ty, n) = extract_array(&ty).unwrap();
-                    wrapper_args.push(quote! { #ident: [#inner_ty;#n] });
-                    extern_args.push(quote! { #ident: *const #inner_ty });
-
-                    convert_stmts.push(quote! {
-                        let #ident = #ident.as_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                } else if is_mut_array(&ty) {
-                    let (inner_ty, n) = extract_mut_array(&ty).unwrap();
-                    wrapper_args.push(quote! { #ident: &mut [#inner_ty;#n] });
-                    extern_args.push(quote! { #ident: *mut #inner_ty });
-
-                    convert_stmts.push(quote! {
-                        let #ident = #ident.as_mut_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                }
-                // 不変スライスの場合は、*const Tに変換
-                if is_slice(&ty) {
-                    let inner_ty = extract_slice(&ty);
-                    wrapper_args.push(quote! { #ident: &[#inner_ty] });
-                    extern_args.push(quote! { #ident: *const #inner_ty });
-
-                    convert_stmts.push(quote! {
-                        let #ident = #ident.as_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                // 可変スライスの場合は、*mut Tに変換
-                } else if is_mut_slice(&ty) {
-                    let inner_ty = extract_mut_slice(&ty);
-                    wrapper_args.push(quote! { #ident: &mut [#inner_ty] });
-                    extern_args.push(quote! { #ident: *mut #inner_ty });
-
-                    convert_stmts.push(quote! {
-                        let #ident = #ident.as_mut_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                }
-                // Vec<T>の場合は、*const Tに変換
-                if is_vec_type(&ty) {
-                    let inner_ty = extract_vec_inner_type(&ty);
-                    wrapper_args.push(quote! { #ident: Vec<#inner_ty> });
-                    extern_args.push(quote! { #ident: *const #inner_ty });
-
-                    convert_stmts.push(quote! {
-                        let #ident = #ident.as_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                // Vec<T>の場合は、*mut Tに変換
-                } else if is_mut_vec_type(&ty) {
-                    let inner_ty = extract_vec_inner_type(&ty);
-                    wrapper_args.push(quote! { #ident: &mut Vec<#inner_ty> });
-                    extern_args.push(quote! { #ident: *mut #inner_ty });
-
-                    convert_stmts.push(quote! {
-                        let #ident = #ident.as_mut_ptr();
-                    });
-
-                    call_idents.push(quote! { #ident });
-                    continue;
-                }
-
-                // &str の場合は *const c_char に変換
-                if let Type::Reference(TypeReference { elem, .. }) = &**ty {
-                    if let Type::Path(TypePath { path, .. }) = &**elem {
-                        if path.is_ident("str") {
-                            wrapper_args.push(quote! { #ident: &str });
-                            extern_args.push(quote! { #ident: *const c_char });
-
-                            let holder_ident = format_ident!("__{}_holder", ident);
-                            convert_stmts.push(quote! {
-                                let #holder_ident = CStringHolder::new(#ident.to_string());
-                                let #ident = #holder_ident.as_ptr();
-                            });
-
-                            call_idents.push(quote! { #ident });
-                            continue;
-                        }
-                    }
-                }
-
-                // String の場合は *const c_char に変換
-                if let Type::Path(TypePath { path, .. }) = &**ty {
-                    if path.is_ident("String") {
-                        wrapper_args.push(quote! { #ident: String });
-                        extern_args.push(quote! { #ident: *const c_char });
-
-                        let holder_ident = format_ident!("__{}_holder", ident);
-                        convert_stmts.push(quote! {
-                            let #holder_ident = CStringHolder::new(#ident.to_string());
-                            let #ident = #holder_ident.as_ptr();
-                        });
-
-                        call_idents.push(quote! { #ident });
-                        continue;
-                    }
-                }
-
-                // &String の場合は *const c_char に変換
-                if let Type::Reference(TypeReference { elem, .. }) = &**ty {
-                    if let Type::Path(TypePath { path, .. }) = &**elem {
-                        if path.is_ident("String") {
-                            wrapper_args.push(quote! { #ident: &String });
-                            extern_args.push(quote! { #ident: *const c_char });
-                            let holder_ident = format_ident!("__{}_holder", ident);
-                            convert_stmts.push(quote! {
-                                let #holder_ident = CStringHolder::new(#ident.to_string());
-                                let #ident = #holder_ident.as_ptr();
-                            });
-
-                            call_idents.push(quote! { #ident });
-                            continue;
-                        }
-                    }
-                }
-                // &mut String の場合は CString に変換 (可変ポインタ *mut c_char)
-                if let Type::Reference(TypeReference {
-                    elem, mutability, ..
-                }) = &**ty
-                {
-                    if let Type::Path(TypePath { path, .. }) = &**elem {
-                        if path.is_ident("String") && mutability.is_some() {
-                            // &mut String の場合
-                            wrapper_args.push(quote! { #ident: &mut String });
-                            extern_args.push(quote! { #ident: *mut c_char });
-                            let holder_ident = format_ident!("__{}_holder", ident);
-                            convert_stmts.push(quote! {
-                                // String を CString に変換し、所有権を取得
-                                let #holder_ident = CString::new(#ident.clone()).unwrap();  // cloneして保持
-                                let #ident = #holder_ident.into_raw();  // *mut c_char を取得
-                            });
-
-                            call_idents.push(quote! { #ident });
-                            continue;
-                        }
-                    }
-                }
-                // それ以外はそのまま
-                wrapper_args.push(quote! { #ident: #ty });
-                extern_args.push(quote! { #ident: #ty });
-                call_idents.push(quote! { #ident });
-            }
+    // ハンドル型は extern のリンクと動的ロードのどちらでも共通
+    output.extend(handle::generate_handles(config));
+
+    // 呼び出し方法と、それを有効にするフィーチャーの条件
+    let mut modes: Vec<(CallMode, Vec<proc_macro2::TokenStream>)> =
+        match (&config.dynamic_loader, &config.dynamic_loader_feature) {
+            (None, _) => vec![(CallMode::Extern, vec![])],
+            (Some(loader), None) => vec![(CallMode::Loader(loader), vec![])],
+            // フィーチャーの有無で extern のリンクと動的ロードを切り替える
+            (Some(loader), Some(feature)) => vec![
+                (CallMode::Extern, vec![quote! { not(feature = #feature) }]),
+                (
+                    CallMode::Loader(loader),
+                    vec![quote! { feature = #feature }],
+                ),
+            ],
+        };
+    // 記録のフィーチャーが有効な場合は、リンク、動的ロードより優先する
+    if let Some(feature) = &config.record_feature {
+        for (_, predicates) in modes.iter_mut() {
+            predicates.push(quote! { not(feature = #feature) });
         }
+        modes.push((CallMode::Record, vec![quote! { feature = #feature }]));
+    }
 
-        let extern_block = quote! {
-            #[link(name = #lib_name)]
-            unsafe extern "stdcall" {
-                fn #extern_name(#(#extern_args),*) #output_ty;
-            }
+    // 呼び出し方法を切り替える場合も、トレイトは最初に生成した関数から1つだけ生成する
+    let mut generated = Vec::new();
+    for (i, (mode, predicates)) in modes.into_iter().enumerate() {
+        let cfg = if predicates.is_empty() {
+            quote! {}
+        } else {
+            quote! { #[cfg(all(#(#predicates),*))] }
         };
-
-        output.extend(extern_block);
-        // DxLib_Init と DxLib_End 用の処理
-        if wrapper_name == "DxLib_Init" {
-            if !is_not_result {
-                let wrapper_fn = quote! {
-                  pub fn #wrapper_name #generics( #(#wrapper_args),* ) -> anyhow::Result<#return_type, DxLibError> {
-                    #(#convert_stmts)*
-                    unsafe {
-                        let result: #return_type = #extern_name(#(#call_idents),*);
-                        if #error_condition {
-                            return Err(DxLibError::InitializeError);
-                        } else {
-                            return Ok(result);
-                        }
-                    }
-                    }
-                };
-                output.extend(wrapper_fn);
-            } else {
-                let wrapper_fn = quote! {
-                  pub fn #wrapper_name #generics( #(#wrapper_args),* ) -> #return_type {
-                    #(#convert_stmts)*
-                    unsafe {
-                        let result: #return_type = #extern_name(#(#call_idents),*);
-                        if #error_condition {
-                            return -1;
-                        } else {
-                            return result;
-                        }
-                    }
-                    }
-                };
-                output.extend(wrapper_fn);
-            }
-            continue; // 次の関数の処理に進む
+        let functions = generate_functions(config, fns, mode, cfg, &mut output);
+        if i == 0 {
+            generated = functions;
         }
-        if wrapper_name == "DxLib_End" {
-            if !is_not_result {
-                let wrapper_fn = quote! {
-                  pub fn #wrapper_name #generics( #(#wrapper_args),* ) -> anyhow::Result<#return_type, DxLibError> {
-                    #(#convert_stmts)*
-
-                    unsafe {
-                        let result: #return_type = #extern_name(#(#call_idents),*);
+    }
+    if let Some(api) = &config.api_trait {
+        output.extend(api::generate_api(api, &generated));
+    }
+    if let Some(context) = &config.context {
+        output.extend(context::generate_context(context, config, &generated));
+    }
 
-                        if #error_condition {
-                            return Err(DxLibError::FinalizeError);
-                        } else {
-                            return Ok(result);
-                        }
+    TokenStream::from(output)
+}
 
-                     }
-                    }
-                };
-                output.extend(wrapper_fn);
-            } else {
-                let wrapper_fn = quote! {
-                  pub fn #wrapper_name #generics( #(#wrapper_args),* ) -> #return_type {
-                    #(#convert_stmts)*
-                    unsafe {
-                        let result: #return_type = #extern_name(#(#call_idents),*);
-                        if #error_condition {
-                            return -1;
-                        } else {
-                            return result;
-                        }
-                    }
-                    }
-                };
-                output.extend(wrapper_fn);
+fn generate_functions(
+    config: &GenConfig,
+    fns: &Punctuated<FunctionWithAttrs, Token![,]>,
+    mode: CallMode,
+    cfg: proc_macro2::TokenStream,
+    output: &mut proc_macro2::TokenStream,
+) -> Vec<codegen::GeneratedFunction> {
+    let mut generated = Vec::new();
+
+    for func in fns.iter() {
+        match codegen::generate_function(config, func, mode, &cfg) {
+            Ok(function) => {
+                output.extend(function.tokens.clone());
+                generated.push(function);
             }
-
-            continue; // 次の関数の処理に進む
-        }
-
-        if !is_not_result {
-            // wrapper 関数の生成
-            let wrapper_fn = quote! {
-                pub fn #wrapper_name #generics( #(#wrapper_args),* ) -> anyhow::Result<#return_type, DxLibError> {
-                    #(#convert_stmts)*
-
-                    unsafe {
-                        let result: #return_type = #extern_name(#(#call_idents),*);
-                        if #error_condition {
-                            return Err(DxLibError::Other(anyhow::anyhow!("Error in {}", stringify!(#wrapper_name))));
-                        } else {
-                            return Ok(result);
-                        }
-                    }
-                }
-            };
-
-            output.extend(wrapper_fn);
-        } else {
-            // wrapper 関数の生成
-            let wrapper_fn = quote! {
-                pub fn #wrapper_name #generics( #(#wrapper_args),* ) -> #return_type {
-                    #(#convert_stmts)*
-
-                    unsafe {
-                        let result: #return_type = #extern_name(#(#call_idents),*);
-                        if #error_condition {
-                            return -1;
-                        } else {
-                            return result;
-                        }
-                    }
+            // 動的ロードの切り替え時に同じエラーが2回表示されないように cfg を付ける
+            Err(e) => {
+                for error in e {
+                    let error = error.to_compile_error();
+                    output.extend(quote! { #cfg #error });
                 }
-            };
-
-            output.extend(wrapper_fn);
+            }
         }
     }
 
-    TokenStream::from(output)
+    if let CallMode::Loader(loader) = mode {
+        output.extend(loader::generate_loader(config, loader, &generated, &cfg));
+    }
+    generated
 }
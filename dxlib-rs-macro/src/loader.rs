@@ -0,0 +1,110 @@
+// src/loader.rs
+use crate::codegen::GeneratedFunction;
+use crate::config::GenConfig;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+
+// 実行時にライブラリを読み込むための関数テーブルを生成する
+// 生成されるラップ関数は #loader::get() から関数ポインタを取り出して呼び出す
+pub fn generate_loader(
+    config: &GenConfig,
+    loader: &Ident,
+    functions: &[GeneratedFunction],
+    cfg: &TokenStream,
+) -> TokenStream {
+    let error_type = &config.error_type;
+    let table_ident = format_ident!("__{}_TABLE", loader.to_string().to_uppercase());
+
+    let names: Vec<_> = functions.iter().map(|f| &f.extern_name).collect();
+    let symbol_names: Vec<_> = names.iter().map(|name| name.to_string()).collect();
+    let fn_ptr_types: Vec<_> = functions.iter().map(|f| &f.fn_ptr_type).collect();
+
+    let load_error = config.load_error.as_ref().map_or_else(
+        || quote! { <#error_type>::from(e.context(format!("Failed to load {}", path))) },
+        |e| quote! { #e(path, e) },
+    );
+    let missing_symbols_error = config.missing_symbols_error.as_ref().map_or_else(
+        || {
+            quote! {
+                <#error_type>::from(anyhow::anyhow!("Missing symbols: {}", missing.join(", ")))
+            }
+        },
+        |e| quote! { #e(missing) },
+    );
+
+    quote! {
+        /// 実行時に読み込んだライブラリの関数テーブル
+        #cfg
+        #[allow(non_snake_case)]
+        pub struct #loader {
+            _library: DynamicLibrary,
+            #( #names: Option<#fn_ptr_types>, )*
+        }
+
+        #cfg
+        static #table_ident: std::sync::OnceLock<#loader> = std::sync::OnceLock::new();
+
+        #cfg
+        impl #loader {
+            /// ライブラリを読み込み、関数テーブルとして登録する
+            /// 見つからない関数がある場合は、その関数名の一覧をエラーとして返す
+            pub fn load(path: impl AsRef<std::ffi::OsStr>) -> Result<&'static #loader, #error_type> {
+                let table = Self::open(path.as_ref())?;
+                let missing = table.missing_symbols();
+                if !missing.is_empty() {
+                    return Err(#missing_symbols_error);
+                }
+                Self::install(table)
+            }
+
+            /// 見つからない関数があってもライブラリを読み込み、関数テーブルとして登録する
+            /// 見つからなかった関数を呼び出した場合はエラーを返す
+            pub fn load_partial(path: impl AsRef<std::ffi::OsStr>) -> Result<&'static #loader, #error_type> {
+                let table = Self::open(path.as_ref())?;
+                Self::install(table)
+            }
+
+            /// 登録済みの関数テーブルを取得する
+            pub fn get() -> Option<&'static #loader> {
+                #table_ident.get()
+            }
+
+            /// ライブラリに存在しなかった関数名の一覧
+            pub fn missing_symbols(&self) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                #(
+                    if self.#names.is_none() {
+                        missing.push(#symbol_names);
+                    }
+                )*
+                missing
+            }
+
+            fn open(path: &std::ffi::OsStr) -> Result<#loader, #error_type> {
+                let library = match DynamicLibrary::open(path) {
+                    Ok(library) => library,
+                    Err(e) => {
+                        let path = path.to_string_lossy().into_owned();
+                        return Err(#load_error);
+                    }
+                };
+                unsafe {
+                    Ok(#loader {
+                        #( #names: library.symbol::<#fn_ptr_types>(#symbol_names), )*
+                        _library: library,
+                    })
+                }
+            }
+
+            fn install(table: #loader) -> Result<&'static #loader, #error_type> {
+                if #table_ident.set(table).is_err() {
+                    return Err(<#error_type>::from(anyhow::anyhow!(
+                        "{} is already loaded",
+                        stringify!(#loader)
+                    )));
+                }
+                Ok(#table_ident.get().unwrap())
+            }
+        }
+    }
+}
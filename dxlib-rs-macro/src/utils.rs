@@ -1,125 +1,11 @@
-use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use crate::config::attribute_str_value;
+use quote::quote;
 use syn::{
-    Expr, ExprLit, FnArg, GenericArgument, Ident, Lit, LitStr, Meta, MetaNameValue, Pat, PatType,
-    PathArguments, ReturnType, Signature, Token, Type, TypeArray, TypeImplTrait, TypeParamBound,
-    TypePath, TypeReference, TypeSlice,
-    parse::{Parse, ParseStream},
-    parse_macro_input, parse_str,
-    punctuated::Punctuated,
+    Expr, GenericArgument, ParenthesizedGenericArguments, PathArguments, ReturnType, Signature,
+    Type, TypeArray, TypeImplTrait, TypeParamBound, TypePath, TypePtr, TypeReference,
+    TypeTraitObject,
 };
-// 型 `a` と `b` が構造的に同じかを判定（再帰）
-pub fn type_eq(a: &Type, b: &Type) -> bool {
-    match (a, b) {
-        (Type::Path(a_path), Type::Path(b_path)) => {
-            let a_segments = &a_path.path.segments;
-            let b_segments = &b_path.path.segments;
-
-            if a_segments.len() != b_segments.len() {
-                return false;
-            }
-
-            for (a_seg, b_seg) in a_segments.iter().zip(b_segments.iter()) {
-                if a_seg.ident != b_seg.ident {
-                    return false;
-                }
-
-                match (&a_seg.arguments, &b_seg.arguments) {
-                    (
-                        PathArguments::AngleBracketed(a_args),
-                        PathArguments::AngleBracketed(b_args),
-                    ) => {
-                        let a_generic = &a_args.args;
-                        let b_generic = &b_args.args;
-
-                        if a_generic.len() != b_generic.len() {
-                            return false;
-                        }
-
-                        for (a_arg, b_arg) in a_generic.iter().zip(b_generic.iter()) {
-                            match (a_arg, b_arg) {
-                                (GenericArgument::Type(a_ty), GenericArgument::Type(b_ty)) => {
-                                    if !type_eq(a_ty, b_ty) {
-                                        return false;
-                                    }
-                                }
-                                _ => return false, // lifetimesや他の引数には未対応
-                            }
-                        }
-                    }
-                    (PathArguments::None, PathArguments::None) => {}
-                    _ => return false,
-                }
-            }
-
-            true
-        }
-        _ => false,
-    }
-}
 
-// `impl Trait<SomeType>` において、Trait名と型引数の型構造が一致するかを判定
-pub fn is_impl_trait_with_target_type_path(
-    ty: &Type,
-    trait_name: &str,
-    expected_ty: &Type,
-) -> bool {
-    match ty {
-        Type::ImplTrait(it) => it.bounds.iter().any(|bound| {
-            if let TypeParamBound::Trait(trait_bound) = bound {
-                let path = &trait_bound.path;
-
-                if let Some(last_segment) = path.segments.last() {
-                    if last_segment.ident == trait_name {
-                        if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
-                            return args.args.iter().any(|arg| {
-                                if let GenericArgument::Type(inner_ty) = arg {
-                                    return type_eq(inner_ty, expected_ty);
-                                }
-                                false
-                            });
-                        }
-                    }
-                }
-            }
-            false
-        }),
-        _ => false,
-    }
-}
-
-pub fn is_impl_trait_with_target_type(ty: &Type, trait_name: &str, type_arg_name: &str) -> bool {
-    match ty {
-        Type::ImplTrait(it) => it.bounds.iter().any(|bound| {
-            if let TypeParamBound::Trait(trait_bound) = bound {
-                let path = &trait_bound.path;
-
-                if let Some(last_segment) = path.segments.last() {
-                    if last_segment.ident == trait_name {
-                        if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
-                            return args.args.iter().any(|arg| {
-                                if let GenericArgument::Type(Type::Path(type_path)) = arg {
-                                    if let Some(ident) = type_path.path.get_ident() {
-                                        return ident == type_arg_name;
-                                    }
-                                }
-                                false
-                            });
-                        }
-                    }
-                }
-            }
-            false
-        }),
-        _ => false,
-    }
-}
-pub fn is_impl(ty: &Type) -> bool {
-    match ty {
-        Type::ImplTrait(_) => true,
-        _ => false,
-    }
-}
 pub fn is_impl_trait_named(ty: &Type, target: &str) -> bool {
     match ty {
         Type::ImplTrait(it) => it
@@ -145,48 +31,46 @@ pub fn get_return_type(sig: &Signature) -> Option<&syn::Type> {
     }
 }
 
-pub fn extract_default_expr(attrs: &[syn::Attribute]) -> Option<proc_macro2::TokenStream> {
+pub fn extract_default_expr(
+    attrs: &[syn::Attribute],
+) -> syn::Result<Option<proc_macro2::TokenStream>> {
     for attr in attrs {
         if attr.path().is_ident("default") {
-            if let Meta::NameValue(MetaNameValue { value, .. }) = &attr.meta {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Str(lit_str),
-                    ..
-                }) = value
-                {
-                    let value = lit_str.value();
-                    return Some(match value.as_str() {
-                        "null" => quote! { std::ptr::null() },
-                        "null_mut" => quote! { std::ptr::null_mut() },
-                        "default" => quote! { Default::default() },
-                        other => {
-                            let tokens: proc_macro2::TokenStream =
-                                other.parse().expect("Invalid default literal");
-                            quote! { #tokens }
-                        }
-                    });
+            let lit_str = attribute_str_value(attr)?;
+            return Ok(Some(match lit_str.value().as_str() {
+                "null" => quote! { std::ptr::null() },
+                "null_mut" => quote! { std::ptr::null_mut() },
+                "default" => quote! { Default::default() },
+                _ => {
+                    let expr: Expr = lit_str.parse().map_err(|e| {
+                        syn::Error::new(e.span(), format!("#[default] の式が不正です: {}", e))
+                    })?;
+                    quote! { #expr }
                 }
-            }
+            }));
         }
     }
-    None
+    Ok(None)
 }
 // Option<T>かどうかを判定
 pub fn is_option(ty: &Type) -> Option<&Type> {
-    if let Type::Path(TypePath { path, .. }) = ty {
-        if path.segments.len() == 1 && path.segments[0].ident == "Option" {
-            if let PathArguments::AngleBracketed(args) = &path.segments[0].arguments {
-                if let Some(GenericArgument::Type(inner_ty)) = args.args.first() {
-                    return Some(inner_ty);
-                }
-            }
-        }
+    if let Type::Path(TypePath { path, .. }) = ty
+        && path.segments.len() == 1
+        && path.segments[0].ident == "Option"
+        && let PathArguments::AngleBracketed(args) = &path.segments[0].arguments
+        && let Some(GenericArgument::Type(inner_ty)) = args.args.first()
+    {
+        return Some(inner_ty);
     }
     None
 }
 
 // AsRef<T>かどうかを判定
 pub fn is_impl_as_ref_type(ty: &Type) -> bool {
+    // (impl A + B) の場合
+    if let Type::Paren(paren) = ty {
+        return is_impl_as_ref_type(&paren.elem);
+    }
     // 参照型(&T, &mut T)の場合
     if let Type::Reference(ref_type) = ty {
         // &mutの場合も対象にする
@@ -198,10 +82,10 @@ pub fn is_impl_as_ref_type(ty: &Type) -> bool {
         for bound in bounds {
             if let syn::TypeParamBound::Trait(trait_bound) = bound {
                 let segments = &trait_bound.path.segments;
-                if let Some(segment) = segments.last() {
-                    if segment.ident == "AsRef" {
-                        return true;
-                    }
+                if let Some(segment) = segments.last()
+                    && segment.ident == "AsRef"
+                {
+                    return true;
                 }
             }
         }
@@ -211,6 +95,10 @@ pub fn is_impl_as_ref_type(ty: &Type) -> bool {
 
 // AsRef<T>を取り出して返す
 pub fn extract_as_ref_generic(ty: &Type) -> Option<&Type> {
+    // (impl A + B) の場合
+    if let Type::Paren(paren) = ty {
+        return extract_as_ref_generic(&paren.elem);
+    }
     // 参照型(&T, &mut T)の場合
     if let Type::Reference(ref_type) = ty {
         return extract_as_ref_generic(&ref_type.elem);
@@ -221,14 +109,13 @@ pub fn extract_as_ref_generic(ty: &Type) -> Option<&Type> {
         for bound in bounds {
             if let TypeParamBound::Trait(trait_bound) = bound {
                 let segments = &trait_bound.path.segments;
-                if let Some(segment) = segments.last() {
-                    if segment.ident == "AsRef" {
-                        if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                            for arg in &args.args {
-                                if let GenericArgument::Type(inner_ty) = arg {
-                                    return Some(inner_ty);
-                                }
-                            }
+                if let Some(segment) = segments.last()
+                    && segment.ident == "AsRef"
+                    && let PathArguments::AngleBracketed(args) = &segment.arguments
+                {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(inner_ty) = arg {
+                            return Some(inner_ty);
                         }
                     }
                 }
@@ -240,6 +127,10 @@ pub fn extract_as_ref_generic(ty: &Type) -> Option<&Type> {
 
 // AsMut<T>かどうかを判定
 pub fn is_impl_as_mut_type(ty: &Type) -> bool {
+    // (impl A + B) の場合
+    if let Type::Paren(paren) = ty {
+        return is_impl_as_mut_type(&paren.elem);
+    }
     // 参照型(&T, &mut T)の場合
     if let Type::Reference(ref_type) = ty {
         // &mutの場合も対象にする
@@ -249,12 +140,11 @@ pub fn is_impl_as_mut_type(ty: &Type) -> bool {
     // 通常のimpl AsMut<T>の場合
     if let Type::ImplTrait(TypeImplTrait { bounds, .. }) = ty {
         for bound in bounds {
-            if let syn::TypeParamBound::Trait(trait_bound) = bound {
-                if let Some(segment) = trait_bound.path.segments.last() {
-                    if segment.ident == "AsMut" {
-                        return true;
-                    }
-                }
+            if let syn::TypeParamBound::Trait(trait_bound) = bound
+                && let Some(segment) = trait_bound.path.segments.last()
+                && segment.ident == "AsMut"
+            {
+                return true;
             }
         }
     }
@@ -262,6 +152,10 @@ pub fn is_impl_as_mut_type(ty: &Type) -> bool {
 }
 
 pub fn extract_as_mut_generic(ty: &Type) -> Option<&Type> {
+    // (impl A + B) の場合
+    if let Type::Paren(paren) = ty {
+        return extract_as_mut_generic(&paren.elem);
+    }
     // 参照型(&T, &mut T)の場合
     if let Type::Reference(ref_type) = ty {
         return extract_as_mut_generic(&ref_type.elem);
@@ -272,14 +166,13 @@ pub fn extract_as_mut_generic(ty: &Type) -> Option<&Type> {
         for bound in bounds {
             if let TypeParamBound::Trait(trait_bound) = bound {
                 let segments = &trait_bound.path.segments;
-                if let Some(segment) = segments.last() {
-                    if segment.ident == "AsMut" {
-                        if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                            for arg in &args.args {
-                                if let GenericArgument::Type(inner_ty) = arg {
-                                    return Some(inner_ty);
-                                }
-                            }
+                if let Some(segment) = segments.last()
+                    && segment.ident == "AsMut"
+                    && let PathArguments::AngleBracketed(args) = &segment.arguments
+                {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(inner_ty) = arg {
+                            return Some(inner_ty);
                         }
                     }
                 }
@@ -289,10 +182,7 @@ pub fn extract_as_mut_generic(ty: &Type) -> Option<&Type> {
     None
 }
 
-// 配列[num;T]かどうかを判定
-pub fn is_array(ty: &Type) -> bool {
-    matches!(*ty, Type::Array(_))
-}
+// 配列[num;T]の要素型と長さを返す
 pub fn extract_array(ty: &Type) -> Option<(&Type, &Expr)> {
     if let Type::Array(TypeArray { elem, len, .. }) = ty {
         Some((elem.as_ref(), len))
@@ -300,20 +190,6 @@ pub fn extract_array(ty: &Type) -> Option<(&Type, &Expr)> {
         None
     }
 }
-// 可変配列（&mut [T;N]）かどうかを判定
-pub fn is_mut_array(ty: &Type) -> bool {
-    if let Type::Reference(TypeReference {
-        elem,
-        mutability: Some(_),
-        ..
-    }) = ty
-    {
-        matches!(**elem, Type::Array(_))
-    } else {
-        false
-    }
-}
-
 // `&mut [T; N]` を受け取り、要素型 `T` と長さ `N` を返す
 pub fn extract_mut_array(ty: &Type) -> Option<(&Type, &Expr)> {
     if let Type::Reference(TypeReference {
@@ -401,43 +277,377 @@ pub fn extract_mut_slice(ty: &Type) -> Option<&Type> {
     }
 }
 
-pub fn extract_error_condition(attrs: &[syn::Attribute]) -> Option<proc_macro2::TokenStream> {
+pub fn extract_error_condition(
+    attrs: &[syn::Attribute],
+) -> syn::Result<Option<proc_macro2::TokenStream>> {
     for attr in attrs {
         if attr.path().is_ident("error_condition") {
-            if let Meta::NameValue(MetaNameValue { value, .. }) = &attr.meta {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Str(lit_str),
-                    ..
-                }) = value
-                {
-                    let value = lit_str.value();
-                    return Some(value.parse().expect("Invalid error condition expression"));
-                }
-            }
+            let lit_str = attribute_str_value(attr)?;
+            let expr: Expr = lit_str.parse().map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    format!("#[error_condition] の式が不正です: {}", e),
+                )
+            })?;
+            return Ok(Some(quote! { #expr }));
         }
     }
-    None
+    Ok(None)
 }
 
-pub fn extract_alias_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+pub fn extract_alias_attribute(attrs: &[syn::Attribute]) -> syn::Result<Option<String>> {
     for attr in attrs {
         if attr.path().is_ident("alias") {
-            if let Meta::NameValue(MetaNameValue { value, .. }) = &attr.meta {
-                if let Expr::Lit(ExprLit {
-                    lit: Lit::Str(lit_str),
-                    ..
-                }) = value
-                {
-                    return Some(lit_str.value());
+            let lit_str = attribute_str_value(attr)?;
+            // 関数名として使用できるかを確認する
+            lit_str.parse::<syn::Ident>().map_err(|_| {
+                syn::Error::new_spanned(&lit_str, "#[alias] には関数名を指定してください")
+            })?;
+            return Ok(Some(lit_str.value()));
+        }
+    }
+    Ok(None)
+}
+
+// 関数シグネチャの前に指定できる属性
+pub const FUNCTION_ATTRIBUTES: &[&str] = &[
+    "doc",
+    "doc_en",
+    "alias",
+    "not_result",
+    "error_condition",
+    "role",
+    "abi",
+    "link_kind",
+    "call_error",
+    "wide",
+    "lossy",
+    "handle",
+    "dx_enum",
+    "trace",
+    "format",
+    "context",
+];
+// 引数の前に指定できる属性
+pub const PARAM_ATTRIBUTES: &[&str] = &[
+    "default",
+    "out",
+    "out_string",
+    "len_of",
+    "capacity",
+    "char_code_format",
+    "lossy",
+    "dx_enum",
+    "callback",
+];
+
+// 未対応の属性(綴りの誤り等)をエラーにする
+// known に含まれず other に含まれる場合は、指定する位置の誤りとして扱う
+pub fn validate_attributes(
+    attrs: &[syn::Attribute],
+    known: &[&str],
+    other: &[&str],
+    place: &str,
+) -> syn::Result<()> {
+    for attr in attrs {
+        let path = attr.path();
+        let name = match path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => String::new(),
+        };
+        if known.contains(&name.as_str()) {
+            continue;
+        }
+        let message = if other.contains(&name.as_str()) {
+            format!("#[{}] は{}には指定できません", name, place)
+        } else {
+            match known
+                .iter()
+                .map(|candidate| (edit_distance(&name, candidate), candidate))
+                .filter(|(distance, _)| *distance <= 2)
+                .min()
+            {
+                Some((_, candidate)) => {
+                    format!("未対応の属性です (#[{}] の誤りではありませんか)", candidate)
                 }
+                None => format!(
+                    "未対応の属性です ({}に指定できる属性: {})",
+                    place,
+                    known.join(", ")
+                ),
             }
+        };
+        return Err(syn::Error::new_spanned(path, message));
+    }
+    Ok(())
+}
+
+// 2つの文字列の編集距離 (属性名の候補の表示用)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1));
         }
+        prev = current;
     }
-    None
+    prev[b.len()]
 }
+
 pub fn is_not_result_attribute(attrs: &[syn::Attribute]) -> bool {
     attrs.iter().any(|attr| attr.path().is_ident("not_result"))
 }
+// 関数の #[wide] 属性 (W 付きの関数など、文字列を UTF-16 で渡す)
+pub fn is_wide_attribute(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("wide"))
+}
+// #[lossy] 属性 (文字列の途中のNUL文字をエラーにせず、そこまでで切り詰める)
+pub fn is_lossy_attribute(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("lossy"))
+}
+// 引数の #[char_code_format] 属性 (呼び出し成功後に文字コードの設定として記録する)
+pub fn is_char_code_format_attribute(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("char_code_format"))
+}
+// #[trace(skip)] 属性 (#![trace_feature] が有効でも呼び出しを記録しない)
+pub fn is_trace_skip_attribute(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    for attr in attrs {
+        if attr.path().is_ident("trace") {
+            let mut skip = false;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("#[trace] には skip のみ指定できます"))
+                }
+            })?;
+            if !skip {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "#[trace(skip)] の形式で指定してください",
+                ));
+            }
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+// #[context],#[context(mut)] 属性 (#![context] の型のメソッドにする、Some(true) は &mut self のメソッド)
+pub fn extract_context_attribute(attrs: &[syn::Attribute]) -> syn::Result<Option<bool>> {
+    for attr in attrs {
+        if attr.path().is_ident("context") {
+            let mut mutable = false;
+            match &attr.meta {
+                syn::Meta::Path(_) => {}
+                syn::Meta::List(_) => attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("mut") {
+                        mutable = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("#[context] には mut のみ指定できます"))
+                    }
+                })?,
+                syn::Meta::NameValue(_) => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "#[context] もしくは #[context(mut)] の形式で指定してください",
+                    ));
+                }
+            }
+            return Ok(Some(mutable));
+        }
+    }
+    Ok(None)
+}
+// #[format(macro = "draw_format_string")] 属性 (可変長引数の関数を Rust の書式で呼び出すマクロの名前)
+pub fn extract_format_macro(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Ident>> {
+    for attr in attrs {
+        if attr.path().is_ident("format") {
+            let mut name = None;
+            if let syn::Meta::List(_) = &attr.meta {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("macro") {
+                        let lit_str: syn::LitStr = meta.value()?.parse()?;
+                        name = Some(lit_str.parse::<syn::Ident>().map_err(|_| {
+                            syn::Error::new_spanned(&lit_str, "マクロ名を指定してください")
+                        })?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("#[format] には macro のみ指定できます"))
+                    }
+                })?;
+            }
+            return match name {
+                Some(name) => Ok(Some(name)),
+                None => Err(syn::Error::new_spanned(
+                    attr,
+                    "#[format(macro = \"マクロ名\")] の形式で指定してください",
+                )),
+            };
+        }
+    }
+    Ok(None)
+}
+// 引数の #[out_string(capacity = N)] 属性の容量
+pub fn extract_out_string_capacity(attrs: &[syn::Attribute]) -> syn::Result<Option<Expr>> {
+    for attr in attrs {
+        if attr.path().is_ident("out_string") {
+            let mut capacity = None;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("capacity") {
+                    capacity = Some(meta.value()?.parse::<Expr>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("#[out_string] には capacity のみ指定できます"))
+                }
+            })?;
+            return capacity.map(Some).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    attr,
+                    "#[out_string(capacity = N)] の形式で指定してください",
+                )
+            });
+        }
+    }
+    Ok(None)
+}
+// bool かどうかを判定 (DxLib の TRUE/FALSE の i32 として渡す)
+pub fn is_bool_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { path, .. }) if path.is_ident("bool"))
+}
+// #[dx_enum] 属性 (DxEnum を実装した列挙型を i32 として渡す、受け取る)
+pub fn is_dx_enum_attribute(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("dx_enum"))
+}
+// &mut String かどうかを判定
+pub fn is_mut_string(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Reference(TypeReference { elem, mutability: Some(_), .. })
+            if matches!(&**elem, Type::Path(TypePath { path, .. }) if path.is_ident("String"))
+    )
+}
+// &mut String の引数の #[capacity = N] 属性 (C側で書き込める最大文字数)
+pub fn extract_capacity(attrs: &[syn::Attribute]) -> syn::Result<Option<Expr>> {
+    for attr in attrs {
+        if attr.path().is_ident("capacity") {
+            return Ok(Some(attr.meta.require_name_value()?.value.clone()));
+        }
+    }
+    Ok(None)
+}
+// 引数の #[len_of(buffer)] 属性の対象の引数名
+pub fn extract_len_of(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Ident>> {
+    for attr in attrs {
+        if attr.path().is_ident("len_of") {
+            return attr.parse_args::<syn::Ident>().map(Some);
+        }
+    }
+    Ok(None)
+}
+// 引数の #[callback] 属性 (クロージャを C の関数ポインタとして渡す)
+pub fn is_callback_attribute(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("callback"))
+}
+// #[callback] の引数の型 Option<Box<dyn FnMut(A, B) -> R + Send>> の dyn の型と FnMut の引数、戻り値
+pub fn extract_callback(ty: &Type) -> Option<(&Type, &ParenthesizedGenericArguments)> {
+    let Type::Path(TypePath { path, .. }) = is_option(ty)? else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Box" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let Some(GenericArgument::Type(dyn_ty)) = args.args.first() else {
+        return None;
+    };
+    let Type::TraitObject(TypeTraitObject { bounds, .. }) = dyn_ty else {
+        return None;
+    };
+    let trait_segment = |name: &str| {
+        bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Trait(tb) => tb
+                .path
+                .segments
+                .last()
+                .filter(|segment| segment.ident == name),
+            _ => None,
+        })
+    };
+    // 別スレッドから呼ばれる場合があるため Send を要求する
+    trait_segment("Send")?;
+    match &trait_segment("FnMut")?.arguments {
+        PathArguments::Parenthesized(fn_args) => Some((dyn_ty, fn_args)),
+        _ => None,
+    }
+}
+// 生ポインタを含む型かどうかを判定 (Option<*mut T> 等の型引数も含む)
+pub fn contains_raw_pointer(ty: &Type) -> bool {
+    match ty {
+        Type::Ptr(_) => true,
+        Type::Path(TypePath { path, .. }) => path.segments.iter().any(|segment| {
+            matches!(&segment.arguments, PathArguments::AngleBracketed(args)
+                if args.args.iter().any(|arg| matches!(arg, GenericArgument::Type(ty) if contains_raw_pointer(ty))))
+        }),
+        Type::Reference(TypeReference { elem, .. }) => contains_raw_pointer(elem),
+        Type::Slice(slice) => contains_raw_pointer(&slice.elem),
+        Type::Array(array) => contains_raw_pointer(&array.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().any(contains_raw_pointer),
+        Type::Paren(paren) => contains_raw_pointer(&paren.elem),
+        Type::Group(group) => contains_raw_pointer(&group.elem),
+        _ => false,
+    }
+}
+// 生ポインタを受け取る引数 (#[callback] の引数を除く)
+// 呼び出し側がポインタの有効性を保証する必要があるため、ラップ関数を unsafe fn にする
+pub fn raw_pointer_params(sig: &Signature) -> Vec<&syn::Ident> {
+    sig.inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(syn::PatType { pat, ty, attrs, .. })
+                if !is_callback_attribute(attrs) && contains_raw_pointer(ty) =>
+            {
+                match &**pat {
+                    syn::Pat::Ident(pi) => Some(&pi.ident),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+// #[len_of] で長さを渡す生ポインタの、スライス版の関数での型 (*mut c_void -> &mut [u8]、*const T -> &[T])
+pub fn raw_pointer_slice(ty: &Type) -> Option<proc_macro2::TokenStream> {
+    let Type::Ptr(TypePtr {
+        mutability, elem, ..
+    }) = ty
+    else {
+        return None;
+    };
+    let elem = if is_c_void(elem) {
+        quote! { u8 }
+    } else {
+        quote! { #elem }
+    };
+    Some(quote! { &#mutability [#elem] })
+}
+pub fn is_c_void(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { path, .. })
+        if path.segments.last().is_some_and(|segment| segment.ident == "c_void"))
+}
+// 引数の #[out] 属性 (C側で書き込まれる値を戻り値として受け取る)
+pub fn is_out_attribute(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("out"))
+}
 
 pub fn is_impl_trait_into_vec(ty: &Type) -> bool {
     match ty {
@@ -445,17 +655,16 @@ pub fn is_impl_trait_into_vec(ty: &Type) -> bool {
             if let TypeParamBound::Trait(trait_bound) = bound {
                 let path = &trait_bound.path;
 
-                if let Some(last_segment) = path.segments.last() {
-                    if last_segment.ident == "Into" {
-                        if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
-                            return args.args.iter().any(|arg| {
-                                if let GenericArgument::Type(inner_ty) = arg {
-                                    return is_vec_type(inner_ty);
-                                }
-                                false
-                            });
+                if let Some(last_segment) = path.segments.last()
+                    && last_segment.ident == "Into"
+                    && let PathArguments::AngleBracketed(args) = &last_segment.arguments
+                {
+                    return args.args.iter().any(|arg| {
+                        if let GenericArgument::Type(inner_ty) = arg {
+                            return is_vec_type(inner_ty);
                         }
-                    }
+                        false
+                    });
                 }
             }
             false
@@ -465,10 +674,10 @@ pub fn is_impl_trait_into_vec(ty: &Type) -> bool {
 }
 
 pub fn is_vec_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(last_segment) = type_path.path.segments.last() {
-            return last_segment.ident == "Vec";
-        }
+    if let Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+    {
+        return last_segment.ident == "Vec";
     }
     false
 }
@@ -479,26 +688,22 @@ pub fn is_mut_vec_type(ty: &Type) -> bool {
         elem,
         ..
     }) = ty
+        && let Type::Path(TypePath { path, .. }) = elem.as_ref()
+        && let Some(last_segment) = path.segments.last()
     {
-        if let Type::Path(TypePath { path, .. }) = elem.as_ref() {
-            if let Some(last_segment) = path.segments.last() {
-                return last_segment.ident == "Vec";
-            }
-        }
+        return last_segment.ident == "Vec";
     }
     false
 }
 pub fn extract_vec_inner_type(ty: &Type) -> Option<&Type> {
-    if let Type::Path(type_path) = ty {
-        if let Some(last_segment) = type_path.path.segments.last() {
-            if last_segment.ident == "Vec" {
-                if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
-                    for arg in &args.args {
-                        if let GenericArgument::Type(inner_ty) = arg {
-                            return Some(inner_ty);
-                        }
-                    }
-                }
+    if let Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        && last_segment.ident == "Vec"
+        && let PathArguments::AngleBracketed(args) = &last_segment.arguments
+    {
+        for arg in &args.args {
+            if let GenericArgument::Type(inner_ty) = arg {
+                return Some(inner_ty);
             }
         }
     }
@@ -512,20 +717,18 @@ pub fn extract_vec_inner_type(ty: &Type) -> Option<&Type> {
 pub fn extract_vec_inner_type_from_impl_trait(ty: &Type) -> Option<&Type> {
     if let Type::ImplTrait(it) = ty {
         for bound in &it.bounds {
-            if let TypeParamBound::Trait(trait_bound) = bound {
-                if trait_bound.path.segments.last()?.ident == "Into" {
-                    if let PathArguments::AngleBracketed(args) =
-                        &trait_bound.path.segments.last()?.arguments
-                    {
-                        for arg in &args.args {
-                            if let GenericArgument::Type(ty) = arg {
-                                return extract_vec_inner_type(ty);
-                            }
-                            // &mut Vec<T> などの参照タイプも対応
-                            if let Type::Reference(TypeReference { elem, .. }) = ty {
-                                return extract_vec_inner_type(elem);
-                            }
-                        }
+            if let TypeParamBound::Trait(trait_bound) = bound
+                && trait_bound.path.segments.last()?.ident == "Into"
+                && let PathArguments::AngleBracketed(args) =
+                    &trait_bound.path.segments.last()?.arguments
+            {
+                for arg in &args.args {
+                    if let GenericArgument::Type(ty) = arg {
+                        return extract_vec_inner_type(ty);
+                    }
+                    // &mut Vec<T> などの参照タイプも対応
+                    if let Type::Reference(TypeReference { elem, .. }) = ty {
+                        return extract_vec_inner_type(elem);
                     }
                 }
             }
@@ -539,16 +742,12 @@ pub fn is_ref_vec_type(ty: &Type) -> Option<()> {
     if let Type::Reference(TypeReference {
         elem, mutability, ..
     }) = ty
+        && mutability.is_none()
+        && let Type::Path(TypePath { path, .. }) = elem.as_ref()
+        && let Some(last_segment) = path.segments.last()
+        && last_segment.ident == "Vec"
     {
-        if mutability.is_none() {
-            if let Type::Path(TypePath { path, .. }) = elem.as_ref() {
-                if let Some(last_segment) = path.segments.last() {
-                    if last_segment.ident == "Vec" {
-                        return Some(()); // 不変 Vec<T>
-                    }
-                }
-            }
-        }
+        return Some(()); // 不変 Vec<T>
     }
     None
 }
@@ -560,41 +759,111 @@ pub fn is_mut_ref_vec_type(ty: &Type) -> Option<()> {
         mutability: Some(_),
         ..
     }) = ty
+        && let Type::Path(TypePath { path, .. }) = elem.as_ref()
+        && let Some(last_segment) = path.segments.last()
+        && last_segment.ident == "Vec"
     {
-        if let Type::Path(TypePath { path, .. }) = elem.as_ref() {
-            if let Some(last_segment) = path.segments.last() {
-                if last_segment.ident == "Vec" {
-                    return Some(()); // 可変 Vec<T>
-                }
-            }
-        }
+        return Some(()); // 可変 Vec<T>
     }
     None
 }
 
-pub fn to_pascal_case(s: &str) -> String {
-    s.split('_')
-        .filter(|part| !part.is_empty())
-        .map(|part| {
-            let mut c = part.chars();
-            match c.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().collect::<String>() + c.as_str(),
+// そのまま C に渡せない型の場合はエラーメッセージを返す
+pub fn unsupported_type_message(ty: &Type) -> Option<&'static str> {
+    match ty {
+        Type::ImplTrait(_) => Some(
+            "未対応のトレイトです (impl AsRef<str>, impl AsRef<[T]>, impl AsMut<[T]>, impl ToString, impl Display を指定できます。独自の変換は具体的な型に IntoDxArg を実装してください)",
+        ),
+        Type::TraitObject(_) => Some("dyn Trait は C に渡せません"),
+        Type::Infer(_) => Some("引数の型を指定してください"),
+        Type::Never(_) => Some("! は引数に指定できません"),
+        Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            Some("タプルは C に渡せません (#[repr(C)] の構造体を使用してください)")
+        }
+        Type::Paren(paren) => unsupported_type_message(&paren.elem),
+        Type::Reference(TypeReference {
+            elem, mutability, ..
+        }) => match elem.as_ref() {
+            Type::ImplTrait(_) | Type::Paren(_) => Some(
+                "この参照の impl Trait は未対応です (&impl AsRef<str>, &mut impl AsMut<[T]> 等を指定してください)",
+            ),
+            Type::TraitObject(_) => Some("dyn Trait は C に渡せません"),
+            Type::Reference(_) => Some("参照の参照は未対応です"),
+            Type::Path(TypePath { path, .. }) if path.is_ident("str") && mutability.is_some() => {
+                Some("&mut str は未対応です (&mut String を使用してください)")
             }
-        })
-        .collect()
+            Type::Path(TypePath { path, .. })
+                if path.segments.last().is_some_and(|s| s.ident == "Option") =>
+            {
+                Some("Option の参照は未対応です (Option<T> を指定してください)")
+            }
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
-pub fn to_capitalized_snake(s: &str) -> String {
-    s.split('_')
-        .filter(|part| !part.is_empty())
-        .map(|part| {
-            let mut c = part.chars();
-            match c.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().collect::<String>() + c.as_str(),
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("_")
+// Option<T> の T に指定できない型の場合はエラーメッセージを返す
+pub fn unsupported_option_message(inner_ty: &Type) -> Option<&'static str> {
+    if let Some(message) = unsupported_type_message(inner_ty) {
+        return Some(message);
+    }
+    let is_string = match inner_ty {
+        Type::Path(TypePath { path, .. }) => path.is_ident("String"),
+        Type::Reference(TypeReference { elem, .. }) => {
+            matches!(elem.as_ref(), Type::Path(TypePath { path, .. }) if path.is_ident("str") || path.is_ident("String"))
+        }
+        _ => false,
+    };
+    if is_string || is_vec_type(inner_ty) || is_option(inner_ty).is_some() {
+        return Some(
+            "Option<T> の T には数値、bool、ポインタ等の C にそのまま渡せる型を指定してください",
+        );
+    }
+    None
+}
+
+// 数値、ポインタ等の C にそのまま渡せる型かどうかを判定
+pub fn is_c_type(ty: &Type) -> bool {
+    const C_TYPES: &[&str] = &[
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "usize",
+        "f32",
+        "f64",
+        "c_char",
+        "c_schar",
+        "c_uchar",
+        "c_short",
+        "c_ushort",
+        "c_int",
+        "c_uint",
+        "c_long",
+        "c_ulong",
+        "c_longlong",
+        "c_ulonglong",
+        "c_float",
+        "c_double",
+        "c_void",
+    ];
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last().is_some_and(|segment| {
+            segment.arguments.is_empty() && C_TYPES.contains(&segment.ident.to_string().as_str())
+        }),
+        Type::Ptr(_) | Type::BareFn(_) => true,
+        Type::Tuple(tuple) => tuple.elems.is_empty(),
+        Type::Reference(TypeReference { elem, .. }) => is_c_type(elem),
+        Type::Paren(paren) => is_c_type(&paren.elem),
+        Type::Group(group) => is_c_type(&group.elem),
+        _ => false,
+    }
 }
@@ -0,0 +1,207 @@
+/*dxlib char code*/
+use crate::dxlib_constants::*;
+use encoding_rs::{EncoderResult, Encoding};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+// SetUseCharCodeFormatで設定された文字コード
+// 未設定の場合はDxLibの既定と同じShift-JISになる
+static CHAR_CODE_FORMAT: AtomicI32 = AtomicI32::new(DX_CHARCODEFORMAT_SHIFTJIS);
+
+// 文字列の引数の文字コードを記録する(SetUseCharCodeFormatの呼び出し成功後に呼ばれる)
+pub fn set_char_code_format(char_code_format: i32) {
+    CHAR_CODE_FORMAT.store(char_code_format, Ordering::Relaxed);
+}
+
+// 現在の文字列の引数の文字コード(DX_CHARCODEFORMAT_*)
+pub fn char_code_format() -> i32 {
+    CHAR_CODE_FORMAT.load(Ordering::Relaxed)
+}
+
+// encoding_rsで扱う文字コード
+// UTF-8,UTF-16,UTF-32,ASCIIは個別に処理するためNoneを返す
+fn encoding_of(char_code_format: i32) -> Option<&'static Encoding> {
+    match char_code_format {
+        DX_CHARCODEFORMAT_SHIFTJIS => Some(encoding_rs::SHIFT_JIS),
+        DX_CHARCODEFORMAT_GB2312 => Some(encoding_rs::GBK),
+        DX_CHARCODEFORMAT_UHC => Some(encoding_rs::EUC_KR),
+        DX_CHARCODEFORMAT_BIG5 => Some(encoding_rs::BIG5),
+        DX_CHARCODEFORMAT_WINDOWS_1252 => Some(encoding_rs::WINDOWS_1252),
+        DX_CHARCODEFORMAT_ISO_IEC_8859_15 => Some(encoding_rs::ISO_8859_15),
+        _ => None,
+    }
+}
+
+// 文字列を指定の文字コードのバイト列に変換する(終端のNUL文字を含む)
+// 変換できない文字は'?'に置き換えられる
+pub fn encode_string(s: &str, char_code_format: i32) -> Vec<u8> {
+    if let Some(encoding) = encoding_of(char_code_format) {
+        let mut encoder = encoding.new_encoder();
+        let mut bytes = Vec::with_capacity(s.len() * 2 + 1);
+        let mut src = s;
+        loop {
+            let (result, read) =
+                encoder.encode_from_utf8_to_vec_without_replacement(src, &mut bytes, true);
+            src = &src[read..];
+            match result {
+                EncoderResult::InputEmpty => break,
+                EncoderResult::OutputFull => bytes.reserve(src.len() * 2 + 16),
+                EncoderResult::Unmappable(_) => bytes.push(b'?'),
+            }
+        }
+        bytes.push(0);
+        return bytes;
+    }
+
+    let mut bytes = Vec::with_capacity(s.len() + 4);
+    match char_code_format {
+        DX_CHARCODEFORMAT_UTF16LE => {
+            s.encode_utf16().for_each(|c| bytes.extend(c.to_le_bytes()));
+            bytes.extend([0; 2]);
+        }
+        DX_CHARCODEFORMAT_UTF16BE => {
+            s.encode_utf16().for_each(|c| bytes.extend(c.to_be_bytes()));
+            bytes.extend([0; 2]);
+        }
+        DX_CHARCODEFORMAT_UTF32LE => {
+            s.chars()
+                .for_each(|c| bytes.extend((c as u32).to_le_bytes()));
+            bytes.extend([0; 4]);
+        }
+        DX_CHARCODEFORMAT_UTF32BE => {
+            s.chars()
+                .for_each(|c| bytes.extend((c as u32).to_be_bytes()));
+            bytes.extend([0; 4]);
+        }
+        DX_CHARCODEFORMAT_ASCII => {
+            bytes.extend(s.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' }));
+            bytes.push(0);
+        }
+        // UTF-8、もしくは未対応の文字コード
+        _ => {
+            bytes.extend(s.as_bytes());
+            bytes.push(0);
+        }
+    }
+    bytes
+}
+
+// 文字コードの1単位(NUL文字)のバイト数 (UTF-16は2、UTF-32は4、それ以外は1)
+pub fn code_unit_size(char_code_format: i32) -> usize {
+    match char_code_format {
+        DX_CHARCODEFORMAT_UTF16LE | DX_CHARCODEFORMAT_UTF16BE => 2,
+        DX_CHARCODEFORMAT_UTF32LE | DX_CHARCODEFORMAT_UTF32BE => 4,
+        _ => 1,
+    }
+}
+
+// 指定の文字コードの文字列バッファ(NUL終端)をStringに変換する
+// 不正なバイト列の場合はエラーを返す
+pub fn decode_string(buffer: &[u8], char_code_format: i32) -> anyhow::Result<String> {
+    // 文字コードごとの1文字の単位で終端のNUL文字を探す
+    let unit = match char_code_format {
+        DX_CHARCODEFORMAT_UTF16LE | DX_CHARCODEFORMAT_UTF16BE => 2,
+        DX_CHARCODEFORMAT_UTF32LE | DX_CHARCODEFORMAT_UTF32BE => 4,
+        _ => 1,
+    };
+    let len = buffer
+        .chunks(unit)
+        .position(|c| c.iter().all(|&b| b == 0))
+        .map_or(buffer.len() - buffer.len() % unit, |i| i * unit);
+    let bytes = &buffer[..len];
+
+    if let Some(encoding) = encoding_of(char_code_format) {
+        return encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|s| s.into_owned())
+            .ok_or_else(|| anyhow::anyhow!("invalid {} string", encoding.name()));
+    }
+
+    match char_code_format {
+        DX_CHARCODEFORMAT_UTF16LE | DX_CHARCODEFORMAT_UTF16BE => {
+            let units: Vec<u16> = bytes
+                .chunks(2)
+                .map(|c| match char_code_format {
+                    DX_CHARCODEFORMAT_UTF16LE => u16::from_le_bytes([c[0], c[1]]),
+                    _ => u16::from_be_bytes([c[0], c[1]]),
+                })
+                .collect();
+            Ok(String::from_utf16(&units)?)
+        }
+        DX_CHARCODEFORMAT_UTF32LE | DX_CHARCODEFORMAT_UTF32BE => bytes
+            .chunks(4)
+            .map(|c| {
+                let code = match char_code_format {
+                    DX_CHARCODEFORMAT_UTF32LE => u32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+                    _ => u32::from_be_bytes([c[0], c[1], c[2], c[3]]),
+                };
+                char::from_u32(code)
+                    .ok_or_else(|| anyhow::anyhow!("invalid UTF-32 code point {:#x}", code))
+            })
+            .collect(),
+        DX_CHARCODEFORMAT_ASCII if !bytes.is_ascii() => {
+            Err(anyhow::anyhow!("invalid ASCII string"))
+        }
+        _ => Ok(String::from_utf8(bytes.to_vec())?),
+    }
+}
+
+// W付きの関数用に文字列をUTF-16に変換する(終端のNUL文字を含む)
+pub fn encode_wide_string(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+// UTF-16の文字列バッファ(NUL終端)をStringに変換する
+pub fn decode_wide_string(buffer: &[u16]) -> anyhow::Result<String> {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Ok(String::from_utf16(&buffer[..len])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_string() {
+        assert_eq!(
+            encode_string("aあ", DX_CHARCODEFORMAT_UTF8),
+            b"a\xe3\x81\x82\0"
+        );
+        assert_eq!(
+            encode_string("aあ", DX_CHARCODEFORMAT_SHIFTJIS),
+            b"a\x82\xa0\0"
+        );
+        assert_eq!(
+            encode_string("aあ", DX_CHARCODEFORMAT_UTF16LE),
+            b"a\0\x42\x30\0\0"
+        );
+        assert_eq!(
+            encode_string("aあ", DX_CHARCODEFORMAT_UTF16BE),
+            b"\0a\x30\x42\0\0"
+        );
+        assert_eq!(encode_string("aあ", DX_CHARCODEFORMAT_ASCII), b"a?\0");
+        // Shift-JISで表せない文字は'?'になる
+        assert_eq!(encode_string("a😀", DX_CHARCODEFORMAT_SHIFTJIS), b"a?\0");
+        assert_eq!(encode_wide_string("aあ"), vec![0x61, 0x3042, 0]);
+    }
+
+    #[test]
+    fn test_decode_string() {
+        let formats = [
+            DX_CHARCODEFORMAT_UTF8,
+            DX_CHARCODEFORMAT_SHIFTJIS,
+            DX_CHARCODEFORMAT_UTF16LE,
+            DX_CHARCODEFORMAT_UTF16BE,
+            DX_CHARCODEFORMAT_UTF32LE,
+            DX_CHARCODEFORMAT_UTF32BE,
+        ];
+        for format in formats {
+            let mut buffer = encode_string("あいうえお abc", format);
+            // 終端以降のゴミは無視される
+            buffer.extend([0x41; 8]);
+            assert_eq!(decode_string(&buffer, format).unwrap(), "あいうえお abc");
+        }
+        assert!(decode_string(b"\xff\xfe\0", DX_CHARCODEFORMAT_UTF8).is_err());
+        assert!(decode_string(b"\x82\0", DX_CHARCODEFORMAT_SHIFTJIS).is_err());
+        assert_eq!(decode_wide_string(&[0x61, 0x3042, 0, 0x62]).unwrap(), "aあ");
+    }
+}
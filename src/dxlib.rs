@@ -1,21 +1,24 @@
 #![allow(non_snake_case)]
+// #[callback]の引数はOption<Box<dyn FnMut(..)>>の型をそのまま記述する
+#![allow(clippy::type_complexity)]
 
+use crate::charcode::*;
 use crate::dxlib_constants::*;
+use crate::dxlib_enums::*;
 use crate::dxlib_error::*;
 use crate::dxlib_types::*;
+#[cfg(feature = "record")]
+use crate::record::*;
 use crate::utils::*;
 
 use dxlib_rs_macro::dxlib_gen;
 
-static mut DEFAULT_RECT: RECT = RECT {
+static DEFAULT_RECT: RECT = RECT {
     left: -1,
     right: -1,
     top: -1,
     bottom: -1,
 };
-fn default_rect_ptr() -> *mut RECT {
-    unsafe { &raw mut DEFAULT_RECT }
-}
 // =======================================================
 // dxlib-rs版
 // dxlib_gen! {
@@ -35,65 +38,344 @@ fn default_rect_ptr() -> *mut RECT {
 //	    〇&mut impl AsMut<T>
 //	    〇impl ToString
 //	    ✕ impl Display
-//	尚、<A:AsRef<[u8]>>(buffer: A)等の書き方は許容されない
+//	・型パラメータ、where句 -> トレイト境界のimpl Traitとして扱われ、同じ規則で変換される
+//	    fn DrawString<S: AsRef<str>>(x: i32, y: i32, string: S, color: i32) -> i32,
+//	    fn LoadGraph<P>(file_name: P) -> i32 where P: AsRef<str> + std::fmt::Debug,
+//	  (トレイト境界のない型パラメータ、戻り値での型パラメータの使用は許容されない)
+//	・上記以外の型 -> utils::IntoDxArg(&mut TはDxArgMut)を実装していればその変換で渡す
+//	    impl IntoDxArg for Color { type Raw = u32; fn into_dx_arg(self) -> anyhow::Result<DxArg<u32>> { ... } }
+//	    fn SetBackgroundColor(color: Color) -> i32,
+//	  (Path,PathBufは文字列と同様に*const c_charとして渡される。dxlib_typesの構造体はdx_arg_repr_c!で&T -> *const T、&mut T -> *mut Tとして渡せる。
+//	   変換に失敗した場合はエラー、エラー時の引数は型名で表示される)
 //	== その他 マクロ仕様等 ==
 //	・指定戻り値 -> anyhow::Result<指定戻り値,DxLibError>へ変換する
 //	・#[default="0"] Option<T> -> None時の渡すデフォルト値を指定したデフォルト値にする
 //	・#[alias="dxlib_init"] fn DxLib_Init() -> i32, -> 生成時の関数名を指定したエイリアス名にする
 //	・#[not_result] fn DxLib_Init() -> i32, -> 生成時の関数戻り値をanyhow::Resultに変換しない
+//	・#[out] x: i32 -> 引数に*mut i32を渡し、呼び出し後の値を戻り値として返す
+//	    fn GetGraphSize(gr_handle: &GraphHandle, #[out] size_x: i32, #[out] size_y: i32) -> i32,
+//	    -> GetGraphSize(gr_handle) -> anyhow::Result<(i32, i32), DxLibError>
+//	    (#[out]が1つの場合はタプルではなくその値、error_conditionはCの戻り値に対して判定される)
+//	・#[out_string(capacity = N)] buffer: String -> (N+1)*4バイトのバッファを確保して*mut c_charを渡し、
+//	  書き込まれた文字列をStringとして戻り値に返す(不正な文字列の場合はDxLibError::InvalidString)
+//	  (どの文字コードでもN文字と終端のNUL文字が収まるように1文字4バイトで確保する。wide版はN+1要素のu16)
+//	・#[len_of(buffer)] size: i32 -> 引数から除かれ、#[out_string]の引数bufferの容量として文字数Nが自動的に渡される
+//	    fn FileRead_gets(#[out_string(capacity = 1024)] buffer: String, #[len_of(buffer)] num: i32, file_handle: i32) -> i32,
+//	    -> FileRead_gets(file_handle) -> anyhow::Result<String, DxLibError>
+//	  文字列の引数(impl AsRef<str>等)を指定した場合は、変換後の文字コードでの長さ(終端のNUL文字を含まない単位の数)が渡される
+//	    fn GetDrawStringSize(.., string: impl AsRef<str>, #[len_of(string)] str_len: i32, ..) -> i32,
+//	・生ポインタ(*mut T,*const T,Option<*mut T>等)の引数がある関数 -> 呼び出し側がポインタの有効性を保証するためunsafe fnとして生成する
+//	  (#[callback]のクロージャの引数は対象外。ドキュメントに# Safetyが追加される)
+//	・#[len_of(buffer)] size: i32 (bufferが生ポインタの場合) -> unsafe fnの引数には残し、
+//	  bufferをスライス(*mut c_voidは&mut [u8]、*const Tは&[T])で受け取り、sizeにスライスの長さを渡す安全な関数(関数名_slice)も生成する
+//	    fn FileRead_read(buffer: *mut c_void, #[len_of(buffer)] read_size: i32, file_handle: &FileHandle) -> i32,
+//	    -> unsafe FileRead_read(buffer, read_size, file_handle)、FileRead_read_slice(buffer: &mut [u8], file_handle)
+//	・#[char_code_format] -> 呼び出し成功後に、その引数の値を文字列の引数の文字コードとして記録する
+//	  (SetUseCharCodeFormatに指定されている。未設定の場合はDxLibの既定と同じShift-JIS)
+//	  文字列の引数はcharcode::encode_stringで記録された文字コード(DX_CHARCODEFORMAT_*)に変換されて渡され、
+//	  #[out_string]も同じ文字コードとしてデコードされる
+//	・&mut String -> 書き込み可能なバッファを確保して*mut c_charを渡し、呼び出し成功後に書き込まれた文字列で元のStringを更新する
+//	  (#[capacity = N]とString::capacity()の大きい方の文字数分確保する、#[len_of]でその容量を渡すことも可能)
+//	    fn Edit(#[capacity = 256] buffer: &mut String, #[len_of(buffer)] size: i32) -> i32,
+//	・#[wide] fn DrawStringW(...) -> 文字列の引数を文字コードの設定に関係なくUTF-16(*const u16)で渡す
+//	  (W付きの関数用、#[out_string]もUTF-16としてデコードされる)
+//	・文字列の引数の途中にNUL文字が含まれている場合はDxLibError::InteriorNul(関数名,引数名,位置)を返す
+//	・#[lossy] fn DrawString(...) -> 文字列の途中のNUL文字をエラーにせず、そこまでで切り詰めて渡す
+//	  (表示のみの関数用、引数の前に付けた場合はその引数のみ)
+//	・関数がエラーを返した場合はDxLibError::Call { function, code, args }を返す
+//	  (codeは関数の戻り値、argsは引数名とDebugで表示した値、文字列の引数は変換前の文字列)
+//	・#[call_error = "DxLibError::LoadFailed"] -> エラー時にCallの代わりに指定した列挙子を返す
+//	  (同じfunction,code,argsのフィールドを持つ必要がある。DxLibError::LoadFailed,InvalidHandle等)
+//	・#[handle(type = GraphHandle, drop = "DeleteGraph")] fn LoadGraph(...) -> i32,
+//	  -> 戻り値をGraphHandle型で返す(Drop時にDeleteGraph(handle, 残りの引数はDefault::default())で解放される)
+//	  into_raw()で解放せずにi32に戻す、leak()で解放せずに破棄する、from_raw(i32)で作成することが可能
+//	  引数を&GraphHandleとした場合はi32として渡されるため、別のハンドル型(&SoundHandle等)は渡せない
+//	  (解放関数は同じdxlib_gen!内で宣言されている必要がある)
+//	・bool -> DxLibのTRUE/FALSE(i32)として渡す、戻り値、#[out]の場合は0以外をtrueとして受け取る
+//	    fn CheckHitKey(key_code: i32) -> bool, -> CheckHitKey(key_code) -> anyhow::Result<bool, DxLibError>
+//	・#[dx_enum] play_type: PlayType -> dx_enum!で定義した列挙型(dxlib_enums)を列挙子の値(i32)として渡す
+//	  関数シグネチャの前に付けた場合は戻り値、#[out]と併用した場合はその値をi32から変換し、
+//	  対応する列挙子がない場合はDxLibError::InvalidEnumValue(関数名,型名,値)を返す
+//	・/// コメント(#[doc = "..."]) -> 生成されるラップ関数のドキュメントになる
+//	  (エラー条件、Optionの引数のNone時の値、Cの関数名の注記が自動的に追加される。//のコメントは反映されない)
+//	・#[doc_en = "..."] -> 英語の説明を///の説明の後に追加する(複数行の場合は繰り返し指定する)
+//	・#[role = "init"],#[role = "finalize"] -> エラー時にDxLibError::InitializeError,FinalizeErrorを返す
+//	  (DxLib_Init,DxLib_Endは指定しなくても自動的に設定される)
+//	・未対応の属性(綴りの誤り等)、C に渡せない引数の型、パターン付き引数、不正な#[default],#[error_condition]の式は
+//	  panicせずに該当箇所を指したコンパイルエラーになる(tests/ui)
+//	・#[format(macro = "draw_format_string")] fn DrawFormatString(x: i32, y: i32, color: i32, format_string: impl AsRef<str>, ...) -> i32,
+//	  -> 可変長引数の関数を、書式化済みの文字列を受け取る関数と、format!の書式で呼び出すマクロとして生成する
+//	    draw_format_string!(x, y, color, "score: {}", score)? -> DrawFormatString(x, y, color, format!("score: {}", score))?
+//	  Cには書式文字列として"%s"、可変長引数として文字列を渡すため、文字列内の%は解釈されない
+//	  (...の前の引数は文字列である必要がある。マクロは#[macro_export]され、#![macro_module = "dxlib"]の場合は$crate::dxlib::関数名を呼び出す)
+//	・#[callback] callback: Option<Box<dyn FnMut(i32) -> i32 + Send>> -> クロージャを受け取り、
+//	  クロージャを呼び出すextern関数(トランポリン)の関数ポインタを渡す(Noneの場合はNULLを渡して登録を解除する)
+//	    fn SetHookWinProc(#[callback] win_proc: Option<Box<dyn FnMut(*mut c_void, u32, usize, isize) -> isize + Send>>) -> i32,
+//	  クロージャは関数ごとに1つ保持され、次に登録するかNoneを渡すまで破棄されない
+//	  (FnMutの引数、戻り値はCのコールバックと同じ型にする。戻り値はDefaultを実装している必要がある)
+//	  クロージャ内のpanicはCへ巻き戻さずに既定値を返し、utils::take_callback_panic()で取り出せる
+//	  (クロージャ内から同じコールバックが呼ばれた場合もクロージャを呼ばずに既定値を返す)
+//	== 初期化、スレッドの検査 ==
+//	・#![context = "DxContext"] -> 初期化の関数(#[role = "init"])で作成する!Sendな型DxContextを生成する
+//	    let mut dx = DxContext::DxLib_Init()?; -> 初期化に成功した場合はDxContextを返す
+//	      (DxContextは同時に1つのみ。初期化済みの場合はDxLibError::Context("DxLib_Init",ContextError::AlreadyInitialized))
+//	    dx.DrawString(0, 0, "abc", color)?; -> #[context]の関数はDxContextのメソッドとしても呼び出せる
+//	    dx.DxLib_End()?; -> 終了の関数はDxContextを消費する(失敗した場合も初期化前の状態に戻る)
+//	    DxContext::check() -> 初期化済みで、初期化したスレッドからの呼び出しかを確認する(DxContextは作成しない)
+//	  DxContextはSend,Syncを実装しないため、初期化したスレッド以外へ渡すことはできない
+//	・#[context] fn DrawString(...) -> DxContextの&selfのメソッドを生成する(描画、入力、サウンド等の関数に指定する)
+//	・#[context(mut)] fn SetDrawScreen(...) -> &mut selfのメソッドを生成する(描画先等の状態を変更する関数に指定する)
+//	  自由関数もそのまま呼び出せるが、#![context_check]の条件(既定はdebug_assertions)を満たす場合は呼び出し時に検査し、
+//	  初期化前、終了後はDxLibError::Context(関数名,ContextError::NotInitialized) ("called before init")、
+//	  初期化したスレッド以外からはDxLibError::Context(関数名,ContextError::OffMainThread) ("called off main thread")を返す
+//	  (#![api_trait]で差し替えた呼び出し先、#![record_feature]の記録時は検査しない)
+//	・#![context_check = "debug_assertions"] -> 検査を行うcfgの条件(既定はdebug_assertions)
+//	== 呼び出し規約、リンク方法 ==
+//	ライブラリ名の前に内部属性を指定して変更できる(関数シグネチャの前に付けた場合はその関数のみ)
+//	・#![abi = "system"] -> externの呼び出し規約("system","C","stdcall"、既定は"system")
+//	・#![link_kind = "import"] -> リンク方法
+//	    "import" : インポートライブラリ(.lib)、共有ライブラリ(.so)をリンクする(既定)
+//	    "static" : 静的ライブラリとしてリンクする
+//	    "raw-dylib" : インポートライブラリなしでDLLを直接参照する(Windowsのみ)
+//	    "none" : #[link]を付けない(build.rs等で別途リンクする)
+//	== 動的ロード ==
+//	・#![dynamic_loader = "DxLib"] -> 関数をリンクせず、DxLib::load(path)で実行時に読み込む
+//	    DxLib::load(path) : 見つからない関数がある場合はDxLibError::MissingSymbolsを返す
+//	    DxLib::load_partial(path) : 見つからない関数があっても読み込み、その関数の呼び出し時に
+//	                                DxLibError::SymbolNotAvailableを返す
+//	・#![dynamic_loader_feature = "dynamic"] -> 指定フィーチャーが有効な場合のみ動的ロードにする
+//	Windows以外ではbuild.rsがDxLib/stub/dxlib_stub.cをlibDxLib_x64.aとしてビルドするため、
+//	同じシグネチャのままLinux等でもビルド、テストが可能
+//	== 呼び出し先の差し替え ==
+//	・#![api_trait = "DxLibApi"] -> 全ての関数をメソッドに持つトレイトDxLibApiと、下記を生成する
+//	    DxLibApiReal : 実際のライブラリを呼び出す実装(トレイトの既定の実装もこれを呼び出す)
+//	    DxLibApiBackend::scoped(api, f) : fの実行中のみ、このスレッドの関数の呼び出し先をapiにする
+//	    DxLibApiBackend::set_thread(api),set_global(api) : スレッドごと、全スレッド共通の呼び出し先を設定する
+//	  メソッドの引数のimpl AsRef<T>は&T、impl AsMut<T>は&mut T、impl ToString,impl Displayは&str、impl Into<T>はTになる
+//	  (差し替えたメソッド内で同じ関数を呼び出すと再帰するため、実際の関数はDxLibApiRealから呼び出す)
+//	・#![record_feature = "record"] -> 指定フィーチャーが有効な場合、Cの関数を呼び出さずにrecord::Recorderに記録する
+//	  (リンク、動的ロードより優先される。引数はCに渡す値で記録され、戻り値はRecorder::onで設定する、未設定の場合は0)
+//	・#![trace_feature = "trace"] -> 指定フィーチャーが有効な場合、呼び出しごとに関数名、引数、戻り値、経過時間を
+//	  tracing::debug!のイベントとして出力する(フィーチャーが無効な場合は何も生成されない)
+//	    #[trace(skip)] fn DrawGraph(...) -> 毎フレーム大量に呼ばれる関数等は出力しない
+//	== cffi_gen! ==
+//	dxlib_gen!は汎用のcffi_gen!に下記の内部属性を設定したプリセットになっている
+//	    #![library_name = "DxLib_x64"]
+//	    #![func_name_top_prefix = "dx_"]
+//	    #![as_result_error_type = "DxLibError"]
+//	    #![init_error = "DxLibError::InitializeError"]
+//	    #![finalize_error = "DxLibError::FinalizeError"]
+//	他のCライブラリをラップする場合はcffi_gen!を直接使用する
 // =======================================================
 dxlib_gen! {
+    // dynamicフィーチャー有効時は、DxLib::load()で実行時に読み込む
+    #![dynamic_loader = "DxLib"]
+    #![dynamic_loader_feature = "dynamic"]
+    // DxLibApiを実装した型に呼び出し先を差し替えられるようにする(DxLibなしでのテスト用)
+    #![api_trait = "DxLibApi"]
+    // recordフィーチャー有効時は、DxLibを呼び出さずにrecord::Recorderに記録する
+    #![record_feature = "record"]
+    // traceフィーチャー有効時は、呼び出しごとにtracingのイベントを出力する(#[trace(skip)]の関数以外)
+    #![trace_feature = "trace"]
+    // DxLib_InitでDxContextを作成し、#[context]の関数をDxContextのメソッドとしても呼び出せるようにする
+    #![context = "DxContext"]
+    // #[format]で生成するマクロ(dx_printf!等)からは$crate::dxlib::*の関数を呼び出す
+    #![macro_module = "dxlib"]
     // ライブラリ名
     "DxLib_x64",
-    // ライブラリの初期化
+    /// ライブラリの初期化
     //#[alias = "dxlib_init"]
     //#[not_result]
     fn DxLib_Init() -> i32,
-    // ライブラリ使用の終了関数
+    /// ライブラリ使用の終了関数
     fn DxLib_End() -> i32,
-    // ウインドウズのメッセージを処理する
+    /// ウインドウズのメッセージを処理する
+    #[context]
     fn ProcessMessage() -> i32,
-    // フリップ関数、画面の裏ページ(普段は表示されていない)の内容を表ページ(普段表示されている)に反映する
+    /// フリップ関数、画面の裏ページ(普段は表示されていない)の内容を表ページ(普段表示されている)に反映する
+    #[context]
     fn ScreenFlip() -> i32,
-    // 描画先グラフィック領域の指定
+    /// 描画先グラフィック領域の指定
+    #[context(mut)]
     fn SetDrawScreen(#[default = "DX_SCREEN_BACK"] draw_screen: Option<i32>) -> i32,
-    fn ClearDrawScreen(#[default = "default_rect_ptr()"] clear_rect: Option<*mut RECT>) -> i32,
-    // ウインドウモード・フルスクリーンモードの変更を行う
-    fn ChangeWindowMode(#[default = "1"] flag:Option<i32>) -> i32,
-    // ウインドウのタイトルを変更する
-    fn SetMainWindowText(window_text: impl AsRef<str>) -> i32,
-    // キーの入力待ち
+    /// 画面に描かれたものを消去する
+    #[context]
+    fn ClearDrawScreen(#[default = "&DEFAULT_RECT"] clear_rect: Option<&RECT>) -> i32,
+    /// ウインドウモード・フルスクリーンモードの変更を行う
+    fn ChangeWindowMode(#[default = "true"] flag: Option<bool>) -> i32,
+    /// 画面モードを設定する
+    #[error_condition = "result != DX_CHANGESCREEN_OK"]
+    fn SetGraphMode(
+        screen_size_x: i32,
+        screen_size_y: i32,
+        color_bit_depth: i32,
+        #[default = "60"] refresh_rate: Option<i32>,
+    ) -> i32,
+    /// ログファイルを出力するかどうかを設定する
+    fn SetOutApplicationLogValidFlag(flag: bool) -> i32,
+    /// ログファイルを保存するディレクトリを設定する
+    fn SetApplicationLogSaveDirectory(dir_path: impl AsRef<str>) -> i32,
+    /// ScreenFlipで垂直同期を待つかどうかを設定する
+    fn SetWaitVSyncFlag(flag: bool) -> i32,
+    /// ウインドウのタイトルを変更する
+    fn SetMainWindowText<S>(window_text: S) -> i32
+    where
+        S: AsRef<str>,
+    /// キーの入力待ち
     #[error_condition = "result == i32::MAX"]
+    #[context]
     fn WaitKey() -> i32,
-    // キーボードによる文字列の入力
+    /// キーボードによる文字列の入力
+    #[context]
     fn KeyInputString(
         x: i32,
         y: i32,
-        char_max_length: i32,
-        str_buffer:&mut impl AsMut<[std::os::raw::c_char]>,
-        cancel_valid_flag: i32,
+        #[len_of(str_buffer)] char_max_length: i32,
+        #[out_string(capacity = 256)] str_buffer: String,
+        cancel_valid_flag: bool,
     ) -> i32,
-    // 文字列の引数の文字コードを設定する
+    /// 文字列の引数の文字コードを設定する
     fn SetUseCharCodeFormat(
-        char_code_format: i32,
+        #[char_code_format] char_code_format: i32,
     ) -> i32,
-    // 色コードを取得する
+    /// 色コードを取得する
     #[error_condition = "result == i32::MAX"]
+    #[context]
     fn GetColor(red: i32, green: i32, blue: i32) -> i32,
-    // 文字列を描画する
-    fn DrawString(x: i32, y: i32, string: impl AsRef<str>, color: i32) -> i32,
+    /// 文字列を描画する
+    #[lossy]
+    #[context]
+    fn DrawString<S: AsRef<str>>(x: i32, y: i32, string: S, color: i32) -> i32,
+    /// 書式指定した文字列を描画する (draw_format_string!(x, y, color, "score: {}", score)で呼び出す)
+    #[format(macro = "draw_format_string")]
+    #[lossy]
+    #[context]
+    fn DrawFormatString(x: i32, y: i32, color: i32, format_string: impl AsRef<str>, ...) -> i32,
+    /// フォントハンドルを使用して書式指定した文字列を描画する
+    #[format(macro = "draw_format_string_to_handle")]
+    #[lossy]
+    #[context]
+    fn DrawFormatStringToHandle(
+        x: i32,
+        y: i32,
+        color: i32,
+        font_handle: i32,
+        format_string: impl AsRef<str>,
+        ...
+    ) -> i32,
+    /// 書式指定した文字列を画面にデバッグ表示する (dx_printf!("fps: {}", fps)で呼び出す)
+    #[format(macro = "dx_printf")]
+    #[lossy]
+    #[context]
+    fn printfDx(format_string: impl AsRef<str>, ...) -> i32,
+    /// 画像ファイルを読み込む (戻り値のGraphHandleはDrop時にDeleteGraphで解放される)
+    #[call_error = "DxLibError::LoadFailed"]
+    #[handle(type = GraphHandle, drop = "DeleteGraph")]
+    #[context]
     fn LoadGraph(file_name: impl AsRef<str>) -> i32,
-    fn DrawGraph(x: i32, y: i32, gr_handle: i32, trans_flag: i32) -> i32,
-    fn PlaySoundMem(sound_handle: i32, play_type: i32, top_position_flag: i32) -> i32,
+    /// グラフィックを削除する
+    #[call_error = "DxLibError::InvalidHandle"]
+    #[context]
+    fn DeleteGraph(gr_handle: i32, #[default = "false"] log_out_flag: Option<bool>) -> i32,
+    /// 読み込んでいるグラフィックの数を取得する
+    #[context]
+    fn GetGraphNum() -> i32,
+    /// 描画ブレンドモードを設定する
+    #[context(mut)]
+    fn SetDrawBlendMode(#[dx_enum] blend_mode: BlendMode, blend_param: i32) -> i32,
+    /// 描画ブレンドモードを取得する
+    #[context]
+    fn GetDrawBlendMode(#[out] #[dx_enum] blend_mode: BlendMode, #[out] blend_param: i32) -> i32,
+    /// メモリに読みこんだグラフィックの描画
+    #[call_error = "DxLibError::InvalidHandle"]
+    #[trace(skip)]
+    #[context]
+    fn DrawGraph(x: i32, y: i32, gr_handle: &GraphHandle, trans_flag: bool) -> i32,
+    /// メモリに読み込んだ音声データを再生する
+    #[call_error = "DxLibError::InvalidHandle"]
+    #[context]
+    fn PlaySoundMem(
+        sound_handle: &SoundHandle,
+        #[dx_enum] play_type: PlayType,
+        top_position_flag: bool,
+    ) -> i32,
+    /// 音声ファイルを読み込む (戻り値のSoundHandleはDrop時にDeleteSoundMemで解放される)
+    #[call_error = "DxLibError::LoadFailed"]
+    #[handle(type = SoundHandle, drop = "DeleteSoundMem")]
+    #[context]
     fn LoadSoundMem(file_name: impl AsRef<str>) -> i32,
+    /// サウンドハンドルを削除する
+    #[call_error = "DxLibError::InvalidHandle"]
+    #[context]
+    fn DeleteSoundMem(sound_handle: i32, #[default = "false"] log_out_flag: Option<bool>) -> i32,
+    /// キーボードの入力状態を取得する
     #[error_condition = "result == i32::MAX"]
-    fn CheckHitKey(key_code: i32) -> i32,
-    fn FileRead_open(file_path: impl AsRef<str>,r#async: i32) -> i32,
+    #[context]
+    fn CheckHitKey(key_code: i32) -> bool,
+    /// ファイルを開く (戻り値のFileHandleはDrop時にFileRead_closeで閉じられる)
+    #[call_error = "DxLibError::LoadFailed"]
+    #[handle(type = FileHandle, drop = "FileRead_close")]
+    fn FileRead_open(file_path: impl AsRef<str>,r#async: bool) -> i32,
+    /// ファイルのサイズを取得する
     fn FileRead_size(file_path: impl AsRef<str>) -> std::os::raw::c_long,
+    /// ファイルを閉じる
+    #[call_error = "DxLibError::InvalidHandle"]
     fn FileRead_close(file_handle: i32) -> i32,
-    fn FileRead_tell(file_handle: i32) -> std::os::raw::c_long,
-    fn FileRead_seek(file_handle: i32,offset: std::os::raw::c_long,origin: i32) -> i32,
-    fn FileRead_read(buffer: *mut std::os::raw::c_void,read_size: i32,file_handle: i32) -> i32,
-    fn FileRead_gets(buffer: &mut Vec<std::os::raw::c_char>,num: i32,file_handle: i32) -> i32,
-    fn SetUseASyncLoadFlag(flag: i32) -> i32,
+    /// ファイルポインタの読み込み位置を取得する
+    #[call_error = "DxLibError::InvalidHandle"]
+    fn FileRead_tell(file_handle: &FileHandle) -> std::os::raw::c_long,
+    /// ファイルポインタの読み込み位置を変更する
+    fn FileRead_seek(file_handle: &FileHandle,offset: std::os::raw::c_long,origin: i32) -> i32,
+    /// ファイルからデータを読み込む
+    fn FileRead_read(
+        buffer: *mut std::os::raw::c_void,
+        #[len_of(buffer)] read_size: i32,
+        file_handle: &FileHandle,
+    ) -> i32,
+    /// ファイルから1行読み込む
+    fn FileRead_gets(
+        #[out_string(capacity = 1024)] buffer: String,
+        #[len_of(buffer)] num: i32,
+        file_handle: &FileHandle,
+    ) -> i32,
+    /// 非同期読み込みを行うかどうかを設定する
+    fn SetUseASyncLoadFlag(flag: bool) -> i32,
+    /// メッセージをフックするウインドウプロシージャを登録する (Noneで解除)
+    fn SetHookWinProc(
+        #[callback] win_proc: Option<Box<dyn FnMut(*mut std::os::raw::c_void, u32, usize, isize) -> isize + Send>>,
+    ) -> i32,
+    /// グラフィックスデバイスの復元時にグラフィックを再読み込みする関数を登録する (Noneで解除)
+    fn SetRestoreGraphCallback(#[callback] callback: Option<Box<dyn FnMut() + Send>>) -> i32,
+    /// グラフィックスデバイスのロスト発生時に呼ばれる関数を登録する (Noneで解除)
+    fn SetGraphicsDeviceLostCallbackFunction(
+        #[callback] callback: Option<Box<dyn FnMut(*mut std::os::raw::c_void) + Send>>,
+        #[default = "std::ptr::null_mut()"] callback_data: Option<*mut std::os::raw::c_void>,
+    ) -> i32,
+    /// マウスカーソルの位置を取得する
+    #[context]
+    fn GetMousePoint(#[out] x_buf: i32, #[out] y_buf: i32) -> i32,
+    /// グラフィックのサイズを得る
+    #[call_error = "DxLibError::InvalidHandle"]
+    #[context]
+    fn GetGraphSize(gr_handle: &GraphHandle, #[out] size_x: i32, #[out] size_y: i32) -> i32,
+    /// 現在の画面の大きさとカラービット数を得る
+    #[context]
+    fn GetScreenState(
+        #[out] size_x: i32,
+        #[out] size_y: i32,
+        #[out] color_bit_depth: i32,
+    ) -> i32,
+    /// 文字列を描画した際のサイズと行数を取得する
+    #[context]
+    fn GetDrawStringSize(
+        #[out] size_x: i32,
+        #[out] size_y: i32,
+        #[out] line_count: i32,
+        string: impl AsRef<str>,
+        #[len_of(string)] str_len: i32,
+        #[default = "false"] vertical_flag: Option<bool>,
+    ) -> i32,
+    /// ジョイパッドのアナログ的なレバー入力情報を得る
+    #[context]
+    fn GetJoypadAnalogInput(#[out] x_buf: i32, #[out] y_buf: i32, input_type: i32) -> i32,
 }
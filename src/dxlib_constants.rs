@@ -215,7 +215,7 @@ pub const DX_MV1_MESHCATEGORY_OUTLINE_ORIG_SHADER: i32 = 2;
 pub const DX_MV1_MESHCATEGORY_NUM: i32 = 3;
 pub const MV1_SAVETYPE_MESH: i32 = 1;
 pub const MV1_SAVETYPE_ANIM: i32 = 2;
-pub const MV1_SAVETYPE_NORMAL: i32 = (MV1_SAVETYPE_MESH | MV1_SAVETYPE_ANIM);
+pub const MV1_SAVETYPE_NORMAL: i32 = MV1_SAVETYPE_MESH | MV1_SAVETYPE_ANIM;
 pub const MV1_ANIMKEY_DATATYPE_ROTATE: i32 = 0;
 pub const MV1_ANIMKEY_DATATYPE_ROTATE_X: i32 = 1;
 pub const MV1_ANIMKEY_DATATYPE_ROTATE_Y: i32 = 2;
@@ -258,8 +258,8 @@ pub const DX_IMAGESAVETYPE_DDS: i32 = 3;
 pub const DX_PLAYTYPE_LOOPBIT: i32 = 2;
 pub const DX_PLAYTYPE_BACKBIT: i32 = 1;
 pub const DX_PLAYTYPE_NORMAL: i32 = 0;
-pub const DX_PLAYTYPE_BACK: i32 = (DX_PLAYTYPE_BACKBIT);
-pub const DX_PLAYTYPE_LOOP: i32 = (DX_PLAYTYPE_LOOPBIT | DX_PLAYTYPE_BACKBIT);
+pub const DX_PLAYTYPE_BACK: i32 = DX_PLAYTYPE_BACKBIT;
+pub const DX_PLAYTYPE_LOOP: i32 = DX_PLAYTYPE_LOOPBIT | DX_PLAYTYPE_BACKBIT;
 pub const DX_MOVIEPLAYTYPE_BCANCEL: i32 = 0;
 pub const DX_MOVIEPLAYTYPE_NORMAL: i32 = 1;
 pub const DX_SOUNDTYPE_NORMAL: i32 = 0;
@@ -320,8 +320,8 @@ pub const DX_CMP_GREATER: i32 = 5;
 pub const DX_CMP_NOTEQUAL: i32 = 6;
 pub const DX_CMP_GREATEREQUAL: i32 = 7;
 pub const DX_CMP_ALWAYS: i32 = 8;
-pub const DX_ZCMP_DEFAULT: i32 = (DX_CMP_LESSEQUAL);
-pub const DX_ZCMP_REVERSE: i32 = (DX_CMP_GREATEREQUAL);
+pub const DX_ZCMP_DEFAULT: i32 = DX_CMP_LESSEQUAL;
+pub const DX_ZCMP_REVERSE: i32 = DX_CMP_GREATEREQUAL;
 pub const DX_SHADEMODE_FLAT: i32 = 1;
 pub const DX_SHADEMODE_GOURAUD: i32 = 2;
 pub const DX_FOGMODE_NONE: i32 = 0;
@@ -0,0 +1,24 @@
+/*dxlib enums*/
+// モードを指定する引数等で使用する列挙型 (dxlib_gen! の #[dx_enum])
+use crate::dx_enum;
+use crate::dxlib_constants::*;
+
+dx_enum! {
+    // PlaySoundMem等の再生形式
+    pub enum PlayType {
+        Normal = DX_PLAYTYPE_NORMAL,
+        Back = DX_PLAYTYPE_BACK,
+        Loop = DX_PLAYTYPE_LOOP,
+    }
+}
+
+dx_enum! {
+    // SetDrawBlendMode等の描画ブレンドモード
+    pub enum BlendMode {
+        NoBlend = DX_BLENDMODE_NOBLEND,
+        Alpha = DX_BLENDMODE_ALPHA,
+        Add = DX_BLENDMODE_ADD,
+        Sub = DX_BLENDMODE_SUB,
+        Mul = DX_BLENDMODE_MUL,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::utils::ContextError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -6,6 +7,78 @@ pub enum DxLibError {
     InitializeError,
     #[error("Failed to DxLib_End()")]
     FinalizeError,
+    #[error("Failed to load {0}: {1}")]
+    LoadError(String, anyhow::Error),
+    #[error("Missing symbols: {}", .0.join(", "))]
+    MissingSymbols(Vec<&'static str>),
+    #[error("Symbol {0} is not available")]
+    SymbolNotAvailable(&'static str),
+    #[error("Invalid string data in {0}({1}): {2}")]
+    InvalidString(&'static str, &'static str, anyhow::Error),
+    #[error("Interior NUL byte at position {2} in {0}({1})")]
+    InteriorNul(&'static str, &'static str, usize),
+    #[error("Invalid value {2} for {1} in {0}")]
+    InvalidEnumValue(&'static str, &'static str, i32),
+    // 初期化前、終了後、初期化したスレッド以外から呼び出された (#[context]の関数、デバッグビルドのみ検査する)
+    // もしくは初期化済みの状態でDxContext::DxLib_Initを呼び出した
+    #[error("{0}() {1}")]
+    Context(&'static str, ContextError),
+    // DxLibBuilderの設定が不正 (設定名,理由)
+    #[error("Invalid {0}: {1}")]
+    InvalidSetting(&'static str, String),
+    // DxLibBuilder::buildの呼び出し時に、既にDxLib_Initが呼び出されている
+    #[error("DxLib is already initialized")]
+    AlreadyInitialized,
+    // DxLibの関数がエラーを返した (codeは関数の戻り値、argsは引数名とDebugで表示した値)
+    #[error("{function}({}) failed with code {code}", format_call_args(.args))]
+    Call {
+        function: &'static str,
+        code: i64,
+        args: Vec<(&'static str, String)>,
+    },
+    // ファイル等の読み込みに失敗した (LoadGraph,LoadSoundMem,FileRead_open等)
+    #[error("{function}({}) failed to load resource (code {code})", format_call_args(.args))]
+    LoadFailed {
+        function: &'static str,
+        code: i64,
+        args: Vec<(&'static str, String)>,
+    },
+    // 無効なハンドルが渡された (DrawGraph,PlaySoundMem,FileRead_close等)
+    #[error("{function}({}) failed with invalid handle (code {code})", format_call_args(.args))]
+    InvalidHandle {
+        function: &'static str,
+        code: i64,
+        args: Vec<(&'static str, String)>,
+    },
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
+
+impl DxLibError {
+    // エラーになった関数名 (Call,LoadFailed,InvalidHandleの場合)
+    pub fn function(&self) -> Option<&'static str> {
+        match self {
+            Self::Call { function, .. }
+            | Self::LoadFailed { function, .. }
+            | Self::InvalidHandle { function, .. } => Some(function),
+            _ => None,
+        }
+    }
+
+    // エラーになった関数の戻り値 (Call,LoadFailed,InvalidHandleの場合)
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Self::Call { code, .. }
+            | Self::LoadFailed { code, .. }
+            | Self::InvalidHandle { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn format_call_args(args: &[(&'static str, String)]) -> String {
+    args.iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,7 +1,10 @@
 /*dxlib struct types*/
+#![allow(non_snake_case)]
+use crate::dx_arg_repr_c;
 use std::ops::*;
 use std::os::raw::*;
 #[repr(C)]
+#[derive(Debug, Clone, Copy)]
 pub struct RECT {
     pub left: CInt,
     pub top: CInt,
@@ -17,11 +20,11 @@ pub struct VECTOR {
 impl Add for VECTOR {
     type Output = VECTOR;
     fn add(self, other: VECTOR) -> VECTOR {
-        return VECTOR {
+        VECTOR {
             x: self.x + other.x,
             y: self.y + other.y,
             z: self.z + other.z,
-        };
+        }
     }
 }
 impl Clone for VECTOR {
@@ -151,3 +154,17 @@ pub type CVoid = c_void;
 pub type CLongLong = i64;
 pub type CDouble = f64;
 pub type CChar = c_char;
+// dxlib_gen!の引数に値、参照(*const T)、可変参照(*mut T)として指定できるようにする
+dx_arg_repr_c!(
+    RECT,
+    VECTOR,
+    COLOR_U8,
+    COLOR_F,
+    MATERIALPARAM,
+    VERTEX3D,
+    HITRESULT_LINE,
+    DATEDATA,
+    XAUDIO2FX_REVERB_PARAMETERS,
+    IPDATA,
+    MATRIX,
+);
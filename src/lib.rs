@@ -1,74 +1,69 @@
+pub mod charcode;
 pub mod dxlib;
 pub mod dxlib_constants;
+pub mod dxlib_enums;
 pub mod dxlib_error;
 pub mod dxlib_types;
+// dxlib-rs-genでDxLib.hの抜粋から生成したモジュール (生成結果がdxlib_gen!でコンパイルできることを確認する)
+#[cfg(test)]
+#[path = "../dxlib-rs-gen/tests/fixtures/dxlib_excerpt.rs"]
+mod dxlib_excerpt;
+#[cfg(feature = "record")]
+pub mod record;
+pub mod session;
+#[doc(hidden)]
+pub mod test_support;
 pub mod utils;
-pub use dxlib_rs_macro::dxlib_gen;
+pub use dxlib_rs_macro::{cffi_gen, dxlib_gen};
+// #[macro_export]したマクロから$crate::anyhowとして参照する(利用側のクレートがanyhowに依存していなくても使えるようにする)
+#[doc(hidden)]
+pub use anyhow;
 
+#[cfg(test)]
 mod tests {
     use crate::dxlib::*;
+    #[cfg(not(feature = "record"))]
     use crate::dxlib_constants::*;
+    #[cfg(not(feature = "record"))]
+    use crate::dxlib_enums::*;
     use crate::dxlib_error::*;
-    use crate::dxlib_types::*;
+    use crate::test_support::*;
     use anyhow::Result as R;
-    use std::f64::consts::PI;
-    use std::ffi::CStr;
-    use std::os::raw::c_char;
+
+    // recordフィーチャー有効時はDxLibを呼び出さないため、スタブの値を確認するテストは実行しない
+    // (test_dxlib_1はCheckHitKeyが常にfalseとなり終了しない)
+    #[cfg(not(feature = "record"))]
     #[test]
     fn test_dxlib_1() -> R<(), DxLibError> {
+        let _lock = lock_dxlib();
         SetUseCharCodeFormat(DX_CHARCODEFORMAT_UTF8)?;
         let window_title = "aiueo!! あいうえお!";
-        SetMainWindowText(window_title);
+        SetMainWindowText(window_title)?;
         ChangeWindowMode(None)?;
         DxLib_Init()?;
         SetDrawScreen(None)?;
-        SetUseASyncLoadFlag(TRUE)?;
+        SetUseASyncLoadFlag(true)?;
 
         let center_x = 320.0;
         let center_y = 240.0;
         let radius = 100.0;
         let mut deg = 0;
-        let file_buffer_size: usize = 256;
-
-        let mut file_buffer = vec![1i8; file_buffer_size];
-        let file_handle = FileRead_open("./test.txt", TRUE)?;
-        {
-            FileRead_gets(&mut file_buffer, file_buffer_size as i32, file_handle);
-            let file_buffer_u8 = file_buffer.iter().map(|&b| b as u8).collect();
-            println!("{}", String::from_utf8(file_buffer_u8).unwrap());
-        }
-        {
-            FileRead_gets(&mut file_buffer, file_buffer_size as i32, file_handle);
-            let file_buffer_u8 = file_buffer.iter().map(|&b| b as u8).collect();
-            println!("{}", String::from_utf8(file_buffer_u8).unwrap());
-        }
-
-        FileRead_close(file_handle)?;
-
-        let key_input_size: usize = 1024;
-        let key_input_size = 128; // 例: 入力バッファのサイズ
-        let mut key_input: Vec<c_char> = vec![0; key_input_size]; // バッファの初期化
-        //let mut key_input: [c_char; 1024] = [0; 1024];
-        KeyInputString(0, 0, key_input_size as i32, &mut key_input, FALSE); // スライスとして渡す
-
-        // KeyInputString 呼び出し時に Vec<CChar> を &mut [CChar] として渡す
-        //KeyInputString(0, 0, key_input_size as i32, &mut key_input[..], FALSE); // スライスとして渡す
+        let file_handle = FileRead_open("./test.txt", true)?;
+        println!("{}", FileRead_gets(&file_handle)?);
+        println!("{}", FileRead_gets(&file_handle)?);
 
-        // key_inputからUTF-8の文字列に変換
+        FileRead_close(file_handle.into_raw())?;
 
-        let key_input_string = unsafe {
-            CStr::from_ptr(key_input.as_ptr()) // ポインタからC文字列を取得
-                .to_string_lossy() // UTF-8に変換、無効なバイトはUTF-8のエラーを無視して処理
-                .into_owned() // String型に変換
-        };
+        // 入力された文字列はStringとして返される
+        let key_input_string = KeyInputString(0, 0, false)?;
         //let snd =
         //LoadSoundMem("D:/win/program/rb/main-project/youtube-download/touhou-mangetu.mp3")?;
-        //PlaySoundMem(snd, DX_PLAYTYPE_LOOP, 0)?;
+        //PlaySoundMem(&snd, PlayType::Loop, false)?;
 
         while ScreenFlip().is_ok()
             && ClearDrawScreen(None).is_ok()
             && ProcessMessage().is_ok()
-            && CheckHitKey(KEY_INPUT_ESCAPE)? == FALSE
+            && !CheckHitKey(KEY_INPUT_ESCAPE)?
         {
             deg = (deg + 1) % 360;
             let angle_rad = deg as f64 * std::f64::consts::PI / 180.0;
@@ -78,7 +73,7 @@ mod tests {
             DrawString(
                 0,
                 0,
-                &format!(
+                format!(
                     "x: {:.2} y: {:.2} angle_rad: {:.2} deg: {}",
                     x, y, angle_rad, deg
                 ),
@@ -94,4 +89,168 @@ mod tests {
         DxLib_End()?;
         Ok(())
     }
+
+    // #[out]引数はタプルとして返される(スタブの値で確認)
+    #[cfg(not(any(windows, feature = "record")))]
+    #[test]
+    fn test_out_params() -> R<(), DxLibError> {
+        with_dxlib(|| {
+            assert_eq!(GetScreenState()?, (640, 480, 32));
+            assert_eq!(GetMousePoint()?, (0, 0));
+            assert_eq!(GetDrawStringSize("abc", None)?, (24, 16, 1));
+            // #[len_of(string)]は変換後の文字コードでの長さを渡す (スタブはstr_len*8を幅として返す)
+            let previous = crate::charcode::char_code_format();
+            SetUseCharCodeFormat(DX_CHARCODEFORMAT_SHIFTJIS)?;
+            assert_eq!(GetDrawStringSize("あい", None)?.0, 4 * 8);
+            SetUseCharCodeFormat(DX_CHARCODEFORMAT_UTF16LE)?;
+            assert_eq!(GetDrawStringSize("あい", None)?.0, 2 * 8);
+            SetUseCharCodeFormat(previous)?;
+            // error_conditionはCの戻り値に対して判定される
+            assert!(GetGraphSize(&GraphHandle::from_raw(-1)).is_err());
+            Ok(())
+        })
+    }
+
+    // #[out_string]で不正な文字列が書き込まれた場合はpanicせずにエラーを返す
+    #[cfg(not(any(windows, feature = "record")))]
+    #[test]
+    fn test_out_string_invalid_data() -> R<(), DxLibError> {
+        let _lock = lock_dxlib();
+        let path = std::env::temp_dir().join("dxlib_rs_invalid_string.txt");
+        std::fs::write(&path, b"\xff\xfe\n").unwrap();
+        let file_handle = FileRead_open(path.to_str().unwrap(), false)?;
+        let result = FileRead_gets(&file_handle);
+        FileRead_close(file_handle.into_raw())?;
+        assert!(matches!(
+            result,
+            Err(DxLibError::InvalidString("FileRead_gets", "buffer", _))
+        ));
+        Ok(())
+    }
+
+    // 文字列の途中にNUL文字が含まれている場合はpanicせずにエラーを返す
+    #[test]
+    fn test_interior_nul() -> R<(), DxLibError> {
+        with_dxlib(|| {
+            assert!(matches!(
+                LoadGraph("a\0b.png"),
+                Err(DxLibError::InteriorNul("LoadGraph", "file_name", 1))
+            ));
+            // #[lossy]の関数はNUL文字までで切り詰めて渡される
+            DrawString(0, 0, "a\0b", GetColor(255, 255, 255)?)?;
+            Ok(())
+        })
+    }
+
+    // エラー時は関数名、戻り値、引数を持つDxLibErrorを返す
+    #[cfg(not(any(windows, feature = "record")))]
+    #[test]
+    fn test_call_error() {
+        with_dxlib(|| {
+            match LoadGraph("not_found.png") {
+                Err(DxLibError::LoadFailed {
+                    function,
+                    code,
+                    args,
+                }) => {
+                    assert_eq!(function, "LoadGraph");
+                    assert_eq!(code, -1);
+                    assert_eq!(args, vec![("file_name", "\"not_found.png\"".to_string())]);
+                }
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
+            }
+            let error = GetGraphSize(&GraphHandle::from_raw(-5)).unwrap_err();
+            assert!(matches!(error, DxLibError::InvalidHandle { .. }));
+            assert_eq!(error.function(), Some("GetGraphSize"));
+            assert_eq!(error.code(), Some(-1));
+            assert_eq!(
+                error.to_string(),
+                "GetGraphSize(gr_handle = -5) failed with invalid handle (code -1)"
+            );
+            // 引数は C の値に変換する前の値を表示する
+            let error = DeleteGraph(-5, None).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "DeleteGraph(gr_handle = -5, log_out_flag = None) failed with invalid handle (code -1)"
+            );
+            let error = DeleteGraph(-5, Some(true)).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "DeleteGraph(gr_handle = -5, log_out_flag = Some(true)) failed with invalid handle (code -1)"
+            );
+            let error = PlaySoundMem(&SoundHandle::from_raw(-5), PlayType::Back, true).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "PlaySoundMem(sound_handle = -5, play_type = Back, top_position_flag = true) failed with invalid handle (code -1)"
+            );
+        })
+    }
+
+    // bool、#[dx_enum]の引数、戻り値の変換
+    #[cfg(not(any(windows, feature = "record")))]
+    #[test]
+    fn test_bool_and_enum() -> R<(), DxLibError> {
+        with_dxlib(|| {
+            assert!(CheckHitKey(KEY_INPUT_ESCAPE)?);
+            SetDrawBlendMode(BlendMode::Alpha, 128)?;
+            assert_eq!(GetDrawBlendMode()?, (BlendMode::Alpha, 128));
+            SetDrawBlendMode(BlendMode::NoBlend, 255)?;
+            Ok(())
+        })
+    }
+
+    // dxlib-rs-genで生成した関数の呼び出し (既定引数、boolの変換、文字列の引数)
+    #[cfg(not(windows))]
+    #[test]
+    fn test_generated_functions() -> R<(), DxLibError> {
+        use crate::dxlib_excerpt::*;
+
+        DrawLine(0, 0, 10, 10, 0xffffff, None)?;
+        assert!(DrawLine(0, 0, 10, 10, 0xffffff, Some(0)).is_err());
+        DrawBox(0, 0, 10, 10, 0xffffff, true)?;
+        DrawCircle(5, 5, 3, 0xffffff, None, None)?;
+        assert!(DrawCircle(5, 5, -1, 0xffffff, Some(false), None).is_err());
+        let count = GetNowCount(None)?;
+        assert!(GetNowCount(Some(true))? > count);
+        SetWindowText("generated")?;
+        Ok(())
+    }
+
+    // デバッグビルドでは初期化前、初期化したスレッド以外からの#[context]の関数の呼び出しはエラーになる
+    #[cfg(all(debug_assertions, not(any(windows, feature = "record"))))]
+    #[test]
+    fn test_context_check() {
+        use crate::session::DxLibBuilder;
+        use crate::utils::ContextError;
+
+        let _lock = lock_dxlib();
+        assert!(matches!(
+            GetColor(255, 255, 255),
+            Err(DxLibError::Context(
+                "GetColor",
+                ContextError::NotInitialized
+            ))
+        ));
+        let session = DxLibBuilder::new().build().unwrap();
+        assert!(GetColor(255, 255, 255).is_ok());
+        std::thread::spawn(|| {
+            assert!(matches!(
+                DrawString(0, 0, "abc", 0),
+                Err(DxLibError::Context(
+                    "DrawString",
+                    ContextError::OffMainThread
+                ))
+            ));
+        })
+        .join()
+        .unwrap();
+        session.end().unwrap();
+        assert!(matches!(
+            ProcessMessage(),
+            Err(DxLibError::Context(
+                "ProcessMessage",
+                ContextError::NotInitialized
+            ))
+        ));
+    }
 }
@@ -0,0 +1,298 @@
+/*dxlib call recorder*/
+// recordフィーチャー有効時、dxlib::*の関数はDxLibを呼び出さずにここで記録される
+// 記録はスレッドごとで、Recorder::start()からRecorderがDropされるまでの呼び出しが対象
+//  let recorder = Recorder::start();
+//  recorder.on("CheckHitKey").arg("key_code", KEY_INPUT_ESCAPE).frame(3).returns(TRUE);
+//  run_game()?;
+//  recorder.assert_snapshot("...");
+// 設定できるのはCの関数の戻り値のみで、#[out],#[out_string]の値は既定値(0、false、空文字列)になる
+use crate::dxlib_error::format_call_args;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+// フレームの終わりとして数える関数
+const FRAME_FUNCTION: &str = "ScreenFlip";
+
+// 記録された関数呼び出し
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCall {
+    // 呼び出し時のフレーム番号(ScreenFlipの呼び出し回数、最初のフレームは0)
+    pub frame: u32,
+    pub function: &'static str,
+    // 引数名と値(文字列は変換前の文字列、ハンドル,boolはCに渡した数値)
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl RecordedCall {
+    // 引数の値
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(arg_name, _)| *arg_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// DrawString(x = 10, y = 20, string = "score", color = 16777215)
+impl fmt::Display for RecordedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.function, format_call_args(&self.args))
+    }
+}
+
+// 設定された戻り値
+struct Script {
+    function: &'static str,
+    args: Vec<(&'static str, String)>,
+    frames: Option<(u32, u32)>,
+    value: i64,
+}
+
+impl Script {
+    fn matches(&self, call: &RecordedCall) -> bool {
+        self.function == call.function
+            && self
+                .frames
+                .is_none_or(|(first, last)| (first..=last).contains(&call.frame))
+            && self
+                .args
+                .iter()
+                .all(|(name, value)| call.arg(name) == Some(value.as_str()))
+    }
+}
+
+#[derive(Default)]
+struct RecorderState {
+    calls: Vec<RecordedCall>,
+    scripts: Vec<Script>,
+    frame: u32,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Rc<RefCell<RecorderState>>>> = const { RefCell::new(None) };
+}
+
+// このスレッドの関数呼び出しの記録
+// Drop時に記録を終了する(startの前に記録していたRecorderがあればそちらに戻る)
+pub struct Recorder {
+    state: Rc<RefCell<RecorderState>>,
+    previous: Option<Rc<RefCell<RecorderState>>>,
+}
+
+impl Recorder {
+    // このスレッドの関数呼び出しの記録を開始する
+    pub fn start() -> Self {
+        let state = Rc::new(RefCell::new(RecorderState::default()));
+        let previous = RECORDER.replace(Some(state.clone()));
+        Self { state, previous }
+    }
+
+    // 記録された呼び出し
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.state.borrow().calls.clone()
+    }
+
+    // 指定した関数の記録された呼び出し
+    pub fn calls_of(&self, function: &str) -> Vec<RecordedCall> {
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .filter(|call| call.function == function)
+            .cloned()
+            .collect()
+    }
+
+    // 現在のフレーム番号
+    pub fn frame(&self) -> u32 {
+        self.state.borrow().frame
+    }
+
+    // 記録された呼び出しを消去する(設定された戻り値、フレーム番号はそのまま)
+    pub fn clear(&self) {
+        self.state.borrow_mut().calls.clear();
+    }
+
+    // 記録された呼び出しを1行に1つずつ並べた文字列
+    pub fn snapshot(&self) -> String {
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .map(|call| format!("{}\n", call))
+            .collect()
+    }
+
+    // 記録された呼び出しがexpectedと一致することを確認する
+    // (各行の前後の空白、空行は無視される)
+    #[track_caller]
+    pub fn assert_snapshot(&self, expected: &str) {
+        let normalize = |s: &str| {
+            s.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let actual = self.snapshot();
+        let (actual, expected) = (normalize(&actual), normalize(expected));
+        assert!(
+            actual == expected,
+            "recorded calls do not match\n--- expected\n{}\n--- actual\n{}",
+            expected,
+            actual
+        );
+    }
+
+    // 関数の戻り値を設定する(設定されていない関数は0を返す)
+    // 複数の設定が一致する場合は後に設定したものが優先される
+    pub fn on(&self, function: &'static str) -> ScriptBuilder<'_> {
+        ScriptBuilder {
+            recorder: self,
+            script: Script {
+                function,
+                args: Vec::new(),
+                frames: None,
+                value: 0,
+            },
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        RECORDER.set(self.previous.take());
+    }
+}
+
+// Recorder::onで設定する戻り値の条件
+pub struct ScriptBuilder<'a> {
+    recorder: &'a Recorder,
+    script: Script,
+}
+
+impl ScriptBuilder<'_> {
+    // 引数の値が一致する場合のみ(値はDebugで表示した文字列で比較する)
+    pub fn arg(mut self, name: &'static str, value: impl fmt::Debug) -> Self {
+        self.script.args.push((name, format!("{:?}", value)));
+        self
+    }
+
+    // 指定したフレームのみ
+    pub fn frame(mut self, frame: u32) -> Self {
+        self.script.frames = Some((frame, frame));
+        self
+    }
+
+    // 指定したフレーム以降
+    pub fn from_frame(mut self, frame: u32) -> Self {
+        self.script.frames = Some((frame, u32::MAX));
+        self
+    }
+
+    // Cの関数の戻り値として返す値(TRUE、ハンドルの値、-1等)
+    pub fn returns(mut self, value: impl Into<i64>) {
+        self.script.value = value.into();
+        self.recorder.state.borrow_mut().scripts.push(self.script);
+    }
+}
+
+// 記録する引数の値
+// ポインタはアドレスが実行ごとに異なるため、nullかどうかのみ記録する
+pub trait RecordArg {
+    fn record_arg(&self) -> String;
+}
+
+macro_rules! impl_record_arg_debug {
+    ($($ty:ty),*) => {
+        $(
+            impl RecordArg for $ty {
+                fn record_arg(&self) -> String {
+                    format!("{:?}", self)
+                }
+            }
+        )*
+    };
+}
+impl_record_arg_debug!(
+    i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, bool, str, String
+);
+// &RECT で渡す引数 (ClearDrawScreen) は値を記録する
+impl_record_arg_debug!(crate::dxlib_types::RECT);
+
+impl<T: ?Sized> RecordArg for *const T {
+    fn record_arg(&self) -> String {
+        if self.is_null() { "null" } else { "<ptr>" }.to_string()
+    }
+}
+
+impl<T: ?Sized> RecordArg for *mut T {
+    fn record_arg(&self) -> String {
+        self.cast_const().record_arg()
+    }
+}
+
+impl<T: RecordArg + ?Sized> RecordArg for &T {
+    fn record_arg(&self) -> String {
+        (**self).record_arg()
+    }
+}
+
+impl<T: RecordArg + ?Sized> RecordArg for &mut T {
+    fn record_arg(&self) -> String {
+        (**self).record_arg()
+    }
+}
+
+// 設定された戻り値からCの関数の戻り値を作成する
+pub trait FromRecorded {
+    fn from_recorded(value: i64) -> Self;
+}
+
+macro_rules! impl_from_recorded {
+    ($($ty:ty),*) => {
+        $(
+            impl FromRecorded for $ty {
+                fn from_recorded(value: i64) -> Self {
+                    value as $ty
+                }
+            }
+        )*
+    };
+}
+impl_from_recorded!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl FromRecorded for () {
+    fn from_recorded(_value: i64) -> Self {}
+}
+
+// 関数呼び出しを記録し、設定された戻り値を返す(dxlib_gen!の生成する関数から呼ばれる)
+// 記録中でない場合は0を返す
+#[doc(hidden)]
+pub fn record_call<A, R: FromRecorded>(
+    function: &'static str,
+    args: Vec<(&'static str, String)>,
+    _raw_args: A,
+) -> R {
+    let Some(state) = RECORDER.with_borrow(|recorder| recorder.clone()) else {
+        return R::from_recorded(0);
+    };
+    let mut state = state.borrow_mut();
+    let call = RecordedCall {
+        frame: state.frame,
+        function,
+        args,
+    };
+    let value = state
+        .scripts
+        .iter()
+        .rev()
+        .find(|script| script.matches(&call))
+        .map_or(0, |script| script.value);
+    state.calls.push(call);
+    if function == FRAME_FUNCTION {
+        state.frame += 1;
+    }
+    R::from_recorded(value)
+}
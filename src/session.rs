@@ -0,0 +1,314 @@
+/*dxlib session*/
+// DxLib_Init前の設定を正しい順序で行って初期化し、Drop時にDxLib_Endを1度だけ呼び出すセッション
+//  let mut dx = DxLibBuilder::new()
+//      .char_code_format(DX_CHARCODEFORMAT_UTF8)
+//      .window_title("タイトル")
+//      .window_mode(WindowMode::Windowed)
+//      .graph_mode(800, 600, 32)
+//      .build()?;
+//  dx.DrawString(0, 0, "hello", dx.GetColor(255, 255, 255)?)?;
+//  (?で早期リターンした場合もDrop時にDxLib_Endが呼び出される)
+use crate::charcode::char_code_format;
+use crate::dxlib::*;
+use crate::dxlib_constants::*;
+use crate::dxlib_error::DxLibError;
+use crate::utils::ContextError;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// DxLibBuilder::buildで作成したセッションが存在するか
+static SESSION_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// 文字列の引数の文字コードとして指定できる値 (charcode::encode_stringが対応している値)
+const CHAR_CODE_FORMATS: &[i32] = &[
+    DX_CHARCODEFORMAT_SHIFTJIS,
+    DX_CHARCODEFORMAT_GB2312,
+    DX_CHARCODEFORMAT_UHC,
+    DX_CHARCODEFORMAT_BIG5,
+    DX_CHARCODEFORMAT_UTF16LE,
+    DX_CHARCODEFORMAT_UTF16BE,
+    DX_CHARCODEFORMAT_WINDOWS_1252,
+    DX_CHARCODEFORMAT_ISO_IEC_8859_15,
+    DX_CHARCODEFORMAT_UTF8,
+    DX_CHARCODEFORMAT_ASCII,
+    DX_CHARCODEFORMAT_UTF32LE,
+    DX_CHARCODEFORMAT_UTF32BE,
+];
+
+// ウインドウモード、フルスクリーンモード (ChangeWindowMode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    Fullscreen,
+}
+
+// 画面の大きさとカラービット数 (SetGraphMode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphMode {
+    pub width: i32,
+    pub height: i32,
+    pub color_bit_depth: i32,
+}
+
+// DxLib_Init前の設定 (未指定の項目はDxLibの既定のまま、設定する関数を呼び出さない)
+#[derive(Debug, Clone)]
+pub struct DxLibBuilder {
+    window_title: Option<String>,
+    window_mode: Option<WindowMode>,
+    graph_mode: Option<GraphMode>,
+    char_code_format: Option<i32>,
+    log: Option<bool>,
+    log_directory: Option<PathBuf>,
+    async_load: Option<bool>,
+    vsync: Option<bool>,
+    // DxLib_Init後に設定する描画先 (既定はScreenFlipで表示する裏画面)
+    draw_screen: i32,
+}
+
+impl Default for DxLibBuilder {
+    fn default() -> Self {
+        Self {
+            window_title: None,
+            window_mode: None,
+            graph_mode: None,
+            char_code_format: None,
+            log: None,
+            log_directory: None,
+            async_load: None,
+            vsync: None,
+            draw_screen: DX_SCREEN_BACK,
+        }
+    }
+}
+
+impl DxLibBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ウインドウのタイトル (SetMainWindowText)
+    pub fn window_title(mut self, title: impl Into<String>) -> Self {
+        self.window_title = Some(title.into());
+        self
+    }
+
+    // ウインドウモード、フルスクリーンモード (ChangeWindowMode)
+    pub fn window_mode(mut self, mode: WindowMode) -> Self {
+        self.window_mode = Some(mode);
+        self
+    }
+
+    // 画面の大きさとカラービット数(16か32) (SetGraphMode)
+    pub fn graph_mode(mut self, width: i32, height: i32, color_bit_depth: i32) -> Self {
+        self.graph_mode = Some(GraphMode {
+            width,
+            height,
+            color_bit_depth,
+        });
+        self
+    }
+
+    // 文字列の引数の文字コード(DX_CHARCODEFORMAT_*) (SetUseCharCodeFormat)
+    pub fn char_code_format(mut self, char_code_format: i32) -> Self {
+        self.char_code_format = Some(char_code_format);
+        self
+    }
+
+    // ログファイルを出力するか (SetOutApplicationLogValidFlag)
+    pub fn log(mut self, enabled: bool) -> Self {
+        self.log = Some(enabled);
+        self
+    }
+
+    // ログファイルの保存先 (SetApplicationLogSaveDirectory)
+    pub fn log_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.log_directory = Some(directory.into());
+        self
+    }
+
+    // 非同期読み込みを行うか (SetUseASyncLoadFlag)
+    pub fn async_load(mut self, enabled: bool) -> Self {
+        self.async_load = Some(enabled);
+        self
+    }
+
+    // ScreenFlipで垂直同期を待つか (SetWaitVSyncFlag)
+    pub fn vsync(mut self, enabled: bool) -> Self {
+        self.vsync = Some(enabled);
+        self
+    }
+
+    // DxLib_Init後に設定する描画先 (SetDrawScreen、既定はDX_SCREEN_BACK)
+    pub fn draw_screen(mut self, draw_screen: i32) -> Self {
+        self.draw_screen = draw_screen;
+        self
+    }
+
+    // 設定を検証する (不正な場合はDxLibError::InvalidSetting(設定名,理由))
+    pub fn validate(&self) -> Result<(), DxLibError> {
+        let invalid =
+            |name: &'static str, reason: String| Err(DxLibError::InvalidSetting(name, reason));
+        if let Some(title) = &self.window_title
+            && let Some(position) = title.find('\0')
+        {
+            return invalid("window_title", format!("interior NUL at byte {}", position));
+        }
+        if let Some(GraphMode {
+            width,
+            height,
+            color_bit_depth,
+        }) = self.graph_mode
+        {
+            if width <= 0 || height <= 0 {
+                return invalid("graph_mode", format!("invalid size {}x{}", width, height));
+            }
+            if !matches!(color_bit_depth, 16 | 32) {
+                return invalid(
+                    "graph_mode",
+                    format!("color_bit_depth must be 16 or 32, got {}", color_bit_depth),
+                );
+            }
+        }
+        if let Some(char_code_format) = self.char_code_format
+            && !CHAR_CODE_FORMATS.contains(&char_code_format)
+        {
+            return invalid(
+                "char_code_format",
+                format!("unknown DX_CHARCODEFORMAT value {}", char_code_format),
+            );
+        }
+        if let Some(directory) = &self.log_directory {
+            if self.log == Some(false) {
+                return invalid(
+                    "log_directory",
+                    "log output is disabled by log(false)".to_string(),
+                );
+            }
+            if directory.as_os_str().is_empty() {
+                return invalid("log_directory", "empty path".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // 設定を検証し、DxLib_Init前の設定関数を順に呼び出して初期化する
+    // 既に初期化されている場合(別のセッション、dxlib::DxLib_Initの呼び出し)はDxLibError::AlreadyInitialized
+    // DxLib_Initに失敗した場合は文字コードの設定を元に戻す (他の設定はDxLibに設定されたままになる)
+    pub fn build(&self) -> Result<DxLibSession, DxLibError> {
+        self.validate()?;
+        // セッションの確保は1度のswapで判定し、以降の設定関数が他のセッションと重ならないようにする
+        if SESSION_ACTIVE.swap(true, Ordering::SeqCst) {
+            return Err(DxLibError::AlreadyInitialized);
+        }
+        // dxlib::DxLib_Initで初期化済みの場合は、初期化後のDxLibに設定関数を呼び出さない
+        if !matches!(DxContext::check(), Err(ContextError::NotInitialized)) {
+            SESSION_ACTIVE.store(false, Ordering::SeqCst);
+            return Err(DxLibError::AlreadyInitialized);
+        }
+        let previous_char_code_format = char_code_format();
+        let context = match self.initialize() {
+            Ok(context) => context,
+            Err(e) => {
+                if self.char_code_format.is_some() {
+                    let _ = SetUseCharCodeFormat(previous_char_code_format);
+                }
+                SESSION_ACTIVE.store(false, Ordering::SeqCst);
+                return Err(e);
+            }
+        };
+        // 初期化後の設定に失敗した場合は、sessionのDropでDxLib_Endを呼び出す
+        let mut session = DxLibSession {
+            context: Some(context),
+        };
+        session.SetDrawScreen(Some(self.draw_screen))?;
+        Ok(session)
+    }
+
+    fn initialize(&self) -> Result<DxContext, DxLibError> {
+        // 文字列の引数(タイトル、ログの保存先)の変換に使用されるため、文字コードを最初に設定する
+        if let Some(char_code_format) = self.char_code_format {
+            SetUseCharCodeFormat(char_code_format)?;
+        }
+        // ログの設定はDxLib_Init中の出力から反映される
+        if let Some(enabled) = self.log {
+            SetOutApplicationLogValidFlag(enabled)?;
+        }
+        if let Some(directory) = &self.log_directory {
+            SetApplicationLogSaveDirectory(directory.to_string_lossy())?;
+        }
+        if let Some(mode) = self.window_mode {
+            ChangeWindowMode(Some(mode == WindowMode::Windowed))?;
+        }
+        if let Some(GraphMode {
+            width,
+            height,
+            color_bit_depth,
+        }) = self.graph_mode
+        {
+            SetGraphMode(width, height, color_bit_depth, None)?;
+        }
+        if let Some(title) = &self.window_title {
+            SetMainWindowText(title)?;
+        }
+        if let Some(enabled) = self.vsync {
+            SetWaitVSyncFlag(enabled)?;
+        }
+        if let Some(enabled) = self.async_load {
+            SetUseASyncLoadFlag(enabled)?;
+        }
+        // 検査後にdxlib::DxLib_Initが呼び出された場合も、DxContextの作成時にエラーになる
+        DxContext::DxLib_Init().map_err(|e| match e {
+            DxLibError::Context(_, ContextError::AlreadyInitialized) => {
+                DxLibError::AlreadyInitialized
+            }
+            e => e,
+        })
+    }
+}
+
+// DxLibBuilder::buildで初期化したセッション
+// DxContextとして描画等の関数を呼び出せ、Drop時(もしくはend)にDxLib_Endを1度だけ呼び出す
+#[derive(Debug)]
+pub struct DxLibSession {
+    // endでDxLib_Endを呼び出した後のみNone
+    context: Option<DxContext>,
+}
+
+impl DxLibSession {
+    // DxLib_Endを呼び出してセッションを終了する (Dropでは無視されるDxLib_Endのエラーを受け取る場合に使用する)
+    // DxLib_Endが失敗した場合もDxContextは初期化前の状態に戻るため、再度buildできる
+    pub fn end(mut self) -> Result<(), DxLibError> {
+        match self.context.take() {
+            Some(context) => context.DxLib_End().map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Deref for DxLibSession {
+    type Target = DxContext;
+    fn deref(&self) -> &DxContext {
+        self.context
+            .as_ref()
+            .expect("DxLibSession is already ended")
+    }
+}
+
+impl DerefMut for DxLibSession {
+    fn deref_mut(&mut self) -> &mut DxContext {
+        self.context
+            .as_mut()
+            .expect("DxLibSession is already ended")
+    }
+}
+
+impl Drop for DxLibSession {
+    fn drop(&mut self) {
+        // DxContext::DxLib_Endは成否に関わらず初期化の状態を終了後に戻す
+        if let Some(context) = self.context.take() {
+            let _ = context.DxLib_End();
+        }
+        SESSION_ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,38 @@
+/*dxlib test support*/
+// スタブ(Windows以外)に対するテストで共有する準備 (ユニットテスト、tests/commonから使用する)
+use crate::session::DxLibBuilder;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+// DxLibの初期化はプロセスで1つのため、スタブを呼び出すテストは順に実行する
+static DXLIB_LOCK: Mutex<()> = Mutex::new(());
+
+// dynamicフィーチャー有効時は、build.rsがビルドしたスタブを読み込む (1度だけ)
+fn load_stub() {
+    #[cfg(feature = "dynamic")]
+    {
+        static LOAD: std::sync::Once = std::sync::Once::new();
+        LOAD.call_once(|| {
+            #[cfg(not(windows))]
+            let path = env!("DXLIB_STUB_DYLIB");
+            #[cfg(windows)]
+            let path = "DxLib_x64.dll";
+            crate::dxlib::DxLib::load(path).unwrap();
+        });
+    }
+}
+
+// 他のテストの初期化、終了と重ならないようにロックを取得し、スタブを読み込む
+// (DxLib_Initを呼び出さずにスタブを呼び出すテストもロックを取得する)
+pub fn lock_dxlib() -> MutexGuard<'static, ()> {
+    let lock = DXLIB_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    load_stub();
+    lock
+}
+
+// このスレッドでDxLib_Initを呼び出してテストを実行し、終了時(panicした場合も含む)にDxLib_Endを呼び出す
+// (#[context]の関数はデバッグビルドではDxLib_Initを呼び出したスレッドからのみ呼び出せる)
+pub fn with_dxlib<T>(test: impl FnOnce() -> T) -> T {
+    let _lock = lock_dxlib();
+    let _session = DxLibBuilder::new().build().unwrap();
+    test()
+}
@@ -1,23 +1,619 @@
-use crate::dxlib_constants::*;
-use crate::dxlib_error::*;
-use crate::dxlib_types::*;
-use std::ffi::CString;
+use crate::charcode::*;
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::ThreadId;
 
+// #![trace_feature]を指定したcffi_gen!の生成するコードから参照する
+#[cfg(feature = "trace")]
+pub use tracing;
+
+// 文字列の途中にNUL文字が含まれていた場合のエラー(positionはNUL文字のバイト位置)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteriorNulError {
+    pub position: usize,
+}
+
+impl std::fmt::Display for InteriorNulError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Interior NUL at byte {}", self.position)
+    }
+}
+
+impl std::error::Error for InteriorNulError {}
+
+fn check_interior_nul(s: &str) -> Result<(), InteriorNulError> {
+    match s.find('\0') {
+        Some(position) => Err(InteriorNulError { position }),
+        None => Ok(()),
+    }
+}
+
+fn truncate_at_nul(s: &str) -> &str {
+    s.split('\0').next().unwrap_or_default()
+}
+
+// 文字列の引数を、SetUseCharCodeFormatで設定された文字コードのNUL終端のバイト列として保持する
+// (元の文字列はエラー時の引数の表示用に保持する)
 pub struct CStringHolder {
-    _c_string: CString,
-    ptr: *const CChar,
+    _bytes: Vec<u8>,
+    // 変換した文字コードの1単位のバイト数
+    unit_size: usize,
+    source: String,
 }
 
 impl CStringHolder {
-    pub fn new(s: impl ToString) -> Self {
-        let c_string = CString::new(s.to_string()).unwrap();
+    // 文字列の途中にNUL文字が含まれている場合はエラーを返す
+    pub fn new(s: impl ToString) -> Result<Self, InteriorNulError> {
+        let s = s.to_string();
+        check_interior_nul(&s)?;
+        let char_code_format = char_code_format();
+        Ok(Self {
+            _bytes: encode_string(&s, char_code_format),
+            unit_size: code_unit_size(char_code_format),
+            source: s,
+        })
+    }
+
+    // 文字列の途中にNUL文字が含まれている場合はそこまでで切り詰める(#[lossy])
+    pub fn new_lossy(s: impl ToString) -> Self {
+        let s = s.to_string();
+        let char_code_format = char_code_format();
         Self {
-            _c_string: c_string,
-            ptr: std::ptr::null_mut(),
+            _bytes: encode_string(truncate_at_nul(&s), char_code_format),
+            unit_size: code_unit_size(char_code_format),
+            source: s,
         }
     }
 
     pub fn as_ptr(&self) -> *const std::os::raw::c_char {
-        self._c_string.as_ptr()
+        self._bytes.as_ptr() as *const std::os::raw::c_char
+    }
+
+    // 変換後の文字列の長さ (文字コードの単位の数、終端のNUL文字を含まない。#[len_of]で渡す)
+    pub fn len(&self) -> usize {
+        self._bytes.len() / self.unit_size - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+// W付きの関数(#[wide])の文字列の引数を、NUL終端のUTF-16として保持する
+pub struct WideStringHolder {
+    _wide: Vec<u16>,
+    source: String,
+}
+
+impl WideStringHolder {
+    pub fn new(s: impl ToString) -> Result<Self, InteriorNulError> {
+        let s = s.to_string();
+        check_interior_nul(&s)?;
+        Ok(Self {
+            _wide: encode_wide_string(&s),
+            source: s,
+        })
+    }
+
+    pub fn new_lossy(s: impl ToString) -> Self {
+        let s = s.to_string();
+        Self {
+            _wide: encode_wide_string(truncate_at_nul(&s)),
+            source: s,
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const u16 {
+        self._wide.as_ptr()
+    }
+
+    // 変換後の文字列の長さ (UTF-16の単位の数、終端のNUL文字を含まない。#[len_of]で渡す)
+    pub fn len(&self) -> usize {
+        self._wide.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+// &mut String の引数を、C側で書き込み可能なNUL終端のバッファとして保持する
+// capacity(文字数)分と終端のNUL文字の分を確保し、呼び出し後にdecodeで書き込まれた文字列を取り出す
+pub struct StringBuffer {
+    bytes: Vec<u8>,
+    capacity: usize,
+}
+
+impl StringBuffer {
+    pub fn new(s: &str, capacity: usize) -> Result<Self, InteriorNulError> {
+        check_interior_nul(s)?;
+        Ok(Self::with_contents(s, capacity))
+    }
+
+    pub fn new_lossy(s: &str, capacity: usize) -> Self {
+        Self::with_contents(truncate_at_nul(s), capacity)
+    }
+
+    fn with_contents(s: &str, capacity: usize) -> Self {
+        let capacity = capacity.max(s.chars().count());
+        let mut bytes = encode_string(s, char_code_format());
+        // UTF-16,UTF-32 の文字コードでも収まるように1文字4バイトで確保する
+        bytes.resize(bytes.len().max((capacity + 1) * 4), 0);
+        Self { bytes, capacity }
+    }
+
+    // C側に渡す最大文字数
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut std::os::raw::c_char {
+        self.bytes.as_mut_ptr() as *mut std::os::raw::c_char
+    }
+
+    pub fn decode(&self) -> anyhow::Result<String> {
+        decode_c_string(&self.bytes)
+    }
+}
+
+// W付きの関数(#[wide])の &mut String の引数を、書き込み可能なUTF-16のバッファとして保持する
+pub struct WideStringBuffer {
+    wide: Vec<u16>,
+    capacity: usize,
+}
+
+impl WideStringBuffer {
+    pub fn new(s: &str, capacity: usize) -> Result<Self, InteriorNulError> {
+        check_interior_nul(s)?;
+        Ok(Self::with_contents(s, capacity))
+    }
+
+    pub fn new_lossy(s: &str, capacity: usize) -> Self {
+        Self::with_contents(truncate_at_nul(s), capacity)
+    }
+
+    fn with_contents(s: &str, capacity: usize) -> Self {
+        let mut wide = encode_wide_string(s);
+        let capacity = capacity.max(wide.len() - 1);
+        wide.resize(capacity + 1, 0);
+        Self { wide, capacity }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u16 {
+        self.wide.as_mut_ptr()
+    }
+
+    pub fn decode(&self) -> anyhow::Result<String> {
+        decode_wide_c_string(&self.wide)
+    }
+}
+
+// C側で書き込まれた文字列バッファ(NUL終端)を、設定された文字コードとしてStringに変換する
+pub fn decode_c_string(buffer: &[u8]) -> anyhow::Result<String> {
+    decode_string(buffer, char_code_format())
+}
+
+// W付きの関数(#[wide])で書き込まれたUTF-16の文字列バッファ(NUL終端)をStringに変換する
+pub fn decode_wide_c_string(buffer: &[u16]) -> anyhow::Result<String> {
+    decode_wide_string(buffer)
+}
+
+// 実行時に読み込む共有ライブラリ(dxlib_gen!の動的ロードで使用)
+pub struct DynamicLibrary {
+    library: libloading::Library,
+}
+
+impl DynamicLibrary {
+    pub fn open(path: impl AsRef<OsStr>) -> anyhow::Result<Self> {
+        let library = unsafe { libloading::Library::new(path.as_ref())? };
+        Ok(Self { library })
+    }
+
+    /// 関数ポインタを取得する。見つからない場合はNoneを返す
+    ///
+    /// # Safety
+    /// `T`は取得する関数の正しいシグネチャの関数ポインタ型でなければならない
+    pub unsafe fn symbol<T: Copy>(&self, name: &str) -> Option<T> {
+        unsafe {
+            self.library
+                .get::<T>(name.as_bytes())
+                .ok()
+                .map(|symbol| *symbol)
+        }
+    }
+}
+
+// #[callback] の引数に渡されたクロージャを保持する (dxlib_gen! が関数ごとに static として生成する)
+// クロージャは次に登録されるか、None で登録が解除されるまで保持される
+pub struct CallbackSlot<F: ?Sized> {
+    current: Mutex<Option<Arc<Mutex<Box<F>>>>>,
+}
+
+// 呼び出し中のクロージャ (スレッドごと、CallbackSlot のアドレス)
+thread_local! {
+    static INVOKING_CALLBACKS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+// クロージャ内で発生した panic (C 側へ巻き戻さずに保持する)
+static CALLBACK_PANIC: Mutex<Option<Box<dyn Any + Send>>> = Mutex::new(None);
+
+impl<F: ?Sized> CallbackSlot<F> {
+    pub const fn new() -> Self {
+        Self {
+            current: Mutex::new(None),
+        }
+    }
+
+    // 登録するクロージャを C 関数の呼び出し前に置き換える (登録中に C 側から呼び出される場合に備える)
+    // 返り値の commit で以前のクロージャを破棄し、commit せずに破棄した場合は以前のクロージャに戻す
+    pub fn register(&self, callback: Option<Box<F>>) -> CallbackRegistration<'_, F> {
+        let previous = self.swap(callback.map(|f| Arc::new(Mutex::new(f))));
+        CallbackRegistration {
+            slot: self,
+            previous: Some(previous),
+        }
+    }
+
+    fn swap(&self, callback: Option<Arc<Mutex<Box<F>>>>) -> Option<Arc<Mutex<Box<F>>>> {
+        std::mem::replace(
+            &mut *self.current.lock().unwrap_or_else(PoisonError::into_inner),
+            callback,
+        )
     }
+
+    // トランポリンから登録されているクロージャを呼び出す
+    // 登録されていない場合、クロージャ内から同じコールバックが呼ばれた場合 (再入) は既定値を返す
+    // クロージャが panic した場合は take_callback_panic で取り出せるように保持して既定値を返す
+    pub fn invoke<R: Default>(&self, call: impl FnOnce(&mut F) -> R) -> R {
+        let Some(callback) = self
+            .current
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+        else {
+            return R::default();
+        };
+        let address = self as *const Self as *const () as usize;
+        let reentrant = INVOKING_CALLBACKS.with_borrow_mut(|invoking| {
+            if invoking.contains(&address) {
+                return true;
+            }
+            invoking.push(address);
+            false
+        });
+        if reentrant {
+            return R::default();
+        }
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut f = callback.lock().unwrap_or_else(PoisonError::into_inner);
+            call(&mut **f)
+        }));
+        INVOKING_CALLBACKS.with_borrow_mut(|invoking| invoking.retain(|a| *a != address));
+        match result {
+            Ok(value) => value,
+            Err(payload) => {
+                *CALLBACK_PANIC
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(payload);
+                R::default()
+            }
+        }
+    }
+}
+
+impl<F: ?Sized> Default for CallbackSlot<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// CallbackSlot::register で置き換えたクロージャの登録 (C 関数の呼び出しに成功した場合のみ commit する)
+pub struct CallbackRegistration<'a, F: ?Sized> {
+    slot: &'a CallbackSlot<F>,
+    // commit 済みの場合は None
+    previous: Option<Option<Arc<Mutex<Box<F>>>>>,
+}
+
+impl<F: ?Sized> CallbackRegistration<'_, F> {
+    // 以前のクロージャを破棄する (呼び出し中でなければここで破棄される)
+    pub fn commit(mut self) {
+        // 以前のクロージャの Drop で再び登録される場合に備えて、ロックの外で破棄する
+        drop(self.previous.take());
+    }
+}
+
+impl<F: ?Sized> Drop for CallbackRegistration<'_, F> {
+    // 呼び出しに失敗した場合 (エラー、panic) は以前のクロージャに戻し、渡されたクロージャを破棄する
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            drop(self.slot.swap(previous));
+        }
+    }
+}
+
+// #[callback] のクロージャ内で発生した最後の panic を取り出す
+// (std::panic::resume_unwind に渡すと Rust 側で panic を再開できる)
+pub fn take_callback_panic() -> Option<Box<dyn Any + Send>> {
+    CALLBACK_PANIC
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+// #[context] の関数の呼び出し時の検査に失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextError {
+    // 初期化の関数の呼び出し前、もしくは終了の関数の呼び出し後
+    NotInitialized,
+    // 初期化したスレッド以外からの呼び出し
+    OffMainThread,
+    // 初期化済み(#![context] の型が存在する)の状態での初期化
+    AlreadyInitialized,
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::NotInitialized => write!(f, "called before init"),
+            ContextError::OffMainThread => write!(f, "called off main thread"),
+            ContextError::AlreadyInitialized => write!(f, "called while already initialized"),
+        }
+    }
+}
+
+impl std::error::Error for ContextError {}
+
+// #![context] の初期化の状態 (cffi_gen! が static として生成する)
+// 初期化の関数を呼び出したスレッドと、#![context] の型が存在するかを保持する
+pub struct ContextState {
+    inner: Mutex<ContextStateInner>,
+}
+
+struct ContextStateInner {
+    // 初期化の関数を呼び出したスレッド (終了の関数の呼び出しで None に戻す)
+    main_thread: Option<ThreadId>,
+    // #![context] の型を作成済みか (型は同時に1つのみ存在する)
+    claimed: bool,
+}
+
+impl ContextState {
+    pub const fn new() -> Self {
+        Self {
+            inner: Mutex::new(ContextStateInner {
+                main_thread: None,
+                claimed: false,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ContextStateInner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn initialize(&self) {
+        self.lock().main_thread = Some(std::thread::current().id());
+    }
+
+    pub fn finalize(&self) {
+        self.lock().main_thread = None;
+    }
+
+    // #![context] の型を作成する権利を得る (初期化済み、もしくは型が存在する場合は false)
+    // 判定と更新を1度のロックで行うため、同時に呼び出しても true になるのは1つのみ
+    pub fn claim(&self) -> bool {
+        let mut inner = self.lock();
+        if inner.claimed || inner.main_thread.is_some() {
+            return false;
+        }
+        inner.claimed = true;
+        true
+    }
+
+    // claim で得た権利を手放す (初期化の失敗時、型の Drop 時)
+    pub fn release(&self) {
+        self.lock().claimed = false;
+    }
+
+    // 初期化済みで、初期化したスレッドからの呼び出しかを確認する
+    pub fn check(&self) -> Result<(), ContextError> {
+        match self.lock().main_thread {
+            None => Err(ContextError::NotInitialized),
+            Some(id) if id != std::thread::current().id() => Err(ContextError::OffMainThread),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+impl Default for ContextState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// #[repr(i32)] の列挙型とDxLibの定数(i32)を相互に変換する (dxlib_gen! の #[dx_enum] で使用)
+pub trait DxEnum: Sized + Copy {
+    fn to_raw(self) -> i32;
+    // 対応する列挙子がない場合はNoneを返す
+    fn from_raw(value: i32) -> Option<Self>;
+}
+
+// DxEnum を実装した #[repr(i32)] の列挙型を定義する
+// dx_enum! {
+//     pub enum PlayType {
+//         Normal = DX_PLAYTYPE_NORMAL,
+//         Back = DX_PLAYTYPE_BACK,
+//     }
+// }
+#[macro_export]
+macro_rules! dx_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(i32)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant = $value),*
+        }
+
+        impl $crate::utils::DxEnum for $name {
+            fn to_raw(self) -> i32 {
+                self as i32
+            }
+
+            fn from_raw(value: i32) -> ::core::option::Option<Self> {
+                $(
+                    if value == $name::$variant as i32 {
+                        return ::core::option::Option::Some($name::$variant);
+                    }
+                )*
+                ::core::option::Option::None
+            }
+        }
+    };
+}
+
+// dxlib_gen! が特別に扱わない型(独自の構造体等)の引数をCの関数に渡す値に変換する
+// impl IntoDxArg for Color {
+//     type Raw = u32;
+//     fn into_dx_arg(self) -> anyhow::Result<DxArg<u32>> {
+//         Ok(DxArg::new(self.to_u32()))
+//     }
+// }
+pub trait IntoDxArg {
+    // Cの関数に渡す型
+    type Raw;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<Self::Raw>>;
+}
+
+// &mut T の引数を、C側で書き込まれるポインタ等として渡す
+pub trait DxArgMut {
+    type Raw;
+    fn as_dx_arg_mut(&mut self) -> Self::Raw;
+}
+
+// IntoDxArg で変換された値
+// rawがポインタの場合、その参照先を呼び出しが終わるまで保持する
+pub struct DxArg<R> {
+    pub raw: R,
+    _holder: Option<Box<dyn std::any::Any>>,
+}
+
+impl<R> DxArg<R> {
+    pub fn new(raw: R) -> Self {
+        Self { raw, _holder: None }
+    }
+
+    // holderを保持し、保持された値からrawを作成する
+    // (holderは移動後の値が渡されるため、内部へのポインタをrawにできる)
+    //  DxArg::with_holder(CStringHolder::new(path)?, |holder| holder.as_ptr())
+    pub fn with_holder<H: 'static>(holder: H, raw: impl FnOnce(&H) -> R) -> Self {
+        let holder = Box::new(holder);
+        let raw = raw(&holder);
+        Self {
+            raw,
+            _holder: Some(holder),
+        }
+    }
+}
+
+macro_rules! impl_into_dx_arg_primitive {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoDxArg for $ty {
+                type Raw = $ty;
+                fn into_dx_arg(self) -> anyhow::Result<DxArg<$ty>> {
+                    Ok(DxArg::new(self))
+                }
+            }
+        )*
+    };
+}
+// CInt等の別名で指定された場合もそのまま渡す
+impl_into_dx_arg_primitive!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T> IntoDxArg for *const T {
+    type Raw = *const T;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<*const T>> {
+        Ok(DxArg::new(self))
+    }
+}
+
+impl<T> IntoDxArg for *mut T {
+    type Raw = *mut T;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<*mut T>> {
+        Ok(DxArg::new(self))
+    }
+}
+
+// パスは文字列の引数と同じ文字コードの*const c_charとして渡す
+// (UTF-8として不正なパスは置換文字に変換される)
+impl IntoDxArg for &std::path::Path {
+    type Raw = *const std::os::raw::c_char;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<Self::Raw>> {
+        let holder = CStringHolder::new(self.to_string_lossy())?;
+        Ok(DxArg::with_holder(holder, |holder| holder.as_ptr()))
+    }
+}
+
+impl IntoDxArg for &std::path::PathBuf {
+    type Raw = *const std::os::raw::c_char;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<Self::Raw>> {
+        self.as_path().into_dx_arg()
+    }
+}
+
+impl IntoDxArg for std::path::PathBuf {
+    type Raw = *const std::os::raw::c_char;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<Self::Raw>> {
+        self.as_path().into_dx_arg()
+    }
+}
+
+// #[repr(C)] の構造体を、値はそのまま、&T は *const T、&mut T は *mut T として渡せるようにする
+// dx_arg_repr_c!(RECT, VECTOR);
+#[macro_export]
+macro_rules! dx_arg_repr_c {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl $crate::utils::IntoDxArg for $ty {
+                type Raw = $ty;
+                fn into_dx_arg(self) -> $crate::anyhow::Result<$crate::utils::DxArg<$ty>> {
+                    ::core::result::Result::Ok($crate::utils::DxArg::new(self))
+                }
+            }
+
+            impl $crate::utils::IntoDxArg for &$ty {
+                type Raw = *const $ty;
+                fn into_dx_arg(self) -> $crate::anyhow::Result<$crate::utils::DxArg<*const $ty>> {
+                    ::core::result::Result::Ok($crate::utils::DxArg::new(self as *const $ty))
+                }
+            }
+
+            impl $crate::utils::DxArgMut for $ty {
+                type Raw = *mut $ty;
+                fn as_dx_arg_mut(&mut self) -> *mut $ty {
+                    self as *mut $ty
+                }
+            }
+        )*
+    };
 }
@@ -0,0 +1,105 @@
+// DxLibApiで関数の呼び出し先を差し替えるテスト
+// 差し替えていない関数はスタブ(実際のライブラリ)が呼び出される
+#![cfg(not(any(windows, feature = "record")))]
+#![allow(non_snake_case)]
+
+mod common;
+
+use dxlib_rs::dxlib::*;
+use dxlib_rs::dxlib_constants::*;
+use dxlib_rs::dxlib_error::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+// テスト対象のゲームロジック
+fn draw_score(score: i32) -> Result<(), DxLibError> {
+    if CheckHitKey(KEY_INPUT_SPACE)? {
+        let color = GetColor(255, 255, 255)?;
+        DrawString(10, 20, format!("score: {}", score), color)?;
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct FakeDxLib {
+    pressed: bool,
+    drawn: RefCell<Vec<(i32, i32, String, i32)>>,
+    deleted: RefCell<Vec<i32>>,
+}
+
+impl DxLibApi for FakeDxLib {
+    fn CheckHitKey(&self, _key_code: i32) -> Result<bool, DxLibError> {
+        Ok(self.pressed)
+    }
+
+    fn DrawString(&self, x: i32, y: i32, string: &str, color: i32) -> Result<i32, DxLibError> {
+        self.drawn
+            .borrow_mut()
+            .push((x, y, string.to_string(), color));
+        Ok(0)
+    }
+
+    fn LoadGraph(&self, _file_name: &str) -> Result<GraphHandle, DxLibError> {
+        Ok(GraphHandle::from_raw(7))
+    }
+
+    fn DeleteGraph(&self, gr_handle: i32, _log_out_flag: Option<bool>) -> Result<i32, DxLibError> {
+        self.deleted.borrow_mut().push(gr_handle);
+        Ok(0)
+    }
+}
+
+#[test]
+fn test_scoped_backend() -> Result<(), DxLibError> {
+    common::with_dxlib(|| {
+        let fake = Rc::new(FakeDxLib {
+            pressed: true,
+            ..Default::default()
+        });
+        DxLibApiBackend::scoped(fake.clone(), || draw_score(42))?;
+        // GetColorは差し替えていないため、スタブの値になる
+        assert_eq!(
+            *fake.drawn.borrow(),
+            vec![(10, 20, "score: 42".to_string(), 0xffffff)]
+        );
+
+        // ハンドルのDrop時のDeleteGraphも差し替えた呼び出し先に渡される
+        DxLibApiBackend::scoped(fake.clone(), || -> Result<(), DxLibError> {
+            let graph = LoadGraph("player.png")?;
+            assert_eq!(graph.as_raw(), 7);
+            Ok(())
+        })?;
+        assert_eq!(*fake.deleted.borrow(), vec![7]);
+
+        // scopedの終了後は元に戻る (スタブのCheckHitKeyは常に押されている)
+        let released = Rc::new(FakeDxLib::default());
+        DxLibApiBackend::scoped(released.clone(), || draw_score(1))?;
+        assert!(released.drawn.borrow().is_empty());
+        assert!(CheckHitKey(KEY_INPUT_SPACE)?);
+        Ok(())
+    })
+}
+
+#[test]
+fn test_global_backend() {
+    common::with_dxlib(|| {
+        struct FixedColor;
+        impl DxLibApi for FixedColor {
+            fn GetColor(&self, _red: i32, _green: i32, _blue: i32) -> Result<i32, DxLibError> {
+                Ok(123)
+            }
+        }
+
+        DxLibApiBackend::set_global(Some(Arc::new(FixedColor)));
+        // 全スレッドで共通の呼び出し先になる
+        let color = std::thread::spawn(|| GetColor(1, 2, 3)).join().unwrap();
+        // スレッドごとの設定が優先される
+        let real = DxLibApiBackend::scoped(Rc::new(DxLibApiReal), || GetColor(1, 2, 3));
+        DxLibApiBackend::set_global(None);
+
+        assert_eq!(color.unwrap(), 123);
+        assert_eq!(real.unwrap(), 0x010203);
+        assert_eq!(GetColor(1, 2, 3).unwrap(), 0x010203);
+    })
+}
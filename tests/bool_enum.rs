@@ -0,0 +1,62 @@
+// bool、#[dx_enum]の変換のテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+use dxlib_rs::dxlib_enums::*;
+use dxlib_rs::dxlib_error::*;
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::dxlib_enums::*;
+    use dxlib_rs::dxlib_error::*;
+    use dxlib_rs::utils::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "bool_enum_test_"]
+        #![as_result_error_type = "DxLibError"]
+        #![enum_error = "DxLibError::InvalidEnumValue"]
+        fn Not(flag: bool) -> bool,
+        #[not_result]
+        fn NotRaw(#[default = "true"] flag: Option<bool>) -> bool,
+        #[dx_enum]
+        fn NextMode(#[dx_enum] mode: BlendMode) -> BlendMode,
+        fn GetFlag(#[out] flag: bool) -> i32,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn bool_enum_test_Not(flag: i32) -> i32 {
+    (flag == 0) as i32
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn bool_enum_test_NotRaw(flag: i32) -> i32 {
+    (flag == 0) as i32
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn bool_enum_test_NextMode(mode: i32) -> i32 {
+    mode + 1
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn bool_enum_test_GetFlag(flag: *mut i32) -> i32 {
+    unsafe { *flag = 2 };
+    0
+}
+
+#[test]
+fn test_bool_enum() {
+    assert!(ffi::Not(false).unwrap());
+    assert!(!ffi::Not(true).unwrap());
+    assert!(!ffi::NotRaw(None));
+    // 0以外はtrueとして受け取る
+    assert!(ffi::GetFlag().unwrap());
+
+    assert_eq!(ffi::NextMode(BlendMode::Add).unwrap(), BlendMode::Sub);
+    // 対応する列挙子がない値はエラーになる
+    assert!(matches!(
+        ffi::NextMode(BlendMode::Mul),
+        Err(DxLibError::InvalidEnumValue("NextMode", "BlendMode", 5))
+    ));
+}
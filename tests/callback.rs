@@ -0,0 +1,161 @@
+// #[callback] の引数(クロージャを呼び出すトランポリンの関数ポインタを渡す)のテスト
+// 呼び出されるC関数はこのテスト内で定義し、登録された関数ポインタを保持する
+#![allow(non_snake_case)]
+
+mod common;
+
+use dxlib_rs::utils::take_callback_panic;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::dxlib_error::*;
+    use dxlib_rs::utils::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "callback_test_"]
+        #![as_result_error_type = "DxLibError"]
+        fn SetCallback(#[callback] callback: Option<Box<dyn FnMut(i32) -> i32 + Send>>) -> i32,
+        fn Fire(value: i32) -> i32,
+    }
+}
+
+type Callback = extern "C" fn(i32) -> i32;
+static CALLBACK: Mutex<Option<Callback>> = Mutex::new(None);
+// true の場合は次の登録を失敗させる
+static FAIL_NEXT: AtomicBool = AtomicBool::new(false);
+// 0 以外の場合は次の登録中にその値で関数を呼び出し、戻り値を FIRED_RESULT に保持する
+static FIRE_ON_REGISTER: AtomicI32 = AtomicI32::new(0);
+static FIRED_RESULT: AtomicI32 = AtomicI32::new(0);
+
+// 登録された関数ポインタを保持する (NULL の場合は 1 を返す、失敗させる場合は保持せずに -1 を返す)
+#[unsafe(no_mangle)]
+extern "C" fn callback_test_SetCallback(callback: Option<Callback>) -> i32 {
+    let value = FIRE_ON_REGISTER.swap(0, Ordering::SeqCst);
+    if value != 0
+        && let Some(callback) = callback
+    {
+        FIRED_RESULT.store(callback(value), Ordering::SeqCst);
+    }
+    if FAIL_NEXT.swap(false, Ordering::SeqCst) {
+        return -1;
+    }
+    *CALLBACK.lock().unwrap() = callback;
+    if callback.is_some() { 0 } else { 1 }
+}
+
+// 登録された関数を呼び出す (登録されていない場合は -1)
+#[unsafe(no_mangle)]
+extern "C" fn callback_test_Fire(value: i32) -> i32 {
+    let callback = *CALLBACK.lock().unwrap();
+    callback.map_or(-1, |callback| callback(value))
+}
+
+// Drop されたことを記録する
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_callback() {
+    // クロージャの状態は呼び出しをまたいで保持される
+    let total = Arc::new(AtomicI32::new(0));
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let counter = total.clone();
+    assert_eq!(
+        ffi::SetCallback(Some(Box::new(move |value| {
+            let _ = &flag;
+            counter.fetch_add(value, Ordering::SeqCst) + value
+        })))
+        .unwrap(),
+        0
+    );
+    assert_eq!(ffi::Fire(2).unwrap(), 2);
+    assert_eq!(ffi::Fire(3).unwrap(), 5);
+    assert!(!dropped.load(Ordering::SeqCst));
+
+    // 再登録すると以前のクロージャは破棄される
+    ffi::SetCallback(Some(Box::new(|value| value * 10))).unwrap();
+    assert!(dropped.load(Ordering::SeqCst));
+    assert_eq!(ffi::Fire(4).unwrap(), 40);
+    assert_eq!(total.load(Ordering::SeqCst), 5);
+
+    // 登録に失敗した場合は以前のクロージャが呼び出され、渡したクロージャは破棄される
+    let rejected = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(rejected.clone());
+    FAIL_NEXT.store(true, Ordering::SeqCst);
+    assert!(
+        ffi::SetCallback(Some(Box::new(move |value| {
+            let _ = &flag;
+            value
+        })))
+        .is_err()
+    );
+    assert!(rejected.load(Ordering::SeqCst));
+    assert_eq!(ffi::Fire(5).unwrap(), 50);
+    // 登録中に C 側から呼び出された場合も渡したクロージャが呼び出される
+    FIRE_ON_REGISTER.store(2, Ordering::SeqCst);
+    ffi::SetCallback(Some(Box::new(|value| value * 100))).unwrap();
+    assert_eq!(FIRED_RESULT.load(Ordering::SeqCst), 200);
+    assert_eq!(ffi::Fire(3).unwrap(), 300);
+    // 登録中に呼び出された後に失敗した場合も以前のクロージャに戻る
+    FIRE_ON_REGISTER.store(4, Ordering::SeqCst);
+    FAIL_NEXT.store(true, Ordering::SeqCst);
+    assert!(ffi::SetCallback(Some(Box::new(|value| value + 1))).is_err());
+    assert_eq!(FIRED_RESULT.load(Ordering::SeqCst), 5);
+    assert_eq!(ffi::Fire(5).unwrap(), 500);
+    ffi::SetCallback(Some(Box::new(|value| value * 10))).unwrap();
+    // 登録の解除に失敗した場合も以前のクロージャが保持される
+    FAIL_NEXT.store(true, Ordering::SeqCst);
+    assert!(ffi::SetCallback(None).is_err());
+    assert_eq!(ffi::Fire(6).unwrap(), 60);
+
+    // panic は C へ巻き戻さずに既定値を返し、take_callback_panic で取り出せる
+    ffi::SetCallback(Some(Box::new(|value| {
+        if value < 0 {
+            panic!("negative value {}", value);
+        }
+        value
+    })))
+    .unwrap();
+    assert_eq!(ffi::Fire(-1).unwrap(), 0);
+    let payload = take_callback_panic().unwrap();
+    assert_eq!(
+        payload.downcast_ref::<String>().map(String::as_str),
+        Some("negative value -1")
+    );
+    assert!(take_callback_panic().is_none());
+    // panic 後も登録されたまま呼び出せる
+    assert_eq!(ffi::Fire(7).unwrap(), 7);
+
+    // None の場合は NULL を渡して登録を解除する
+    assert_eq!(ffi::SetCallback(None).unwrap(), 1);
+    assert!(ffi::Fire(1).is_err());
+}
+
+// スタブの ProcessMessage は登録されたウインドウプロシージャを WM_NULL で呼び出す
+#[cfg(not(any(windows, feature = "record")))]
+#[test]
+fn test_hook_win_proc() {
+    use dxlib_rs::dxlib::*;
+
+    common::with_dxlib(|| {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let received = messages.clone();
+        SetHookWinProc(Some(Box::new(move |_hwnd, msg, _wparam, _lparam| {
+            received.lock().unwrap().push(msg);
+            0
+        })))
+        .unwrap();
+        ProcessMessage().unwrap();
+        SetHookWinProc(None).unwrap();
+        ProcessMessage().unwrap();
+        assert_eq!(*messages.lock().unwrap(), vec![0]);
+    })
+}
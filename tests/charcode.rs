@@ -0,0 +1,88 @@
+// 文字コードの設定(#[char_code_format])、#[wide]に応じた文字列の変換のテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+use dxlib_rs::charcode::*;
+use dxlib_rs::dxlib_constants::*;
+use dxlib_rs::dxlib_error::*;
+use std::ffi::CStr;
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::charcode::*;
+    use dxlib_rs::dxlib_error::*;
+    use dxlib_rs::utils::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "charcode_test_"]
+        #![as_result_error_type = "DxLibError"]
+        #![string_error = "DxLibError::InvalidString"]
+        fn SetFormat(#[char_code_format] format: i32) -> i32,
+        fn ByteLength(string: impl AsRef<str>) -> i32,
+        #[wide]
+        fn WideLength(string: impl AsRef<str>) -> i32,
+        fn WriteShiftJis(#[out_string(capacity = 16)] buffer: String, #[len_of(buffer)] size: i32) -> i32,
+        #[wide]
+        fn WriteWide(#[out_string(capacity = 16)] buffer: String, #[len_of(buffer)] size: i32) -> i32,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn charcode_test_SetFormat(_format: i32) -> i32 {
+    0
+}
+
+// 受け取ったNUL終端のバイト列の長さ
+#[unsafe(no_mangle)]
+extern "C" fn charcode_test_ByteLength(string: *const std::os::raw::c_char) -> i32 {
+    unsafe { CStr::from_ptr(string).to_bytes().len() as i32 }
+}
+
+// 受け取ったNUL終端のUTF-16の長さ
+#[unsafe(no_mangle)]
+extern "C" fn charcode_test_WideLength(string: *const u16) -> i32 {
+    let mut len = 0;
+    while unsafe { *string.add(len) } != 0 {
+        len += 1;
+    }
+    len as i32
+}
+
+// Shift-JISの"あい"を書き込む
+#[unsafe(no_mangle)]
+extern "C" fn charcode_test_WriteShiftJis(buffer: *mut std::os::raw::c_char, size: i32) -> i32 {
+    let bytes = b"\x82\xa0\x82\xa2\0";
+    assert!(size as usize >= bytes.len());
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer as *mut u8, bytes.len()) };
+    0
+}
+
+// UTF-16の"あい"を書き込む
+#[unsafe(no_mangle)]
+extern "C" fn charcode_test_WriteWide(buffer: *mut u16, size: i32) -> i32 {
+    let wide = [0x3042, 0x3044, 0];
+    assert!(size as usize >= wide.len());
+    unsafe { std::ptr::copy_nonoverlapping(wide.as_ptr(), buffer, wide.len()) };
+    0
+}
+
+#[test]
+fn test_char_code_format() {
+    // 未設定の場合はShift-JISで渡される
+    assert_eq!(char_code_format(), DX_CHARCODEFORMAT_SHIFTJIS);
+    assert_eq!(ffi::ByteLength("aあ").unwrap(), 3);
+    assert_eq!(ffi::WriteShiftJis().unwrap(), "あい");
+
+    // 設定後はその文字コードで渡される
+    ffi::SetFormat(DX_CHARCODEFORMAT_UTF8).unwrap();
+    assert_eq!(char_code_format(), DX_CHARCODEFORMAT_UTF8);
+    assert_eq!(ffi::ByteLength("aあ").unwrap(), 4);
+    assert!(matches!(
+        ffi::WriteShiftJis(),
+        Err(DxLibError::InvalidString("WriteShiftJis", "buffer", _))
+    ));
+
+    // #[wide]の関数は設定に関係なくUTF-16で渡される
+    assert_eq!(ffi::WideLength("aあ😀").unwrap(), 4);
+    assert_eq!(ffi::WriteWide().unwrap(), "あい");
+}
@@ -0,0 +1,4 @@
+// DxLib(スタブ)を呼び出す統合テストで共有する準備 (dxlib_rs::test_supportを使用する)
+#![allow(unused_imports)]
+
+pub use dxlib_rs::test_support::*;
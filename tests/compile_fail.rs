@@ -0,0 +1,7 @@
+// 未対応のシグネチャ、属性がpanicせずに該当箇所を指したコンパイルエラーになることのテスト
+// (期待するエラーメッセージはtests/ui/*.stderr、更新する場合はTRYBUILD=overwriteで実行する)
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
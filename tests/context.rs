@@ -0,0 +1,182 @@
+// #![context] (初期化で作成する!Sendな型と、#[context]の関数の呼び出し時の検査) のテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+use dxlib_rs::dxlib_error::DxLibError;
+use dxlib_rs::utils::ContextError;
+use ffi::Context;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::dxlib_error::*;
+    use dxlib_rs::utils::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "context_test_"]
+        #![as_result_error_type = "DxLibError"]
+        #![context = "Context"]
+        #![context_error = "DxLibError::Context"]
+        #[role = "init"]
+        fn Init() -> i32,
+        #[role = "finalize"]
+        fn End() -> i32,
+        /// 値を加算する
+        #[context]
+        fn Add(value: i32) -> i32,
+        #[context(mut)]
+        fn SetValue(value: i32) -> i32,
+        #[context]
+        #[not_result]
+        fn GetValue() -> i32,
+        // 初期化前でも呼び出せる関数
+        fn Configure(value: i32) -> i32,
+        #[handle(type = ValueHandle, drop = "Release")]
+        #[context]
+        fn Create() -> i32,
+        #[context]
+        fn Release(handle: i32) -> i32,
+    }
+}
+
+static VALUE: AtomicI32 = AtomicI32::new(0);
+// 初期化、終了の関数の戻り値 (-1 で失敗させる)
+static INIT_RESULT: AtomicI32 = AtomicI32::new(0);
+static END_RESULT: AtomicI32 = AtomicI32::new(0);
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_Init() -> i32 {
+    INIT_RESULT.load(Ordering::SeqCst)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_End() -> i32 {
+    END_RESULT.load(Ordering::SeqCst)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_Add(value: i32) -> i32 {
+    VALUE.fetch_add(value, Ordering::SeqCst) + value
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_SetValue(value: i32) -> i32 {
+    VALUE.store(value, Ordering::SeqCst);
+    0
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_GetValue() -> i32 {
+    VALUE.load(Ordering::SeqCst)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_Configure(value: i32) -> i32 {
+    value
+}
+
+// 解放されたハンドルの数
+static RELEASED: AtomicI32 = AtomicI32::new(0);
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_Create() -> i32 {
+    1
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn context_test_Release(_handle: i32) -> i32 {
+    RELEASED.fetch_add(1, Ordering::SeqCst);
+    0
+}
+
+// 初期化の状態は cffi_gen! ごとに1つのため、1つのテストで順に確認する
+#[test]
+fn test_context() -> anyhow::Result<(), DxLibError> {
+    assert_eq!(Context::check().unwrap_err(), ContextError::NotInitialized);
+    assert_eq!(ffi::Configure(3)?, 3);
+
+    // デバッグビルドでは初期化前の呼び出しをエラーにする
+    #[cfg(debug_assertions)]
+    {
+        let error = ffi::Add(1).unwrap_err();
+        assert!(matches!(
+            error,
+            DxLibError::Context("Add", ContextError::NotInitialized)
+        ));
+        assert_eq!(error.to_string(), "Add() called before init");
+        // #[not_result] の関数は -1 を返す
+        assert_eq!(ffi::GetValue(), -1);
+    }
+
+    // 初期化に失敗した場合は Context を返さず、再度初期化できる
+    INIT_RESULT.store(-1, Ordering::SeqCst);
+    assert!(Context::Init().is_err());
+    INIT_RESULT.store(0, Ordering::SeqCst);
+
+    // 初期化に成功すると Context を返し、#[context] の関数をメソッドとして呼び出せる
+    let mut context = Context::Init()?;
+    // Context は同時に1つのみ作成できる
+    let error = Context::Init().unwrap_err();
+    assert!(matches!(
+        error,
+        DxLibError::Context("Init", ContextError::AlreadyInitialized)
+    ));
+    assert_eq!(error.to_string(), "Init() called while already initialized");
+    context.SetValue(10)?;
+    assert_eq!(context.Add(5)?, 15);
+    assert_eq!(context.GetValue(), 15);
+    // 自由関数も初期化したスレッドからは呼び出せる
+    assert_eq!(ffi::Add(1)?, 16);
+    assert!(Context::check().is_ok());
+
+    // 初期化したスレッド以外からの呼び出しはエラーにする
+    std::thread::spawn(|| {
+        assert_eq!(Context::check().unwrap_err(), ContextError::OffMainThread);
+        #[cfg(debug_assertions)]
+        {
+            let error = ffi::Add(1).unwrap_err();
+            assert!(matches!(
+                error,
+                DxLibError::Context("Add", ContextError::OffMainThread)
+            ));
+            assert_eq!(error.to_string(), "Add() called off main thread");
+        }
+    })
+    .join()
+    .unwrap();
+    assert_eq!(context.GetValue(), 16);
+
+    // 終了後は初期化前と同じ扱いになる (終了の関数が失敗した場合も同じ)
+    END_RESULT.store(-1, Ordering::SeqCst);
+    assert!(context.End().is_err());
+    END_RESULT.store(0, Ordering::SeqCst);
+    assert_eq!(Context::check().unwrap_err(), ContextError::NotInitialized);
+    #[cfg(debug_assertions)]
+    assert!(matches!(
+        ffi::SetValue(0),
+        Err(DxLibError::Context(
+            "SetValue",
+            ContextError::NotInitialized
+        ))
+    ));
+    Context::Init()?.End()?;
+
+    // ハンドルは初期化済みの場合のみ Drop 時に解放され、終了後の Drop では解放関数を呼び出さない
+    let context = Context::Init()?;
+    drop(ffi::Create()?);
+    assert_eq!(RELEASED.load(Ordering::SeqCst), 1);
+    let handle = ffi::Create()?;
+    context.End()?;
+    drop(handle);
+    assert_eq!(RELEASED.load(Ordering::SeqCst), 1);
+
+    // 終了の関数を呼び出さずに Context を破棄した場合は初期化済みのまま残り、
+    // 自由関数の終了の関数で終了すると再度初期化できる
+    drop(Context::Init()?);
+    assert!(Context::check().is_ok());
+    assert!(Context::Init().is_err());
+    ffi::End()?;
+    assert_eq!(Context::check().unwrap_err(), ContextError::NotInitialized);
+    Context::Init()?.End()?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+// IntoDxArg,DxArgMutを実装した独自の型を引数に渡すテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+use dxlib_rs::dxlib_types::*;
+use dxlib_rs::utils::*;
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+
+// 0xRRGGBBとして渡す色
+#[derive(Debug, Clone, Copy)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl IntoDxArg for Color {
+    type Raw = u32;
+    fn into_dx_arg(self) -> anyhow::Result<DxArg<u32>> {
+        Ok(DxArg::new(
+            (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32,
+        ))
+    }
+}
+
+// C側で書き込まれる座標
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl DxArgMut for Point {
+    type Raw = *mut Point;
+    fn as_dx_arg_mut(&mut self) -> *mut Point {
+        self as *mut Point
+    }
+}
+
+// #[macro_export]したマクロは利用側のクレートのanyhow等を参照しない
+// (anyhowを別のモジュールで隠しても展開できる)
+mod exported_macros {
+    #[allow(dead_code)]
+    mod anyhow {}
+
+    #[repr(C)]
+    pub struct Size {
+        pub width: i32,
+        pub height: i32,
+    }
+    dxlib_rs::dx_arg_repr_c!(Size);
+
+    dxlib_rs::dx_enum! {
+        pub enum Direction {
+            Left = 0,
+            Right = 1,
+        }
+    }
+}
+
+mod ffi {
+    use super::*;
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::dxlib_error::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "dx_arg_test_"]
+        #![as_result_error_type = "DxLibError"]
+        #![call_error = "DxLibError::Call"]
+        fn ColorValue(color: Color) -> i32,
+        fn PathLength(path: &Path) -> i32,
+        fn PathBufLength(path: PathBuf) -> i32,
+        fn MovePoint(point: &mut Point, dx: i32, dy: i32) -> i32,
+        fn RectWidth(rect: &RECT) -> i32,
+        #[error_condition = "result != 0"]
+        fn Fail(color: Color) -> i32,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn dx_arg_test_ColorValue(color: u32) -> i32 {
+    color as i32
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn dx_arg_test_PathLength(path: *const std::os::raw::c_char) -> i32 {
+    unsafe { CStr::from_ptr(path).to_bytes().len() as i32 }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn dx_arg_test_PathBufLength(path: *const std::os::raw::c_char) -> i32 {
+    dx_arg_test_PathLength(path)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn dx_arg_test_MovePoint(point: *mut Point, dx: i32, dy: i32) -> i32 {
+    let point = unsafe { &mut *point };
+    point.x += dx;
+    point.y += dy;
+    0
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn dx_arg_test_RectWidth(rect: *const RECT) -> i32 {
+    let rect = unsafe { &*rect };
+    rect.right - rect.left
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn dx_arg_test_Fail(_color: u32) -> i32 {
+    1
+}
+
+#[test]
+fn test_dx_arg() {
+    let color = Color {
+        r: 0x12,
+        g: 0x34,
+        b: 0x56,
+    };
+    assert_eq!(ffi::ColorValue(color).unwrap(), 0x123456);
+
+    // Path,PathBufは文字列と同様に*const c_charとして渡される
+    let path = PathBuf::from("data/player.png");
+    assert_eq!(ffi::PathLength(&path).unwrap(), 15);
+    assert_eq!(ffi::PathBufLength(path).unwrap(), 15);
+
+    let mut point = Point::default();
+    ffi::MovePoint(&mut point, 3, -2).unwrap();
+    ffi::MovePoint(&mut point, 1, 1).unwrap();
+    assert_eq!((point.x, point.y), (4, -1));
+
+    // dxlib_typesの構造体は参照を*const Tとして渡せる
+    let rect = RECT {
+        left: 10,
+        top: 0,
+        right: 50,
+        bottom: 20,
+    };
+    assert_eq!(ffi::RectWidth(&rect).unwrap(), 40);
+
+    // 変換できない場合はエラーになる
+    assert!(ffi::PathLength(Path::new("a\0b")).is_err());
+    // エラー時の引数は型名で表示される
+    let error = ffi::Fail(color).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Fail(color = <Color>) failed with code 1"
+    );
+}
@@ -0,0 +1,60 @@
+// 動的ロード(#![dynamic_loader])のテスト
+// build.rsがビルドするDxLibのスタブ(libDxLib_x64.so)を実行時に読み込む
+#![cfg(not(windows))]
+#![allow(non_snake_case)]
+
+use dxlib_rs::dxlib_error::*;
+use dxlib_rs::dxlib_gen;
+use dxlib_rs::utils::*;
+
+dxlib_gen! {
+    #![dynamic_loader = "StubDxLib"]
+    "DxLib_x64",
+    fn DxLib_Init() -> i32,
+    fn DxLib_End() -> i32,
+    fn GetColor(red: i32, green: i32, blue: i32) -> i32,
+    fn DrawString(x: i32, y: i32, string: impl AsRef<str>, color: i32) -> i32,
+    // スタブに存在しない関数
+    fn NotExportedFunction(value: i32) -> i32,
+}
+
+#[test]
+fn test_dynamic_loader() {
+    let path = env!("DXLIB_STUB_DYLIB");
+
+    // 読み込み前の呼び出しはエラー
+    assert!(matches!(
+        GetColor(255, 255, 255),
+        Err(DxLibError::SymbolNotAvailable("dx_GetColor"))
+    ));
+
+    // 存在しないライブラリ
+    assert!(matches!(
+        StubDxLib::load("./not_exist/libDxLib_x64.so"),
+        Err(DxLibError::LoadError(..))
+    ));
+
+    // 見つからない関数がある場合はその一覧を返す
+    match StubDxLib::load(path) {
+        Err(DxLibError::MissingSymbols(missing)) => {
+            assert_eq!(missing, vec!["dx_NotExportedFunction"])
+        }
+        _ => panic!("expected DxLibError::MissingSymbols"),
+    }
+    assert!(StubDxLib::get().is_none());
+
+    let table = StubDxLib::load_partial(path).unwrap();
+    assert_eq!(table.missing_symbols(), vec!["dx_NotExportedFunction"]);
+
+    DxLib_Init().unwrap();
+    assert_eq!(GetColor(255, 128, 0).unwrap(), 0xff8000);
+    DrawString(0, 0, "hello world! こんにちは 世界!", 0xffffff).unwrap();
+    assert!(matches!(
+        NotExportedFunction(0),
+        Err(DxLibError::SymbolNotAvailable("dx_NotExportedFunction"))
+    ));
+    DxLib_End().unwrap();
+
+    // 二重に読み込むことはできない
+    assert!(StubDxLib::load_partial(path).is_err());
+}
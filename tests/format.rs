@@ -0,0 +1,34 @@
+// #[format]で生成される書式指定のマクロ(dx_printf!,draw_format_string!等)のテスト
+// スタブの書式指定の関数は、Cで書式化した文字列の長さを返す
+#![cfg(not(any(windows, feature = "record")))]
+
+mod common;
+
+use dxlib_rs::dxlib::*;
+use dxlib_rs::dxlib_error::*;
+use dxlib_rs::{draw_format_string, draw_format_string_to_handle, dx_printf};
+
+#[test]
+fn test_format_macros() -> Result<(), DxLibError> {
+    common::with_dxlib(|| {
+        let score = 120;
+        let text = format!("score: {} (100%)", score);
+        assert_eq!(
+            draw_format_string!(0, 0, GetColor(255, 255, 255)?, "score: {} (100%)", score)?,
+            text.len() as i32
+        );
+        assert_eq!(dx_printf!("fps: {:.1}", 59.94)?, "fps: 59.9".len() as i32);
+
+        // 文字列内の%は書式として解釈されずにそのまま渡される
+        let user_text = "%s%s%n%d";
+        assert_eq!(dx_printf!("{}", user_text)?, user_text.len() as i32);
+        assert_eq!(DrawFormatString(0, 0, 0, "%d%%")?, "%d%%".len() as i32);
+
+        assert_eq!(
+            draw_format_string_to_handle!(0, 16, 0, 1, "{}-{}", "a", "b")?,
+            3
+        );
+        assert!(draw_format_string_to_handle!(0, 16, 0, -1, "{}", "a").is_err());
+        Ok(())
+    })
+}
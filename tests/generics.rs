@@ -0,0 +1,50 @@
+// 型パラメータ、where句を使用した関数シグネチャのテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+use std::ffi::CStr;
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::utils::*;
+    use std::fmt::Debug;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "generics_test_"]
+        fn Length<S: AsRef<str> + Debug>(text: S) -> i32,
+        fn LengthRef<'a, S>(text: &'a S) -> i32
+        where
+            S: AsRef<str> + ?Sized,
+        fn Concat<A, B>(first: A, second: B) -> i32
+        where
+            A: AsRef<str>,
+            B: AsRef<str> + Clone,
+    }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn generics_test_Length(text: *const std::os::raw::c_char) -> i32 {
+    unsafe { CStr::from_ptr(text).to_bytes().len() as i32 }
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn generics_test_LengthRef(text: *const std::os::raw::c_char) -> i32 {
+    generics_test_Length(text)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn generics_test_Concat(
+    first: *const std::os::raw::c_char,
+    second: *const std::os::raw::c_char,
+) -> i32 {
+    generics_test_Length(first) + generics_test_Length(second)
+}
+
+#[test]
+fn test_generic_params() {
+    // impl AsRef<str> と同様に&str,String等を渡せる
+    assert_eq!(ffi::Length("abc").unwrap(), 3);
+    assert_eq!(ffi::Length(String::from("abcd")).unwrap(), 4);
+    assert_eq!(ffi::LengthRef(&String::from("ab")).unwrap(), 2);
+    assert_eq!(ffi::Concat("ab", String::from("cde")).unwrap(), 5);
+}
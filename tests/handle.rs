@@ -0,0 +1,59 @@
+// #[handle]で生成されるハンドル型(Drop時の解放、leak、into_raw)のテスト
+#![cfg(not(any(windows, feature = "record")))]
+
+mod common;
+
+use dxlib_rs::dxlib::*;
+use dxlib_rs::dxlib_error::*;
+
+#[test]
+fn test_graph_handle() -> Result<(), DxLibError> {
+    common::with_dxlib(|| {
+        let path = std::env::temp_dir().join("dxlib_rs_handle.png");
+        std::fs::write(&path, b"").unwrap();
+        let path = path.to_str().unwrap();
+
+        // Drop時にDeleteGraphで解放される
+        let base = GetGraphNum()?;
+        let graph = LoadGraph(path)?;
+        assert_eq!(GetGraphNum()?, base + 1);
+        DrawGraph(0, 0, &graph, false)?;
+        assert_eq!(GetGraphSize(&graph)?, (32, 32));
+        drop(graph);
+        assert_eq!(GetGraphNum()?, base);
+
+        // into_rawの場合は解放されず、生のハンドルとして扱える
+        let raw = LoadGraph(path)?.into_raw();
+        assert_eq!(GetGraphNum()?, base + 1);
+        DeleteGraph(raw, None)?;
+        assert_eq!(GetGraphNum()?, base);
+
+        // leakの場合は解放されない
+        let graph = LoadGraph(path)?;
+        let raw = graph.as_raw();
+        graph.leak();
+        assert_eq!(GetGraphNum()?, base + 1);
+        // from_rawで再び所有権を持つハンドルにできる
+        drop(GraphHandle::from_raw(raw));
+        assert_eq!(GetGraphNum()?, base);
+        Ok(())
+    })
+}
+
+#[test]
+fn test_file_handle() -> Result<(), DxLibError> {
+    let _lock = common::lock_dxlib();
+    let path = std::env::temp_dir().join("dxlib_rs_handle.txt");
+    std::fs::write(&path, b"first\nsecond\n").unwrap();
+
+    let file = FileRead_open(path.to_str().unwrap(), false)?;
+    assert_eq!(FileRead_gets(&file)?, "first");
+    let raw = file.as_raw();
+    drop(file);
+    // 閉じられたハンドルは使用できない
+    assert!(matches!(
+        FileRead_close(raw),
+        Err(DxLibError::InvalidHandle { .. })
+    ));
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// &mut String の引数(C側で書き換えられる文字列バッファ)のテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+use dxlib_rs::dxlib_error::*;
+use std::ffi::CStr;
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::dxlib_error::*;
+    use dxlib_rs::utils::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "in_out_test_"]
+        #![as_result_error_type = "DxLibError"]
+        #![string_error = "DxLibError::InvalidString"]
+        #![nul_error = "DxLibError::InteriorNul"]
+        fn Append(#[capacity = 8] buffer: &mut String, #[len_of(buffer)] size: i32) -> i32,
+        #[wide]
+        fn AppendW(#[capacity = 8] buffer: &mut String, #[len_of(buffer)] size: i32) -> i32,
+    }
+}
+
+// 文字列の末尾に、容量(size文字)に収まるだけ'!'を追加する
+#[unsafe(no_mangle)]
+extern "C" fn in_out_test_Append(buffer: *mut std::os::raw::c_char, size: i32) -> i32 {
+    let len = unsafe { CStr::from_ptr(buffer).to_bytes().len() };
+    for i in len..size as usize {
+        unsafe { *buffer.add(i) = b'!' as std::os::raw::c_char };
+    }
+    unsafe { *buffer.add(size as usize) = 0 };
+    len as i32
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn in_out_test_AppendW(buffer: *mut u16, size: i32) -> i32 {
+    let mut len = 0;
+    while unsafe { *buffer.add(len) } != 0 {
+        len += 1;
+    }
+    for i in len..size as usize {
+        unsafe { *buffer.add(i) = b'!' as u16 };
+    }
+    unsafe { *buffer.add(size as usize) = 0 };
+    len as i32
+}
+
+#[test]
+fn test_in_out_string() {
+    // C側で書き換えられた内容が元のStringに反映される
+    let mut text = String::from("abc");
+    assert_eq!(ffi::Append(&mut text).unwrap(), 3);
+    assert_eq!(text, "abc!!!!!");
+
+    // String::capacity()が#[capacity]より大きい場合はその容量まで書き込める
+    let mut text = String::with_capacity(12);
+    text.push_str("abc");
+    ffi::Append(&mut text).unwrap();
+    assert_eq!(text, "abc!!!!!!!!!");
+
+    // 容量より長い文字列はそのまま渡される
+    let mut text = String::from("0123456789");
+    ffi::Append(&mut text).unwrap();
+    assert_eq!(text, "0123456789");
+
+    let mut text = String::from("あい");
+    ffi::AppendW(&mut text).unwrap();
+    assert_eq!(text, "あい!!!!!!");
+
+    // 途中にNUL文字が含まれている場合はエラーになり、元のStringは変更されない
+    let mut text = String::from("a\0b");
+    assert!(matches!(
+        ffi::Append(&mut text),
+        Err(DxLibError::InteriorNul("Append", "buffer", 1))
+    ));
+    assert_eq!(text, "a\0b");
+}
@@ -0,0 +1,76 @@
+// 生ポインタの引数を持つ関数(unsafe fn)と、#[len_of]で生成されるスライス版の関数のテスト
+// 呼び出されるC関数はこのテスト内で定義する
+#![allow(non_snake_case)]
+
+mod common;
+
+use dxlib_rs::dxlib_error::*;
+
+mod ffi {
+    use dxlib_rs::cffi_gen;
+    use dxlib_rs::dxlib_error::*;
+
+    cffi_gen! {
+        #![func_name_top_prefix = "raw_pointer_test_"]
+        #![as_result_error_type = "DxLibError"]
+        fn Fill(
+            buffer: *mut std::os::raw::c_void,
+            #[len_of(buffer)] size: i32,
+            value: u8,
+        ) -> i32,
+        fn Sum(values: *const i32, #[len_of(values)] count: usize) -> i32,
+    }
+}
+
+// size バイトを value で埋め、埋めたバイト数を返す
+#[unsafe(no_mangle)]
+extern "C" fn raw_pointer_test_Fill(
+    buffer: *mut std::os::raw::c_void,
+    size: i32,
+    value: u8,
+) -> i32 {
+    unsafe { std::ptr::write_bytes(buffer.cast::<u8>(), value, size as usize) };
+    size
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn raw_pointer_test_Sum(values: *const i32, count: usize) -> i32 {
+    unsafe { std::slice::from_raw_parts(values, count) }
+        .iter()
+        .sum()
+}
+
+#[test]
+fn test_raw_pointer() -> Result<(), DxLibError> {
+    let mut buffer = [0u8; 4];
+    // 生ポインタの関数は unsafe fn として生成され、長さはそのまま渡す
+    assert_eq!(unsafe { ffi::Fill(buffer.as_mut_ptr().cast(), 2, 7)? }, 2);
+    assert_eq!(buffer, [7, 7, 0, 0]);
+
+    // スライス版の関数はスライスの長さを渡す
+    assert_eq!(ffi::Fill_slice(&mut buffer, 9)?, 4);
+    assert_eq!(buffer, [9; 4]);
+    assert_eq!(ffi::Fill_slice(&mut buffer[1..3], 1)?, 2);
+    assert_eq!(buffer, [9, 1, 1, 9]);
+
+    assert_eq!(ffi::Sum_slice(&[1, 2, 3])?, 6);
+    assert_eq!(ffi::Sum_slice(&[])?, 0);
+    Ok(())
+}
+
+// スタブの FileRead_read で読み込む
+#[cfg(not(any(windows, feature = "record")))]
+#[test]
+fn test_file_read_slice() -> Result<(), DxLibError> {
+    use dxlib_rs::dxlib::*;
+
+    let _lock = common::lock_dxlib();
+    let path = std::env::temp_dir().join("dxlib_rs_raw_pointer.txt");
+    std::fs::write(&path, b"abcdef").unwrap();
+    let file = FileRead_open(path.to_str().unwrap(), false)?;
+    let mut buffer = [0u8; 4];
+    FileRead_read_slice(&mut buffer, &file)?;
+    assert_eq!(&buffer, b"abcd");
+    assert_eq!(FileRead_tell(&file)?, 4);
+    Ok(())
+}
@@ -0,0 +1,108 @@
+// recordフィーチャーで関数呼び出しを記録するテスト
+// cargo test --features record --test record
+#![cfg(feature = "record")]
+
+use dxlib_rs::dxlib::*;
+use dxlib_rs::dxlib_constants::*;
+use dxlib_rs::dxlib_enums::*;
+use dxlib_rs::dxlib_error::*;
+use dxlib_rs::record::*;
+
+// テスト対象のゲームループ (ESCキーが押されるまで描画する)
+fn run_game() -> Result<i32, DxLibError> {
+    let graph = LoadGraph("player.png")?;
+    let sound = LoadSoundMem("jump.wav")?;
+    let mut frame = 0;
+    while ProcessMessage()? == 0 {
+        if CheckHitKey(KEY_INPUT_ESCAPE)? {
+            break;
+        }
+        DrawGraph(frame * 10, 0, &graph, true)?;
+        DrawString(0, 0, format!("frame {}", frame), GetColor(255, 255, 255)?)?;
+        if frame == 1 {
+            PlaySoundMem(&sound, PlayType::Back, false)?;
+        }
+        ScreenFlip()?;
+        frame += 1;
+    }
+    Ok(frame)
+}
+
+#[test]
+fn test_record_calls() -> Result<(), DxLibError> {
+    let recorder = Recorder::start();
+    recorder.on("LoadGraph").returns(100);
+    recorder.on("LoadSoundMem").returns(200);
+    recorder.on("GetColor").returns(0xffffff);
+    recorder
+        .on("CheckHitKey")
+        .arg("key_code", KEY_INPUT_ESCAPE)
+        .frame(2)
+        .returns(TRUE);
+
+    assert_eq!(run_game()?, 2);
+    assert_eq!(recorder.frame(), 2);
+    recorder.assert_snapshot(
+        r#"
+        LoadGraph(file_name = "player.png")
+        LoadSoundMem(file_name = "jump.wav")
+        ProcessMessage()
+        CheckHitKey(key_code = 1)
+        DrawGraph(x = 0, y = 0, gr_handle = 100, trans_flag = 1)
+        GetColor(red = 255, green = 255, blue = 255)
+        DrawString(x = 0, y = 0, string = "frame 0", color = 16777215)
+        ScreenFlip()
+        ProcessMessage()
+        CheckHitKey(key_code = 1)
+        DrawGraph(x = 10, y = 0, gr_handle = 100, trans_flag = 1)
+        GetColor(red = 255, green = 255, blue = 255)
+        DrawString(x = 0, y = 0, string = "frame 1", color = 16777215)
+        PlaySoundMem(sound_handle = 200, play_type = 1, top_position_flag = 0)
+        ScreenFlip()
+        ProcessMessage()
+        CheckHitKey(key_code = 1)
+        DeleteSoundMem(sound_handle = 200, log_out_flag = 0)
+        DeleteGraph(gr_handle = 100, log_out_flag = 0)
+        "#,
+    );
+
+    let draws = recorder.calls_of("DrawString");
+    assert_eq!(draws[1].frame, 1);
+    assert_eq!(draws[1].arg("string"), Some("\"frame 1\""));
+    Ok(())
+}
+
+#[test]
+fn test_record_errors() {
+    let recorder = Recorder::start();
+    // 設定した戻り値がエラー条件の場合はエラーになる
+    recorder
+        .on("LoadGraph")
+        .arg("file_name", "missing.png")
+        .returns(-1);
+    let error = LoadGraph("missing.png").unwrap_err();
+    assert!(matches!(error, DxLibError::LoadFailed { code: -1, .. }));
+    assert!(LoadGraph("player.png").is_ok());
+
+    // 記録を終了した後は記録されない
+    drop(recorder);
+    let recorder = Recorder::start();
+    {
+        let _inner = Recorder::start();
+        ScreenFlip().unwrap();
+    }
+    assert!(recorder.calls().is_empty());
+}
+
+// #[out],#[out_string]の値は設定できず、既定値(0、空文字列)になる
+// (戻り値のみ設定でき、呼び出しは記録される)
+#[test]
+fn test_record_out_params() -> Result<(), DxLibError> {
+    let recorder = Recorder::start();
+    assert_eq!(GetMousePoint()?, (0, 0));
+    let file = FileRead_open("data.txt", false)?;
+    assert_eq!(FileRead_gets(&file)?, "");
+    assert_eq!(recorder.calls_of("GetMousePoint").len(), 1);
+    assert_eq!(recorder.calls_of("FileRead_gets").len(), 1);
+    Ok(())
+}
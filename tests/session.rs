@@ -0,0 +1,159 @@
+// DxLibBuilder(DxLib_Init前の設定と初期化)、DxLibSession(Drop時のDxLib_End)のテスト
+mod common;
+
+use dxlib_rs::dxlib_constants::*;
+use dxlib_rs::dxlib_error::*;
+use dxlib_rs::session::*;
+
+// 不正な設定はDxLibの関数を呼び出す前にエラーになる
+#[test]
+fn test_validate() {
+    let invalid = |builder: DxLibBuilder| match builder.build() {
+        Err(DxLibError::InvalidSetting(name, _)) => name,
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    };
+    assert_eq!(
+        invalid(DxLibBuilder::new().graph_mode(0, 480, 32)),
+        "graph_mode"
+    );
+    assert_eq!(
+        invalid(DxLibBuilder::new().graph_mode(640, 480, 24)),
+        "graph_mode"
+    );
+    assert_eq!(
+        invalid(DxLibBuilder::new().char_code_format(12345)),
+        "char_code_format"
+    );
+    assert_eq!(
+        invalid(DxLibBuilder::new().window_title("a\0b")),
+        "window_title"
+    );
+    assert_eq!(
+        invalid(DxLibBuilder::new().log(false).log_directory("logs")),
+        "log_directory"
+    );
+    let error = DxLibBuilder::new()
+        .graph_mode(640, 480, 24)
+        .validate()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Invalid graph_mode: color_bit_depth must be 16 or 32, got 24"
+    );
+    assert!(
+        DxLibBuilder::new()
+            .char_code_format(DX_CHARCODEFORMAT_UTF8)
+            .graph_mode(800, 600, 16)
+            .log(true)
+            .log_directory("logs")
+            .validate()
+            .is_ok()
+    );
+}
+
+// スタブのDxLib_Initは初期化済みの場合に失敗するため、再度buildできることでDxLib_Endが呼ばれたことを確認する
+#[cfg(not(any(windows, feature = "record")))]
+#[test]
+fn test_session() -> Result<(), DxLibError> {
+    use dxlib_rs::charcode::char_code_format;
+    use dxlib_rs::dxlib::*;
+
+    let _lock = common::lock_dxlib();
+    let builder = DxLibBuilder::new()
+        .char_code_format(DX_CHARCODEFORMAT_UTF8)
+        .window_title("session テスト")
+        .window_mode(WindowMode::Windowed)
+        .graph_mode(800, 600, 16)
+        .vsync(false);
+    let mut session = builder.build()?;
+    assert_eq!(session.GetScreenState()?, (800, 600, 16));
+    session.SetDrawBlendMode(dxlib_rs::dxlib_enums::BlendMode::NoBlend, 255)?;
+    // 二重の初期化はエラーになる
+    assert!(matches!(
+        builder.build(),
+        Err(DxLibError::AlreadyInitialized)
+    ));
+    drop(session);
+
+    // ?で早期リターンした場合もDxLib_Endが呼ばれる
+    let early_return = || -> Result<(), DxLibError> {
+        let session = builder.build()?;
+        let _graph = session.LoadGraph("not_found.png")?;
+        Ok(())
+    };
+    assert!(matches!(early_return(), Err(DxLibError::LoadFailed { .. })));
+
+    // endで明示的に終了した場合はDropでDxLib_Endを呼ばない
+    builder.build()?.end()?;
+    assert!(DxLib_End().is_err());
+
+    // dxlib::DxLib_Initで初期化済みの場合もエラーになる
+    DxLib_Init()?;
+    assert!(matches!(
+        builder.build(),
+        Err(DxLibError::AlreadyInitialized)
+    ));
+    DxLib_End()?;
+    builder.build()?.end()?;
+
+    // DxLib_Endに失敗した場合も初期化前の状態に戻り、再度buildできる
+    let session = builder.build()?;
+    DxLib_End()?;
+    assert!(session.end().is_err());
+    let session = builder.build()?;
+    DxLib_End()?;
+    drop(session);
+    builder.build()?.end()?;
+
+    // DxContextの作成に失敗した場合は、文字コードの設定を元に戻す
+    let context = DxContext::DxLib_Init()?;
+    DxLib_End()?;
+    let previous = char_code_format();
+    assert!(matches!(
+        builder
+            .clone()
+            .char_code_format(DX_CHARCODEFORMAT_SHIFTJIS)
+            .build(),
+        Err(DxLibError::AlreadyInitialized)
+    ));
+    assert_eq!(char_code_format(), previous);
+    assert!(context.DxLib_End().is_err());
+    builder.build()?.end()?;
+    Ok(())
+}
+
+// 設定関数はDxLib_Initの前に決まった順序で呼び出される
+#[cfg(feature = "record")]
+#[test]
+fn test_session_call_order() -> Result<(), DxLibError> {
+    use dxlib_rs::record::*;
+
+    let recorder = Recorder::start();
+    let session = DxLibBuilder::new()
+        .vsync(true)
+        .async_load(true)
+        .window_title("title")
+        .graph_mode(1280, 720, 32)
+        .window_mode(WindowMode::Fullscreen)
+        .log_directory("logs")
+        .log(true)
+        .char_code_format(DX_CHARCODEFORMAT_UTF8)
+        .build()?;
+    drop(session);
+    recorder.assert_snapshot(
+        r#"
+        SetUseCharCodeFormat(char_code_format = 65001)
+        SetOutApplicationLogValidFlag(flag = 1)
+        SetApplicationLogSaveDirectory(dir_path = "logs")
+        ChangeWindowMode(flag = 0)
+        SetGraphMode(screen_size_x = 1280, screen_size_y = 720, color_bit_depth = 32, refresh_rate = 60)
+        SetMainWindowText(window_text = "title")
+        SetWaitVSyncFlag(flag = 1)
+        SetUseASyncLoadFlag(flag = 1)
+        DxLib_Init()
+        SetDrawScreen(draw_screen = -2)
+        DxLib_End()
+        "#,
+    );
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// traceフィーチャーで呼び出しごとにtracingのイベントが出力されるテスト
+// cargo test --features trace --test trace
+#![cfg(all(feature = "trace", not(any(windows, feature = "record"))))]
+
+mod common;
+
+use dxlib_rs::dxlib::*;
+use dxlib_rs::dxlib_error::*;
+use dxlib_rs::utils::tracing;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// イベントのフィールド名と値
+type Fields = Vec<(String, String)>;
+
+// 出力されたイベントのフィールドを記録する
+#[derive(Clone, Default)]
+struct EventCollector {
+    events: Arc<Mutex<Vec<Fields>>>,
+}
+
+struct FieldVisitor(Fields);
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+}
+
+impl Subscriber for EventCollector {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor(Vec::new());
+        event.record(&mut visitor);
+        self.events.lock().unwrap().push(visitor.0);
+    }
+    fn enter(&self, _span: &Id) {}
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn test_trace_events() -> Result<(), DxLibError> {
+    common::with_dxlib(|| {
+        let collector = EventCollector::default();
+        let events = collector.events.clone();
+        tracing::subscriber::with_default(collector, || -> Result<(), DxLibError> {
+            let color = GetColor(1, 2, 3)?;
+            DrawString(0, 10, "hello", color)?;
+            // #[trace(skip)]の関数は出力されない
+            let path = std::env::temp_dir().join("dxlib_rs_trace.png");
+            std::fs::write(&path, b"").unwrap();
+            let graph = LoadGraph(path.to_str().unwrap())?;
+            DrawGraph(0, 0, &graph, true)?;
+            Ok(())
+        })?;
+
+        let events = events.lock().unwrap();
+        let functions: Vec<&str> = events.iter().map(|fields| fields[0].1.as_str()).collect();
+        assert_eq!(
+            functions,
+            ["GetColor", "DrawString", "LoadGraph", "DeleteGraph"]
+        );
+
+        // 引数は文字列の場合は変換前の文字列、戻り値はResultとして出力される
+        let draw_string = &events[1];
+        let field = |name: &str| {
+            draw_string
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(field("x"), Some("0"));
+        assert_eq!(field("y"), Some("10"));
+        assert_eq!(field("string"), Some("\"hello\""));
+        assert_eq!(field("color"), Some("66051"));
+        assert_eq!(field("result"), Some("Ok(0)"));
+        assert!(field("elapsed").is_some());
+
+        // bool、Option<T>の引数はCの値に変換する前の値が出力される
+        let delete_graph = &events[3];
+        assert!(delete_graph.contains(&("log_out_flag".to_string(), "None".to_string())));
+        Ok(())
+    })
+}
@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn SetCallback(#[callback] callback: Option<Box<dyn FnMut(i32) -> i32>>) -> i32,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use dxlib_rs::cffi_gen;
+use dxlib_rs::utils::*;
+
+cffi_gen! {
+    #![func_name_top_prefix = "ui_"]
+    #![context = "Context"]
+    #[role = "init"]
+    fn Init() -> i32,
+    #[context]
+    fn Draw(x: i32) -> i32,
+}
+
+fn main() {
+    let context = Context::Init().unwrap();
+    // Context は初期化したスレッド以外へ渡せない
+    std::thread::spawn(move || {
+        let _ = context.Draw(0);
+    });
+}
@@ -0,0 +1,9 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    // #![context] を指定していない
+    #[context]
+    fn Draw(x: i32) -> i32,
+}
+
+fn main() {}
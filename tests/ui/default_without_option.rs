@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn SetDrawScreen(#[default = "0"] draw_screen: i32) -> i32,
+}
+
+fn main() {}
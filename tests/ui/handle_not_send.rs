@@ -0,0 +1,16 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #![func_name_top_prefix = "ui_"]
+    #[handle(type = Handle, drop = "Delete")]
+    fn Create() -> i32,
+    fn Delete(handle: i32) -> i32,
+}
+
+fn main() {
+    let handle = Handle::from_raw(1);
+    // ハンドルは初期化したスレッド以外へ渡せない
+    std::thread::spawn(move || {
+        drop(handle);
+    });
+}
@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn SetDrawScreen(#[default = "DX_SCREEN_BACK +"] draw_screen: Option<i32>) -> i32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #[error_condition = "result =="]
+    fn WaitKey() -> i32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #[format(macro = "draw_number")]
+    fn DrawNumber(x: i32, y: i32, number: i32, ...) -> i32,
+}
+
+fn main() {}
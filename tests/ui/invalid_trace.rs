@@ -0,0 +1,9 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #![trace_feature = "trace"]
+    #[trace(hot)]
+    fn DrawGraph(x: i32, y: i32, gr_handle: i32, trans_flag: i32) -> i32,
+}
+
+fn main() {}
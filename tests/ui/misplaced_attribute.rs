@@ -0,0 +1,8 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #[out]
+    fn GetMousePoint(x_buf: i32) -> i32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn ScreenFlip(),
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn DrawPoint((x, y): (i32, i32)) -> i32,
+}
+
+fn main() {}
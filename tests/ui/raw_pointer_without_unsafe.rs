@@ -0,0 +1,13 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #![func_name_top_prefix = "ui_"]
+    fn Fill(buffer: *mut u8, #[len_of(buffer)] size: i32) -> i32,
+}
+
+fn main() {
+    let mut buffer = [0u8; 4];
+    // 生ポインタの引数を持つ関数は unsafe ブロック内でのみ呼び出せる
+    let _ = Fill(buffer.as_mut_ptr(), 4);
+    let _ = Fill_slice(&mut buffer);
+}
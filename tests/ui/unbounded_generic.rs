@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn DrawString<S>(x: i32, y: i32, string: S) -> i32,
+}
+
+fn main() {}
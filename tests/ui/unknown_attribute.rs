@@ -0,0 +1,8 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    #[error_condtion = "result == 0"]
+    fn ProcessMessage() -> i32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn DrawString(x: i32, y: i32, string: impl std::fmt::Debug) -> i32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use dxlib_rs::cffi_gen;
+
+cffi_gen! {
+    fn printfDx(format_string: impl AsRef<str>, ...) -> i32,
+}
+
+fn main() {}